@@ -0,0 +1,15 @@
+-- Network metrics time series
+-- Raw samples are written by the sampler task and rolled up into hourly and
+-- daily buckets; each resolution lives in the same table keyed by `resolution`.
+CREATE TABLE IF NOT EXISTS network_metrics (
+    metric VARCHAR(50) NOT NULL,
+    resolution VARCHAR(10) NOT NULL,
+    bucket TIMESTAMP WITH TIME ZONE NOT NULL,
+    avg DOUBLE PRECISION NOT NULL,
+    min DOUBLE PRECISION NOT NULL,
+    max DOUBLE PRECISION NOT NULL,
+    samples INTEGER NOT NULL DEFAULT 1,
+    PRIMARY KEY (metric, resolution, bucket)
+);
+
+CREATE INDEX idx_network_metrics_bucket ON network_metrics(resolution, bucket);
@@ -0,0 +1,37 @@
+-- Name search index
+-- One row per searchable name: token symbols/names, address labels, program
+-- names and SNS domains. Trigram indexes serve both fuzzy and prefix matches.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE TABLE IF NOT EXISTS search_entries (
+    kind VARCHAR(20) NOT NULL,
+    address VARCHAR(64) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    symbol VARCHAR(32),
+    popularity DOUBLE PRECISION NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (kind, address)
+);
+
+CREATE INDEX idx_search_entries_name ON search_entries USING GIN (lower(name) gin_trgm_ops);
+CREATE INDEX idx_search_entries_symbol ON search_entries USING GIN (lower(symbol) gin_trgm_ops);
+
+INSERT INTO search_entries (kind, address, name, symbol, popularity) VALUES
+    ('token', 'So11111111111111111111111111111111111111112', 'Wrapped SOL', 'SOL', 100),
+    ('token', 'EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v', 'USD Coin', 'USDC', 95),
+    ('token', 'Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB', 'USDT', 'USDT', 90),
+    ('token', 'JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN', 'Jupiter', 'JUP', 80),
+    ('token', 'DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263', 'Bonk', 'Bonk', 70),
+    ('program', '11111111111111111111111111111111', 'System Program', NULL, 100),
+    ('program', 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA', 'Token Program', NULL, 100),
+    ('program', 'TokenzQdBNbLqP5VeNSNFo9ucLgEtudsUjDwMBHyuRWsK', 'Token-2022 Program', NULL, 60),
+    ('program', 'ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL', 'Associated Token Account Program', NULL, 90),
+    ('program', 'Vote111111111111111111111111111111111111111', 'Vote Program', NULL, 90),
+    ('program', 'Stake11111111111111111111111111111111111111', 'Stake Program', NULL, 70),
+    ('program', 'ComputeBudget111111111111111111111111111111', 'Compute Budget Program', NULL, 80),
+    ('program', 'MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr', 'Memo Program', NULL, 50),
+    ('program', 'metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s', 'Metaplex Token Metadata', NULL, 60),
+    ('program', 'JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QUaFovYUrnh', 'Jupiter Aggregator v6', NULL, 85),
+    ('program', '675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8', 'Raydium AMM v4', NULL, 75),
+    ('program', 'whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc', 'Orca Whirlpools', NULL, 70)
+ON CONFLICT (kind, address) DO NOTHING;
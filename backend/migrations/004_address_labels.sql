@@ -0,0 +1,21 @@
+-- Address labels
+-- Curated labels (exchanges, programs, protocols) are public and managed by
+-- admins; user labels are private to the user who set them and take
+-- precedence over curated ones in that user's responses.
+CREATE TABLE IF NOT EXISTS address_labels (
+    address VARCHAR(64) PRIMARY KEY,
+    label VARCHAR(255) NOT NULL,
+    category VARCHAR(50) NOT NULL DEFAULT 'other',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_address_labels_category ON address_labels(category);
+
+CREATE TABLE IF NOT EXISTS user_address_labels (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    address VARCHAR(64) NOT NULL,
+    label VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, address)
+);
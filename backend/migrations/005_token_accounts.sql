@@ -0,0 +1,19 @@
+-- Token holder index
+-- Token accounts are snapshotted per mint by the holder indexer; each run
+-- replaces a mint's rows and records the resulting holder count.
+CREATE TABLE IF NOT EXISTS token_accounts (
+    address VARCHAR(44) PRIMARY KEY,
+    mint VARCHAR(44) NOT NULL,
+    owner VARCHAR(44) NOT NULL,
+    amount NUMERIC(20, 0) NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_token_accounts_mint ON token_accounts(mint, amount DESC);
+CREATE INDEX idx_token_accounts_owner ON token_accounts(owner);
+
+CREATE TABLE IF NOT EXISTS token_holder_counts (
+    mint VARCHAR(44) PRIMARY KEY,
+    holders BIGINT NOT NULL,
+    indexed_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
@@ -0,0 +1,16 @@
+-- Token prices
+-- The latest aggregated price per mint, plus every recorded price for the
+-- 24h change.
+CREATE TABLE IF NOT EXISTS token_prices (
+    mint VARCHAR(44) PRIMARY KEY,
+    price DOUBLE PRECISION NOT NULL,
+    sources INTEGER NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS token_price_history (
+    mint VARCHAR(44) NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    recorded_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (mint, recorded_at)
+);
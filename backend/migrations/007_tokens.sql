@@ -0,0 +1,37 @@
+-- Token listing
+-- One row per known mint. Metadata is written when a token is first
+-- resolved; prices, market caps and holder counts are refreshed by the
+-- token list job.
+CREATE TABLE IF NOT EXISTS tokens (
+    mint VARCHAR(44) PRIMARY KEY,
+    symbol VARCHAR(32),
+    name VARCHAR(255),
+    program VARCHAR(44) NOT NULL DEFAULT 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA',
+    decimals SMALLINT NOT NULL DEFAULT 0,
+    supply NUMERIC(20, 0) NOT NULL DEFAULT 0,
+    logo_uri TEXT,
+    verified BOOLEAN NOT NULL DEFAULT false,
+    price DOUBLE PRECISION,
+    price_change_24h DOUBLE PRECISION,
+    volume_24h DOUBLE PRECISION NOT NULL DEFAULT 0,
+    liquidity DOUBLE PRECISION NOT NULL DEFAULT 0,
+    market_cap DOUBLE PRECISION,
+    holders BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_tokens_volume ON tokens(volume_24h DESC);
+CREATE INDEX idx_tokens_market_cap ON tokens(market_cap DESC NULLS LAST);
+CREATE INDEX idx_tokens_price ON tokens(price DESC NULLS LAST);
+CREATE INDEX idx_tokens_price_change ON tokens(price_change_24h DESC NULLS LAST);
+CREATE INDEX idx_tokens_holders ON tokens(holders DESC NULLS LAST);
+CREATE INDEX idx_tokens_liquidity ON tokens(liquidity);
+
+INSERT INTO tokens (mint, symbol, name, decimals, verified) VALUES
+    ('So11111111111111111111111111111111111111112', 'SOL', 'Wrapped SOL', 9, true),
+    ('EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v', 'USDC', 'USD Coin', 6, true),
+    ('Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB', 'USDT', 'USDT', 6, true),
+    ('JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN', 'JUP', 'Jupiter', 6, true),
+    ('DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263', 'Bonk', 'Bonk', 5, true)
+ON CONFLICT (mint) DO NOTHING;
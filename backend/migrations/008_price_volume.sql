@@ -0,0 +1,3 @@
+-- Price ticks carry the USD volume traded since the previous tick, so candles
+-- can be built from the same rows.
+ALTER TABLE token_price_history ADD COLUMN IF NOT EXISTS volume DOUBLE PRECISION NOT NULL DEFAULT 0;
@@ -0,0 +1,32 @@
+-- Token transfer index
+-- One row per SPL Token / Token-2022 transfer, mint or burn instruction,
+-- top-level or inner (inner_index -1 marks a top-level instruction).
+CREATE TABLE IF NOT EXISTS token_transfers (
+    signature VARCHAR(88) NOT NULL,
+    instruction_index SMALLINT NOT NULL,
+    inner_index SMALLINT NOT NULL,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMP WITH TIME ZONE,
+    kind VARCHAR(10) NOT NULL,
+    mint VARCHAR(44) NOT NULL,
+    program VARCHAR(44) NOT NULL,
+    from_account VARCHAR(44),
+    from_owner VARCHAR(44),
+    to_account VARCHAR(44),
+    to_owner VARCHAR(44),
+    amount NUMERIC(20, 0) NOT NULL,
+    decimals SMALLINT NOT NULL,
+    ui_amount DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (signature, instruction_index, inner_index)
+);
+
+CREATE INDEX idx_token_transfers_mint ON token_transfers(mint, slot DESC);
+CREATE INDEX idx_token_transfers_from_owner ON token_transfers(from_owner, slot DESC);
+CREATE INDEX idx_token_transfers_to_owner ON token_transfers(to_owner, slot DESC);
+
+-- Last slot processed by each block-following indexer.
+CREATE TABLE IF NOT EXISTS indexer_cursors (
+    name VARCHAR(50) PRIMARY KEY,
+    slot BIGINT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
@@ -0,0 +1,8 @@
+-- Token risk reports
+-- Flags and score of each listed token's last risk report.
+ALTER TABLE tokens ADD COLUMN IF NOT EXISTS risk_flags TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE tokens ADD COLUMN IF NOT EXISTS risk_score SMALLINT;
+ALTER TABLE tokens ADD COLUMN IF NOT EXISTS risk_checked_at TIMESTAMP WITH TIME ZONE;
+
+-- Look-alike checks compare symbols case-insensitively.
+CREATE INDEX IF NOT EXISTS idx_tokens_symbol ON tokens(UPPER(symbol));
@@ -0,0 +1,21 @@
+-- DEX markets
+-- One row per indexed pool, oriented so the quote side is the stronger
+-- quote currency. Reserves are in UI units and liquidity in USD.
+CREATE TABLE IF NOT EXISTS markets (
+    address VARCHAR(44) PRIMARY KEY,
+    dex VARCHAR(20) NOT NULL,
+    base_mint VARCHAR(44) NOT NULL,
+    quote_mint VARCHAR(44) NOT NULL,
+    base_reserve DOUBLE PRECISION NOT NULL,
+    quote_reserve DOUBLE PRECISION NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    liquidity DOUBLE PRECISION NOT NULL DEFAULT 0,
+    price_change_24h DOUBLE PRECISION,
+    volume_24h DOUBLE PRECISION NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_markets_liquidity ON markets(liquidity DESC);
+CREATE INDEX idx_markets_volume ON markets(volume_24h DESC);
+CREATE INDEX idx_markets_base_mint ON markets(base_mint);
+CREATE INDEX idx_markets_quote_mint ON markets(quote_mint);
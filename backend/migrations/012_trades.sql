@@ -0,0 +1,25 @@
+-- DEX trades
+-- Vaults of each market, to match swaps against.
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS base_vault VARCHAR(44);
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS quote_vault VARCHAR(44);
+
+-- One row per swap through an indexed market, netted per transaction.
+-- Amounts are in UI units and price is quote per base, in the market's
+-- orientation.
+CREATE TABLE IF NOT EXISTS trades (
+    signature VARCHAR(88) NOT NULL,
+    market VARCHAR(44) NOT NULL,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMP WITH TIME ZONE,
+    side VARCHAR(4) NOT NULL,
+    trader VARCHAR(44) NOT NULL,
+    base_amount DOUBLE PRECISION NOT NULL,
+    quote_amount DOUBLE PRECISION NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    volume_usd DOUBLE PRECISION,
+    PRIMARY KEY (signature, market)
+);
+
+CREATE INDEX idx_trades_market ON trades(market, slot DESC);
+CREATE INDEX idx_trades_block_time ON trades(block_time);
+CREATE INDEX idx_trades_trader ON trades(trader, slot DESC);
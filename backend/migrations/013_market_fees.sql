@@ -0,0 +1,3 @@
+-- Market fees
+-- Swap fee of each pool, as a share of the input amount.
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS fee_rate DOUBLE PRECISION NOT NULL DEFAULT 0;
@@ -0,0 +1,44 @@
+-- NFTs
+-- One row per NFT seen: Metaplex NFTs when an owner or the NFT itself is
+-- viewed, compressed NFTs (keyed by asset id) as Bubblegum instructions are
+-- indexed.
+CREATE TABLE IF NOT EXISTS nfts (
+    mint VARCHAR(44) PRIMARY KEY,
+    name VARCHAR(255) NOT NULL DEFAULT '',
+    symbol VARCHAR(32) NOT NULL DEFAULT '',
+    uri TEXT NOT NULL DEFAULT '',
+    image TEXT,
+    collection VARCHAR(44),
+    collection_verified BOOLEAN NOT NULL DEFAULT false,
+    update_authority VARCHAR(44),
+    seller_fee_basis_points INTEGER NOT NULL DEFAULT 0,
+    -- JSON array of {address, verified, share}.
+    creators TEXT NOT NULL DEFAULT '[]',
+    owner VARCHAR(44),
+    delegate VARCHAR(44),
+    compressed BOOLEAN NOT NULL DEFAULT false,
+    tree VARCHAR(44),
+    leaf_index BIGINT,
+    burned BOOLEAN NOT NULL DEFAULT false,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_nfts_owner ON nfts(owner);
+CREATE INDEX idx_nfts_collection ON nfts(collection);
+
+-- Ownership changes of compressed NFTs, from indexed Bubblegum instructions.
+-- Metaplex NFTs take theirs from token_transfers.
+CREATE TABLE IF NOT EXISTS nft_events (
+    signature VARCHAR(88) NOT NULL,
+    asset VARCHAR(44) NOT NULL,
+    -- Position of the instruction in the transaction's execution order.
+    position SMALLINT NOT NULL,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMP WITH TIME ZONE,
+    kind VARCHAR(10) NOT NULL,
+    from_owner VARCHAR(44),
+    to_owner VARCHAR(44),
+    PRIMARY KEY (signature, asset, position)
+);
+
+CREATE INDEX idx_nft_events_asset ON nft_events(asset, slot DESC);
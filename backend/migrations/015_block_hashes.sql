@@ -0,0 +1,6 @@
+-- Blockhash -> slot index
+-- Filled by the block follower, so blocks can be looked up by hash.
+CREATE TABLE IF NOT EXISTS block_hashes (
+    blockhash VARCHAR(44) PRIMARY KEY,
+    slot BIGINT NOT NULL
+);
@@ -0,0 +1,6 @@
+-- Token age
+-- Oldest transaction the risk scorer has found for each listed token, and
+-- whether it was the very first one, so established tokens aren't paged
+-- through again on every pass.
+ALTER TABLE tokens ADD COLUMN IF NOT EXISTS first_activity TIMESTAMP WITH TIME ZONE;
+ALTER TABLE tokens ADD COLUMN IF NOT EXISTS first_activity_reached BOOLEAN NOT NULL DEFAULT false;
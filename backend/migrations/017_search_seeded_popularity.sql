@@ -0,0 +1,14 @@
+-- Seeded search popularity
+-- The hand-picked popularity of the seeded tokens, kept apart from the
+-- popularity recomputed from holders and liquidity, which may fall below it.
+ALTER TABLE search_entries ADD COLUMN IF NOT EXISTS seeded_popularity DOUBLE PRECISION NOT NULL DEFAULT 0;
+
+UPDATE search_entries e SET seeded_popularity = s.popularity
+FROM (VALUES
+    ('So11111111111111111111111111111111111111112', 100),
+    ('EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v', 95),
+    ('Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB', 90),
+    ('JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN', 80),
+    ('DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263', 70)
+) AS s(address, popularity)
+WHERE e.kind = 'token' AND e.address = s.address;
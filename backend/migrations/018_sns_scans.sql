@@ -0,0 +1,9 @@
+-- SNS owner scans
+-- Wallets whose address page found no favourite or indexed domain, queued
+-- for the background scan of the name program that finds the domains they
+-- own.
+CREATE TABLE IF NOT EXISTS sns_scans (
+    owner VARCHAR(44) PRIMARY KEY,
+    requested_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    scanned_at TIMESTAMP WITH TIME ZONE
+);
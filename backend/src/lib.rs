@@ -0,0 +1,107 @@
+use axum::{
+    routing::{get, post, put, delete},
+    Router,
+};
+use std::sync::Arc;
+use tower_http::cors::{CorsLayer, Any};
+
+pub mod models;
+pub mod routes;
+pub mod services;
+pub mod middleware;
+pub mod utils;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub db: sqlx::PgPool,
+    pub redis: redis::aio::ConnectionManager,
+    pub cache: services::cache::Cache,
+    pub solana_client: Arc<solana_client::rpc_client::RpcClient>,
+    pub http: reqwest::Client,
+    pub prices: services::prices::PriceService,
+}
+
+/// Builds the full API router. Shared by the binary and the integration tests
+/// so both exercise exactly the same routes.
+pub fn app(state: AppState) -> Router {
+    Router::new()
+        // Health check
+        .route("/health", get(routes::health::health_check))
+        
+        // API routes
+        .route("/api/blocks", get(routes::blocks::list_blocks))
+        .route("/api/blocks/production", get(routes::blocks::get_block_production))
+        .route("/api/blocks/at", get(routes::blocks::get_block_at))
+        .route("/api/blocks/height/:height", get(routes::blocks::get_block_by_height))
+        .route("/api/blocks/:id", get(routes::blocks::get_block))
+        .route("/api/transactions", get(routes::transactions::list_transactions))
+        .route("/api/transactions/:signature", get(routes::transactions::get_transaction))
+        .route("/api/addresses/:address", get(routes::addresses::get_address))
+        .route("/api/addresses/:address/transactions", get(routes::addresses::get_address_transactions))
+        .route("/api/tokens", get(routes::tokens::list_tokens))
+        .route("/api/tokens/:mint", get(routes::tokens::get_token))
+        .route("/api/tokens/:mint/holders", get(routes::tokens::get_token_holders))
+        .route("/api/tokens/:mint/candles", get(routes::tokens::get_token_candles))
+        .route("/api/tokens/:mint/transfers", get(routes::tokens::get_token_transfers))
+        .route("/api/tokens/:mint/risk", get(routes::tokens::get_token_risk))
+        .route("/api/markets", get(routes::markets::list_markets))
+        .route("/api/markets/:pair", get(routes::markets::get_market))
+        .route("/api/markets/:pair/trades", get(routes::markets::get_market_trades))
+        .route("/api/markets/:pair/quote", get(routes::markets::get_market_quote))
+        .route("/api/nfts/:mint", get(routes::nfts::get_nft))
+        .route("/api/collections/:collection", get(routes::nfts::get_collection))
+        .route("/api/network/stats", get(routes::network::get_stats))
+        .route("/api/network/tps", get(routes::network::get_throughput))
+        .route("/api/network/history", get(routes::network::get_history))
+        .route("/api/epochs", get(routes::epochs::list_epochs))
+        .route("/api/epochs/:epoch", get(routes::epochs::get_epoch))
+        .route("/api/leaders/upcoming", get(routes::epochs::upcoming_leaders))
+        .route("/api/validators", get(routes::validators::list_validators))
+        .route("/api/validators/:vote_pubkey", get(routes::validators::get_validator))
+        .route("/api/search", get(routes::search::search))
+        .route("/api/search/suggest", get(routes::search::suggest))
+        
+        // Auth routes
+        .route("/api/auth/register", post(routes::auth::register))
+        .route("/api/auth/login", post(routes::auth::login))
+        .route("/api/auth/logout", post(routes::auth::logout))
+        
+        // User routes
+        .route("/api/user/profile", get(routes::user::get_profile))
+        .route("/api/user/profile", put(routes::user::update_profile))
+        .route("/api/user/api-keys", get(routes::user::get_api_keys))
+        .route("/api/user/api-keys", post(routes::user::create_api_key))
+        .route("/api/user/api-keys/:id", delete(routes::user::delete_api_key))
+        .route("/api/user/watchlist", get(routes::user::get_watchlist))
+        .route("/api/user/watchlist", post(routes::user::add_to_watchlist))
+        .route("/api/user/watchlist/:id", delete(routes::user::remove_from_watchlist))
+        .route("/api/user/labels", get(routes::labels::list_private_labels))
+        .route("/api/user/labels", post(routes::labels::set_private_label))
+        .route("/api/user/labels/:address", delete(routes::labels::delete_private_label))
+        
+        // Admin routes
+        .route("/api/admin/users", get(routes::admin::list_users))
+        .route("/api/admin/users/:id", put(routes::admin::update_user))
+        .route("/api/admin/ads", get(routes::admin::list_ads))
+        .route("/api/admin/ads", post(routes::admin::create_ad))
+        .route("/api/admin/ads/:id", put(routes::admin::update_ad))
+        .route("/api/admin/ads/:id", delete(routes::admin::delete_ad))
+        .route("/api/admin/cache", delete(routes::admin::purge_cache))
+        .route("/api/admin/cache/stats", get(routes::admin::cache_stats))
+        .route("/api/admin/labels", get(routes::labels::list_labels))
+        .route("/api/admin/labels", post(routes::labels::upsert_label))
+        .route("/api/admin/labels/import", post(routes::labels::import_labels))
+        .route("/api/admin/labels/:address", delete(routes::labels::delete_label))
+        .route("/api/admin/tokens/import", post(routes::tokens::import_verified_tokens))
+        
+        // State
+        .with_state(state)
+        
+        // CORS
+        .layer(
+            CorsLayer::new()
+                .allow_origin(Any)
+                .allow_methods(Any)
+                .allow_headers(Any)
+        )
+}
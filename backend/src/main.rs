@@ -1,24 +1,9 @@
-use axum::{
-    routing::{get, post, put, delete},
-    Router,
-};
 use sqlx::postgres::PgPoolOptions;
 use std::net::SocketAddr;
-use tower_http::cors::{CorsLayer, Any};
+use std::sync::Arc;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-mod models;
-mod routes;
-mod services;
-mod middleware;
-mod utils;
-
-#[derive(Clone)]
-pub struct AppState {
-    pub db: sqlx::PgPool,
-    pub redis: redis::aio::ConnectionManager,
-    pub solana_client: solana_client::rpc_client::RpcClient,
-}
+use solana_explorer_backend::{app, services::{block_follower, cache::Cache, candles, markets, network_history, prices::PriceService, sns, token_holders, token_list, token_risk, trades}, AppState};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -58,75 +43,47 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .unwrap_or_else(|_| "redis://localhost:6379".to_string());
     let redis_client = redis::Client::open(redis_url)?;
     let redis = redis::aio::ConnectionManager::new(redis_client).await?;
+    let cache = Cache::new(redis.clone(), "cache");
     
     tracing::info!("Redis connected");
 
     // Solana RPC client
     let solana_rpc_url = std::env::var("SOLANA_RPC_URL")
         .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
-    let solana_client = solana_client::rpc_client::RpcClient::new(solana_rpc_url);
+    let solana_client = Arc::new(solana_client::rpc_client::RpcClient::new(solana_rpc_url));
     
     tracing::info!("Solana RPC client initialized");
 
+    // Shared client for price APIs
+    let http = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()?;
+
+    let prices = PriceService::from_env(&http, solana_client.clone());
+
     // Create app state
     let state = AppState {
         db,
         redis,
+        cache,
         solana_client,
+        http,
+        prices,
     };
 
+    // Background jobs
+    network_history::spawn_sampler(state.clone());
+    token_holders::spawn_indexer(state.clone());
+    token_list::spawn_refresher(state.clone());
+    candles::spawn_ticker(state.clone());
+    block_follower::spawn_follower(state.clone());
+    token_risk::spawn_scorer(state.clone());
+    markets::spawn_indexer(state.clone());
+    trades::spawn_stats(state.clone());
+    sns::spawn_scanner(state.clone());
+
     // Build routes
-    let app = Router::new()
-        // Health check
-        .route("/health", get(routes::health::health_check))
-        
-        // API routes
-        .route("/api/blocks", get(routes::blocks::list_blocks))
-        .route("/api/blocks/:number", get(routes::blocks::get_block))
-        .route("/api/transactions", get(routes::transactions::list_transactions))
-        .route("/api/transactions/:signature", get(routes::transactions::get_transaction))
-        .route("/api/addresses/:address", get(routes::addresses::get_address))
-        .route("/api/addresses/:address/transactions", get(routes::addresses::get_address_transactions))
-        .route("/api/tokens", get(routes::tokens::list_tokens))
-        .route("/api/tokens/:mint", get(routes::tokens::get_token))
-        .route("/api/markets", get(routes::markets::list_markets))
-        .route("/api/markets/:pair", get(routes::markets::get_market))
-        .route("/api/network/stats", get(routes::network::get_stats))
-        .route("/api/search", get(routes::search::search))
-        
-        // Auth routes
-        .route("/api/auth/register", post(routes::auth::register))
-        .route("/api/auth/login", post(routes::auth::login))
-        .route("/api/auth/logout", post(routes::auth::logout))
-        
-        // User routes
-        .route("/api/user/profile", get(routes::user::get_profile))
-        .route("/api/user/profile", put(routes::user::update_profile))
-        .route("/api/user/api-keys", get(routes::user::get_api_keys))
-        .route("/api/user/api-keys", post(routes::user::create_api_key))
-        .route("/api/user/api-keys/:id", delete(routes::user::delete_api_key))
-        .route("/api/user/watchlist", get(routes::user::get_watchlist))
-        .route("/api/user/watchlist", post(routes::user::add_to_watchlist))
-        .route("/api/user/watchlist/:id", delete(routes::user::remove_from_watchlist))
-        
-        // Admin routes
-        .route("/api/admin/users", get(routes::admin::list_users))
-        .route("/api/admin/users/:id", put(routes::admin::update_user))
-        .route("/api/admin/ads", get(routes::admin::list_ads))
-        .route("/api/admin/ads", post(routes::admin::create_ad))
-        .route("/api/admin/ads/:id", put(routes::admin::update_ad))
-        .route("/api/admin/ads/:id", delete(routes::admin::delete_ad))
-        
-        // State
-        .with_state(state)
-        
-        // CORS
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any)
-        );
+    let app = app(state);
 
     // Server address
     let host = std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
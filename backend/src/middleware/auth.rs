@@ -0,0 +1,83 @@
+use axum::{async_trait, extract::FromRequestParts, http::{header, request::Parts, StatusCode}};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::AppState;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    /// The user's id.
+    pub sub: String,
+    pub exp: usize,
+}
+
+/// The user behind a `Authorization: Bearer <jwt>` header. Handlers that
+/// also serve anonymous requests take `Option<AuthUser>`.
+#[derive(Debug, Clone, Copy)]
+pub struct AuthUser(pub Uuid);
+
+fn secret() -> Option<String> {
+    std::env::var("JWT_SECRET").ok().filter(|s| !s.is_empty())
+}
+
+fn expiry_secs() -> i64 {
+    std::env::var("JWT_EXPIRY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(86400)
+}
+
+/// Signs a session token for `user_id`. Panics if `JWT_SECRET` is unset.
+pub fn issue_token(user_id: Uuid) -> String {
+    let claims = Claims {
+        sub: user_id.to_string(),
+        exp: (chrono::Utc::now().timestamp() + expiry_secs()) as usize,
+    };
+    let secret = secret().expect("JWT_SECRET must be set");
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes()))
+        .expect("failed to sign token")
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for AuthUser {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // Without a secret no token can be trusted.
+        let secret = secret().ok_or(StatusCode::UNAUTHORIZED)?;
+        let token = parts.headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+
+        let data = decode::<Claims>(token, &DecodingKey::from_secret(secret.as_bytes()), &Validation::default())
+            .map_err(|_| StatusCode::UNAUTHORIZED)?;
+        Uuid::parse_str(&data.claims.sub)
+            .map(AuthUser)
+            .map_err(|_| StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// A signed-in user with the `admin` role. Rejects with 401 without a valid
+/// token and 403 for any other role.
+#[derive(Debug, Clone, Copy)]
+pub struct AdminUser(pub Uuid);
+
+#[async_trait]
+impl FromRequestParts<AppState> for AdminUser {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let AuthUser(user_id) = AuthUser::from_request_parts(parts, state).await?;
+        let role: Option<String> = sqlx::query_scalar("SELECT role FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&state.db)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        match role.as_deref() {
+            Some("admin") => Ok(AdminUser(user_id)),
+            _ => Err(StatusCode::FORBIDDEN),
+        }
+    }
+}
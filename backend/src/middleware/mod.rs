@@ -4,3 +4,5 @@
 // - Rate limiting
 // - Request logging
 // - Error handling
+
+pub mod auth;
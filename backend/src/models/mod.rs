@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct User {
@@ -53,9 +54,37 @@ pub struct Ad {
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct AddressLabel {
+    pub address: String,
+    pub label: String,
+    pub category: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct UserAddressLabel {
+    pub user_id: Uuid,
+    pub address: String,
+    pub label: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The label shown next to an address in API responses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Label {
+    pub name: String,
+    /// Curated labels only, e.g. "exchange" or "program".
+    pub category: Option<String>,
+    /// Set by the requesting user rather than curated.
+    pub private: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Block {
-    pub block_number: i64,
+    /// Block height; skipped slots have none.
+    pub block_number: Option<i64>,
     pub slot: i64,
     pub timestamp: i64,
     pub leader: String,
@@ -63,25 +92,566 @@ pub struct Block {
     pub blockhash: Option<String>,
     pub parent_slot: Option<i64>,
     pub previous_blockhash: Option<String>,
+    /// The scheduled leader did not produce a block for this slot.
+    pub skipped: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockDetails {
+    #[serde(flatten)]
+    pub block: Block,
+    pub total_fees: i64,
+    pub compute_units_consumed: i64,
+    pub successful_transactions: i32,
+    pub failed_transactions: i32,
+    pub vote_transactions: i32,
+    pub rewards: BlockRewards,
+    /// One page of the block's transactions, in block order.
+    pub transactions: Vec<BlockTransaction>,
+    /// Transactions matching the filter, across all pages.
+    pub transactions_total: i64,
+    pub page: i32,
+    pub limit: i32,
+    /// Labels for the leader and the signers on this page.
+    #[serde(default)]
+    pub labels: HashMap<String, Label>,
+}
+
+/// Rewards credited in a block, by type, in SOL.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BlockRewards {
+    pub fee: f64,
+    pub rent: f64,
+    pub staking: f64,
+    pub voting: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockTransaction {
+    pub signature: String,
+    pub signer: String,
+    pub fee: i64,
+    pub status: String,
+    pub compute_units_consumed: Option<i64>,
+    pub vote: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeaderProduction {
+    pub identity: String,
+    pub leader_slots: i64,
+    pub blocks_produced: i64,
+    pub skipped_slots: i64,
+    /// Percentage of leader slots skipped.
+    pub skip_rate: f64,
+}
+
+impl LeaderProduction {
+    pub fn new(identity: String, leader_slots: usize, blocks_produced: usize) -> Self {
+        let skipped_slots = leader_slots.saturating_sub(blocks_produced);
+        Self {
+            identity,
+            leader_slots: leader_slots as i64,
+            blocks_produced: blocks_produced as i64,
+            skipped_slots: skipped_slots as i64,
+            skip_rate: if leader_slots > 0 {
+                skipped_slots as f64 / leader_slots as f64 * 100.0
+            } else {
+                0.0
+            },
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Transaction {
     pub signature: String,
-    pub block_number: i64,
+    /// Height of the block the transaction landed in, when it can be read.
+    pub block_number: Option<i64>,
     pub slot: i64,
     pub timestamp: i64,
     pub fee: i64,
     pub status: String,
     pub signer: String,
+    /// Labels for the transaction's accounts.
+    #[serde(default)]
+    pub labels: HashMap<String, Label>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenDetails {
+    pub mint: String,
+    /// Token program owning the mint: SPL Token or Token-2022.
+    pub program: String,
+    /// Raw supply in base units, as a string to survive JSON number limits.
+    pub supply: String,
+    pub decimals: u8,
+    pub mint_authority: Option<String>,
+    pub freeze_authority: Option<String>,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub uri: Option<String>,
+    /// Image from the off-chain metadata JSON.
+    pub logo_uri: Option<String>,
+    pub description: Option<String>,
+    pub update_authority: Option<String>,
+    /// Where name/symbol/uri came from: "metaplex" or "token-2022".
+    pub metadata_source: Option<String>,
+    /// Whether name/symbol/uri can still be changed; None without metadata.
+    #[serde(default)]
+    pub metadata_mutable: Option<bool>,
+    /// Token-2022 mint extensions; empty for classic SPL mints.
+    #[serde(default)]
+    pub extensions: Vec<TokenExtension>,
+}
+
+/// A decoded Token-2022 mint or token account extension.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenExtension {
+    /// Extension name as in the RPC's jsonParsed encoding, e.g.
+    /// "transferFeeConfig".
+    pub extension: String,
+    /// Decoded fields; null for extensions without any.
+    pub state: serde_json::Value,
+    /// Set for extensions that let someone other than the holder move,
+    /// freeze, tax or lock up tokens, like a permanent delegate.
+    pub risky: bool,
 }
 
+/// A row of the token listing.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Token {
+    pub mint: String,
+    pub symbol: String,
+    pub name: String,
+    pub program: String,
+    pub decimals: i16,
+    pub logo_uri: Option<String>,
+    pub verified: bool,
+    /// USD price; None if no source has ever priced the token.
+    pub price: Option<f64>,
+    pub price_change_24h: Option<f64>,
+    pub volume_24h: f64,
+    pub liquidity: f64,
+    pub market_cap: Option<f64>,
+    /// Indexed holder count; None until the mint has been indexed.
+    pub holders: Option<i64>,
+    /// Names of the flags raised by the token's last risk report.
+    pub risk_flags: Vec<String>,
+}
+
+/// Findings of a token risk report and the metrics behind them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenRisk {
+    pub mint: String,
+    pub verified: bool,
+    /// 0 for no findings, up to 100.
+    pub score: u8,
+    pub flags: Vec<RiskFlag>,
+    /// Share of supply held by the ten largest accounts.
+    pub top_holders_share: Option<f64>,
+    /// USD liquidity across the token's pools.
+    pub liquidity: f64,
+    /// Oldest transaction found for the mint.
+    pub first_activity: Option<DateTime<Utc>>,
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskFlag {
+    /// e.g. "mint_authority" or "impersonates_verified".
+    pub flag: String,
+    /// "high", "medium" or "low".
+    pub severity: String,
+    pub detail: String,
+}
+
+/// A DEX pool, priced in its quote token.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Market {
+    /// Pool account address.
+    pub address: String,
+    /// "BASE/QUOTE" by symbol.
+    pub pair: String,
+    pub base_token: String,
+    pub quote_token: String,
+    pub base_mint: String,
+    pub quote_mint: String,
+    pub base_reserve: f64,
+    pub quote_reserve: f64,
+    /// Quote per base.
+    pub price: f64,
+    pub price_change_24h: Option<f64>,
+    pub volume_24h: f64,
+    /// USD value of both reserves.
+    pub liquidity: f64,
+    /// Share of each swap's input taken as fees.
+    pub fee_rate: f64,
+    /// "raydium", "raydium-cpmm", "raydium-clmm" or "orca".
+    pub dex: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Every indexed pool trading a mint pair, priced in the pair's quote.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MarketPair {
+    /// "BASE/QUOTE" by symbol.
+    pub pair: String,
+    pub base_token: String,
+    pub quote_token: String,
+    pub base_mint: String,
+    pub quote_mint: String,
+    /// Lowest pool price: the best rate for buying base.
+    pub best_buy_price: f64,
+    /// Highest pool price: the best rate for selling base.
+    pub best_sell_price: f64,
+    /// Summed over the pair's pools.
+    pub liquidity: f64,
+    pub volume_24h: f64,
+    /// Deepest first.
+    pub markets: Vec<Market>,
+}
+
+/// Estimated output of one swap through one pool.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PoolQuote {
+    pub market: String,
+    pub dex: String,
+    pub amount_out: f64,
+    pub fee: f64,
+    /// Percentage by which the rate falls short of the pool price.
+    pub price_impact: f64,
+}
+
+/// Estimated output of swapping an amount of one side of a pair for the
+/// other, through each of the pair's pools.
 #[derive(Debug, Serialize, Deserialize)]
+pub struct RouteQuote {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount_in: f64,
+    /// Output of the best pool; None if no pool can fill the swap.
+    pub amount_out: Option<f64>,
+    /// Best output first.
+    pub routes: Vec<PoolQuote>,
+}
+
+/// A swap through an indexed market, netted over its transaction.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Trade {
+    pub signature: String,
+    /// Pool account address.
+    pub market: String,
+    pub slot: i64,
+    pub block_time: Option<DateTime<Utc>>,
+    /// "buy" when the trader took base tokens out of the pool, "sell" when
+    /// they put them in.
+    pub side: String,
+    /// Fee payer of the transaction.
+    pub trader: String,
+    pub base_amount: f64,
+    pub quote_amount: f64,
+    /// Quote per base.
+    pub price: f64,
+    /// None when neither side had a USD price.
+    pub volume_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenPrice {
+    pub mint: String,
+    /// USD price.
+    pub price: f64,
+    /// Percent change against the price 24 hours ago, if one was recorded.
+    pub price_change_24h: Option<f64>,
+    /// Number of sources that agreed on the price.
+    pub sources: i32,
+    pub updated_at: DateTime<Utc>,
+    /// No source has confirmed the price recently.
+    pub stale: bool,
+}
+
+/// One OHLCV bucket of a token's price history.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Candle {
+    /// Start of the bucket.
+    pub bucket: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// USD volume recorded with the bucket's ticks.
+    pub volume: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candles {
+    pub mint: String,
+    pub interval: String,
+    pub from: i64,
+    pub to: i64,
+    pub candles: Vec<Candle>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TokenTransfer {
+    pub signature: String,
+    pub instruction_index: i16,
+    /// Position among the instruction's inner instructions; -1 when the
+    /// transfer is the top-level instruction itself.
+    pub inner_index: i16,
+    pub slot: i64,
+    pub block_time: Option<DateTime<Utc>>,
+    /// "transfer", "mint" or "burn".
+    pub kind: String,
+    pub mint: String,
+    /// Token account debited; None for mints.
+    pub from_account: Option<String>,
+    pub from_owner: Option<String>,
+    /// Token account credited; None for burns.
+    pub to_account: Option<String>,
+    pub to_owner: Option<String>,
+    /// Raw amount in base units.
+    pub amount: String,
+    pub decimals: i16,
+    pub ui_amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenHolder {
+    /// The token account.
+    pub address: String,
+    /// Wallet owning the token account, if it could be read.
+    pub owner: Option<String>,
+    /// Raw amount in base units.
+    pub amount: String,
+    pub ui_amount: f64,
+    /// Fraction of the total supply, 0 to 1.
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenHolders {
+    pub mint: String,
+    pub holders: Vec<TokenHolder>,
+    /// Distinct wallets with a non-zero balance, from the holder index. None
+    /// until the mint has been indexed.
+    pub holder_count: Option<i64>,
+    pub indexed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProgramAccount {
+    pub address: String,
+    pub loader: String,
+    pub lamports: u64,
+    pub data_len: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Validator {
+    pub vote_pubkey: String,
+    pub identity: String,
+    pub commission: u8,
+    /// Activated stake in SOL.
+    pub activated_stake: f64,
+    /// Share of total activated stake, as a percentage.
+    pub stake_share: f64,
+    pub last_vote: i64,
+    pub root_slot: i64,
+    pub leader_slots: i64,
+    pub blocks_produced: i64,
+    /// Percentage of assigned leader slots skipped this epoch; `None` when the
+    /// validator has not been leader yet.
+    pub skip_rate: Option<f64>,
+    pub version: Option<String>,
+    pub delinquent: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EpochSummary {
+    pub epoch: i64,
+    pub first_slot: i64,
+    pub last_slot: i64,
+    pub slots: i64,
+    /// Start and end times are estimated from the recent average slot time.
+    pub start_time: i64,
+    pub end_time: i64,
+    /// `completed`, `current` or `upcoming`.
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EpochDetail {
+    #[serde(flatten)]
+    pub summary: EpochSummary,
+    /// Only known for the current epoch.
+    pub inflation_rate: Option<f64>,
+    /// Only known once the epoch has completed.
+    pub rewards: Option<EpochRewards>,
+    /// Only available for the current and next epoch.
+    pub leader_schedule: Vec<ScheduledLeader>,
+}
+
+/// Inflation rewards paid for an epoch, in SOL.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EpochRewards {
+    /// The block the rewards were credited in.
+    pub slot: i64,
+    pub staking: f64,
+    pub voting: f64,
+    pub total: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduledLeader {
+    pub identity: String,
+    pub leader_slots: i64,
+    /// Share of the epoch's leader slots, as a percentage.
+    pub share: f64,
+    pub first_slot: Option<i64>,
+    pub last_slot: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpcomingLeader {
+    pub slot: i64,
+    pub leader: String,
+    pub estimated_time: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct NetworkStats {
     pub slot: i64,
     pub block_height: i64,
     pub tps: f64,
+    pub non_vote_tps: Option<f64>,
     pub total_transactions: i64,
     pub epoch: i64,
     pub epoch_progress: f64,
+    pub throughput: Vec<ThroughputWindow>,
+}
+
+/// Average throughput over a trailing window ("1m", "5m" or "1h").
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThroughputWindow {
+    pub window: String,
+    pub tps: f64,
+    pub non_vote_tps: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThroughputSample {
+    pub slot: i64,
+    pub timestamp: i64,
+    pub tps: f64,
+    pub non_vote_tps: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct NetworkMetricPoint {
+    pub bucket: DateTime<Utc>,
+    pub avg: f64,
+    pub min: f64,
+    pub max: f64,
+    pub samples: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct SearchEntry {
+    pub kind: String,
+    pub address: String,
+    pub name: String,
+    pub symbol: Option<String>,
+    pub popularity: f64,
+}
+
+/// A Metaplex NFT (decimals 0, supply 1) or a compressed NFT.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Nft {
+    /// Mint, or asset id for compressed NFTs.
+    pub mint: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub image: Option<String>,
+    /// Collection NFT mint.
+    pub collection: Option<String>,
+    pub collection_name: Option<String>,
+    /// Set when the collection's authority has verified membership.
+    pub collection_verified: bool,
+    pub owner: Option<String>,
+    pub compressed: bool,
+    /// Merkle tree holding a compressed NFT's leaf.
+    pub tree: Option<String>,
+    pub leaf_index: Option<i64>,
+    pub burned: bool,
+}
+
+/// NFTs of one collection, or the uncollected ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftCollection {
+    pub collection: Option<String>,
+    pub name: Option<String>,
+    pub nfts: Vec<Nft>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftCreator {
+    pub address: String,
+    pub verified: bool,
+    /// Percentage of royalties.
+    pub share: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftAttribute {
+    pub trait_type: String,
+    pub value: serde_json::Value,
+}
+
+/// A change of an NFT's owner or delegate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftOwnership {
+    pub signature: String,
+    pub slot: i64,
+    pub block_time: Option<DateTime<Utc>>,
+    /// "mint", "transfer", "delegate" or "burn".
+    pub kind: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// State of an account-compression concurrent merkle tree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerkleTree {
+    pub address: String,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub authority: String,
+    pub creation_slot: u64,
+    /// Number of changes applied to the tree.
+    pub sequence_number: u64,
+    /// Leaves appended so far.
+    pub leaves: u64,
+    /// Current root, base58.
+    pub root: String,
+    /// Levels of the tree cached on-chain above the leaves' proofs.
+    pub canopy_depth: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftDetails {
+    #[serde(flatten)]
+    pub nft: Nft,
+    pub description: Option<String>,
+    pub attributes: Vec<NftAttribute>,
+    pub update_authority: Option<String>,
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<NftCreator>,
+    pub delegate: Option<String>,
+    /// Newest first.
+    pub history: Vec<NftOwnership>,
+    /// Tree of a compressed NFT.
+    pub merkle_tree: Option<MerkleTree>,
 }
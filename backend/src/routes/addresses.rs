@@ -1,6 +1,14 @@
 use axum::{extract::{Path, Query, State}, Json};
 use serde::{Deserialize, Serialize};
-use crate::{AppState, routes::blocks::ListParams};
+use std::collections::HashMap;
+use uuid::Uuid;
+use crate::{
+    AppState,
+    middleware::auth::AuthUser,
+    models::{Label, NftCollection, TokenExtension},
+    routes::blocks::ListParams,
+    services::{cache, labels, nfts, prices, sns, solana, token_extensions},
+};
 
 #[derive(Debug, Serialize)]
 pub struct AddressDetails {
@@ -8,7 +16,12 @@ pub struct AddressDetails {
     pub balance: f64,
     #[serde(rename = "type")]
     pub address_type: String,
+    /// Primary `.sol` domain of the wallet, if it has one.
+    pub domain: Option<String>,
+    pub labels: HashMap<String, Label>,
     pub tokens: Vec<TokenBalance>,
+    /// NFTs held, by collection. Their mints aren't listed in `tokens`.
+    pub nfts: Vec<NftCollection>,
     pub transaction_count: i32,
 }
 
@@ -17,9 +30,15 @@ pub struct TokenBalance {
     pub mint: String,
     pub symbol: String,
     pub name: String,
+    /// Raw amount in base units.
     pub balance: String,
     pub decimals: u8,
+    /// Balance at the current price; None if the token has no known price.
     pub usd_value: Option<f64>,
+    /// Token-2022 extensions of the token account.
+    pub extensions: Vec<TokenExtension>,
+    /// Names of the risky extensions of the account or its mint.
+    pub risks: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -30,26 +49,193 @@ pub struct TransactionsResponse {
     pub limit: i32,
 }
 
+/// A non-empty token account of the address being viewed.
+#[derive(Debug, Serialize, Deserialize)]
+struct Holding {
+    mint: String,
+    /// Raw amount in base units.
+    amount: String,
+    decimals: u8,
+    extensions: Vec<TokenExtension>,
+    risks: Vec<String>,
+}
+
+/// Non-empty token accounts of `owner` under both token programs, with the
+/// extensions of Token-2022 accounts and the risky ones of their mints.
+fn token_accounts(state: &AppState, owner: &solana_sdk::pubkey::Pubkey) -> Vec<Holding> {
+    use solana_account_decoder::UiAccountData;
+    use solana_client::rpc_request::TokenAccountsFilter;
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+
+    let mut seen = std::collections::HashSet::new();
+    let accounts: Vec<(String, bool, String, String, u8)> = [solana::TOKEN_PROGRAM_ID, solana::TOKEN_2022_PROGRAM_ID]
+        .into_iter()
+        .flat_map(|program| {
+            state.solana_client
+                .get_token_accounts_by_owner(owner, TokenAccountsFilter::ProgramId(program))
+                .unwrap_or_default()
+        })
+        .filter(|account| seen.insert(account.pubkey.clone()))
+        .filter_map(|account| {
+            let extended = account.account.owner == solana::TOKEN_2022_PROGRAM_ID.to_string();
+            let UiAccountData::Json(data) = account.account.data else { return None };
+            let info = &data.parsed["info"];
+            let amount = info["tokenAmount"]["amount"].as_str()?;
+            if amount == "0" {
+                return None;
+            }
+            Some((
+                account.pubkey,
+                extended,
+                info["mint"].as_str()?.to_string(),
+                amount.to_string(),
+                info["tokenAmount"]["decimals"].as_u64()? as u8,
+            ))
+        })
+        .collect();
+
+    // jsonParsed describes extensions in the node's own shape, so Token-2022
+    // accounts and their mints are read again raw, in one call, and decoded.
+    let mut raw: Vec<Pubkey> = Vec::new();
+    for key in accounts
+        .iter()
+        .filter(|(_, extended, ..)| *extended)
+        .flat_map(|(address, _, mint, ..)| [address, mint])
+        .filter_map(|key| Pubkey::from_str(key).ok())
+    {
+        if !raw.contains(&key) {
+            raw.push(key);
+        }
+    }
+    let mut extensions: HashMap<String, Vec<TokenExtension>> = if raw.is_empty() {
+        HashMap::new()
+    } else {
+        let data = state.solana_client.get_multiple_accounts(&raw).unwrap_or_default();
+        raw.iter()
+            .zip(data)
+            .filter_map(|(key, account)| Some((key.to_string(), token_extensions::decode(&account?.data))))
+            .collect()
+    };
+
+    accounts
+        .into_iter()
+        .map(|(address, _, mint, amount, decimals)| {
+            let account_extensions = extensions.remove(&address).unwrap_or_default();
+            let risks = account_extensions
+                .iter()
+                .chain(extensions.get(&mint).into_iter().flatten())
+                .filter(|extension| extension.risky)
+                .map(|extension| extension.extension.clone())
+                .collect();
+            Holding { mint, amount, decimals, extensions: account_extensions, risks }
+        })
+        .collect()
+}
+
+/// Token holdings of `owner`, split into NFTs, by collection, and the
+/// remaining token accounts.
+async fn holdings(state: &AppState, owner: &solana_sdk::pubkey::Pubkey) -> (Vec<NftCollection>, Vec<Holding>) {
+    let mut accounts = state.cache
+        .get_or_fetch(&format!("token-accounts:{}", owner), cache::BALANCE, || {
+            Some(token_accounts(state, owner))
+        })
+        .await
+        .unwrap_or_default();
+
+    let candidates: Vec<String> = accounts
+        .iter()
+        .filter(|holding| holding.decimals == 0 && holding.amount == "1")
+        .map(|holding| holding.mint.clone())
+        .collect();
+    let nfts = nfts::owned(state, &owner.to_string(), &candidates).await;
+    let nft_mints: std::collections::HashSet<&str> = nfts
+        .iter()
+        .flat_map(|collection| &collection.nfts)
+        .map(|nft| nft.mint.as_str())
+        .collect();
+    accounts.retain(|holding| !nft_mints.contains(holding.mint.as_str()));
+    (nfts, accounts)
+}
+
+/// Token holdings with names from the search index and USD values where a
+/// price is known, most valuable first.
+async fn token_balances(state: &AppState, accounts: Vec<Holding>) -> Vec<TokenBalance> {
+    if accounts.is_empty() {
+        return vec![];
+    }
+
+    let mints: Vec<String> = accounts.iter().map(|holding| holding.mint.clone()).collect();
+    let names: HashMap<String, (String, Option<String>)> = sqlx::query_as::<_, (String, String, Option<String>)>(
+        "SELECT address, name, symbol FROM search_entries WHERE kind = 'token' AND address = ANY($1)"
+    )
+    .bind(&mints)
+    .fetch_all(&state.db)
+    .await
+    .unwrap_or_default()
+    .into_iter()
+    .map(|(address, name, symbol)| (address, (name, symbol)))
+    .collect();
+    let prices = prices::prices(state, &mints).await;
+
+    let mut balances: Vec<TokenBalance> = accounts
+        .into_iter()
+        .map(|holding| {
+            let (name, symbol) = names.get(&holding.mint).cloned().unwrap_or_default();
+            let ui_amount = holding.amount.parse::<f64>().unwrap_or(0.0) / 10f64.powi(holding.decimals as i32);
+            TokenBalance {
+                usd_value: prices.get(&holding.mint).map(|p| p.price * ui_amount),
+                symbol: symbol.unwrap_or_default(),
+                name,
+                mint: holding.mint,
+                balance: holding.amount,
+                decimals: holding.decimals,
+                extensions: holding.extensions,
+                risks: holding.risks,
+            }
+        })
+        .collect();
+    balances.sort_by(|a, b| b.usd_value.unwrap_or(0.0).total_cmp(&a.usd_value.unwrap_or(0.0)));
+    balances
+}
+
 pub async fn get_address(
     State(state): State<AppState>,
+    user: Option<AuthUser>,
     Path(address): Path<String>,
 ) -> Json<Option<AddressDetails>> {
+    Json(fetch_address(&state, &address, user.map(|u| u.0)).await)
+}
+
+/// `address` is a base58 pubkey or a `.sol` domain, which is resolved to
+/// its owner. Labels are resolved as seen by `user`.
+pub async fn fetch_address(state: &AppState, address: &str, user: Option<Uuid>) -> Option<AddressDetails> {
     use solana_sdk::pubkey::Pubkey;
     use std::str::FromStr;
     
-    if let Ok(pubkey) = Pubkey::from_str(&address) {
-        if let Ok(balance) = state.solana_client.get_balance(&pubkey) {
-            return Json(Some(AddressDetails {
-                address: address.clone(),
-                balance: balance as f64 / 1_000_000_000.0,
-                address_type: "wallet".to_string(),
-                tokens: vec![],
-                transaction_count: 0,
-            }));
-        }
-    }
-    
-    Json(None)
+    let pubkey = if sns::is_domain(address) {
+        sns::resolve(state, address).await?
+    } else {
+        Pubkey::from_str(address).ok()?
+    };
+    let address = pubkey.to_string();
+    let balance = state.cache
+        .get_or_fetch(&format!("balance:{}", address), cache::BALANCE, || {
+            state.solana_client.get_balance(&pubkey).ok()
+        })
+        .await?;
+    let (nfts, accounts) = holdings(state, &pubkey).await;
+
+    Some(AddressDetails {
+        address: address.clone(),
+        balance: balance as f64 / 1_000_000_000.0,
+        address_type: "wallet".to_string(),
+        domain: sns::primary_domain(state, &pubkey).await,
+        labels: labels::lookup(&state.db, user, std::slice::from_ref(&address)).await,
+        tokens: token_balances(state, accounts).await,
+        nfts,
+        transaction_count: 0,
+    })
 }
 
 pub async fn get_address_transactions(
@@ -1,6 +1,6 @@
-use axum::{extract::{Path, Query, State}, Json};
-use serde::Serialize;
-use crate::{AppState, models::Ad, routes::blocks::ListParams};
+use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use crate::{AppState, middleware::auth::AdminUser, models::Ad, routes::blocks::ListParams, services::cache::CacheStats};
 
 #[derive(Debug, Serialize)]
 pub struct UsersResponse {
@@ -89,3 +89,26 @@ pub async fn delete_ad(
 ) -> Json<serde_json::Value> {
     Json(serde_json::json!({ "message": "Ad deleted" }))
 }
+
+#[derive(Debug, Deserialize)]
+pub struct PurgeParams {
+    pub pattern: String,
+}
+
+pub async fn purge_cache(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Query(params): Query<PurgeParams>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    match state.cache.purge(&params.pattern).await {
+        Ok(deleted) => Ok(Json(serde_json::json!({ "message": "Cache purged", "deleted": deleted }))),
+        Err(e) => {
+            tracing::error!("cache purge failed for {}: {}", params.pattern, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "message": "Cache purge failed" }))))
+        }
+    }
+}
+
+pub async fn cache_stats(State(state): State<AppState>, _admin: AdminUser) -> Json<CacheStats> {
+    Json(state.cache.stats())
+}
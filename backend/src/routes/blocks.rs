@@ -1,6 +1,15 @@
 use axum::{extract::{Path, Query, State}, Json};
 use serde::{Deserialize, Serialize};
-use crate::{AppState, models::Block};
+use solana_client::rpc_config::{RpcBlockProductionConfig, RpcBlockProductionConfigRange};
+use solana_sdk::hash::Hash;
+use solana_transaction_status::{TransactionDetails, UiConfirmedBlock};
+use std::{collections::{HashMap, HashSet}, str::FromStr};
+use crate::{
+    AppState,
+    middleware::auth::AuthUser,
+    models::{Block, BlockDetails, Label, LeaderProduction},
+    services::{blocks, cache, labels, solana},
+};
 
 #[derive(Debug, Deserialize)]
 pub struct ListParams {
@@ -8,6 +17,10 @@ pub struct ListParams {
     pub page: i32,
     #[serde(default = "default_limit")]
     pub limit: i32,
+    /// Field to sort by, for listings that support sorting.
+    pub sort: Option<String>,
+    /// `asc` or `desc`; each listing picks its own default.
+    pub order: Option<String>,
 }
 
 fn default_page() -> i32 { 1 }
@@ -19,60 +32,259 @@ pub struct BlocksResponse {
     pub total: i64,
     pub page: i32,
     pub limit: i32,
+    /// Labels for the leaders on this page.
+    pub labels: HashMap<String, Label>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct BlockParams {
+    #[serde(default = "default_page")]
+    pub page: i32,
+    #[serde(default = "default_limit")]
+    pub limit: i32,
+    /// Set to `false` to leave validator vote transactions out of the list.
+    #[serde(default = "default_votes")]
+    pub votes: bool,
+}
+
+fn default_votes() -> bool { true }
+
+#[derive(Debug, Deserialize)]
+pub struct AtParams {
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProductionParams {
+    pub identity: Option<String>,
+    pub first_slot: Option<u64>,
+    pub last_slot: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProductionResponse {
+    pub first_slot: i64,
+    pub last_slot: i64,
+    pub total_leader_slots: i64,
+    pub total_blocks_produced: i64,
+    pub total_skipped_slots: i64,
+    pub leaders: Vec<LeaderProduction>,
+}
+
+fn to_block(slot: u64, block: &UiConfirmedBlock, leader: String) -> Block {
+    Block {
+        block_number: block.block_height.map(|height| height as i64),
+        slot: slot as i64,
+        timestamp: block.block_time.unwrap_or(0),
+        leader,
+        transactions_count: block.transactions.as_ref().map_or(0, |txs| txs.len()) as i32,
+        blockhash: Some(block.blockhash.clone()),
+        parent_slot: Some(block.parent_slot as i64),
+        previous_blockhash: Some(block.previous_blockhash.clone()),
+        skipped: false,
+    }
+}
+
+fn skipped_block(slot: u64, leader: String) -> Block {
+    Block {
+        block_number: None,
+        slot: slot as i64,
+        timestamp: 0,
+        leader,
+        transactions_count: 0,
+        blockhash: None,
+        parent_slot: None,
+        previous_blockhash: None,
+        skipped: true,
+    }
+}
+
+/// Scheduled leaders for `limit` slots starting at `start`. The leader
+/// schedule is fixed once an epoch starts, so the lookup is cached forever.
+async fn slot_leaders(state: &AppState, start: u64, limit: u64) -> Vec<String> {
+    state.cache
+        .get_or_fetch(&format!("slot-leaders:{}:{}", start, limit), cache::FINALIZED, || {
+            state.solana_client
+                .get_slot_leaders(start, limit)
+                .ok()
+                .map(|leaders| leaders.iter().map(|l| l.to_string()).collect::<Vec<_>>())
+        })
+        .await
+        .unwrap_or_default()
+}
+
+/// Fetches a block. Its hash is indexed by the block follower, not here.
+async fn fetch_block(state: &AppState, slot: u64) -> Option<UiConfirmedBlock> {
+    state.cache
+        .get_or_fetch(&format!("block:{}", slot), cache::FINALIZED, || {
+            state.solana_client
+                .get_block_with_config(slot, solana::block_config(TransactionDetails::Full, true))
+                .ok()
+        })
+        .await
+}
+
+/// The block summary alone, without its transactions.
+pub async fn block_summary(state: &AppState, slot: u64) -> Option<Block> {
+    let block = fetch_block(state, slot).await?;
+    let leader = slot_leaders(state, slot, 1).await.into_iter().next().unwrap_or_default();
+    Some(to_block(slot, &block, leader))
+}
+
+async fn block_details(
+    state: &AppState,
+    slot: u64,
+    params: &BlockParams,
+    user: Option<AuthUser>,
+) -> Option<BlockDetails> {
+    let block = fetch_block(state, slot).await?;
+    let leader = slot_leaders(state, slot, 1).await.into_iter().next().unwrap_or_default();
+    let summary = to_block(slot, &block, leader);
+    let mut details = blocks::details(summary, &block, params.votes, params.page, params.limit);
+
+    let mut accounts: Vec<String> = details.transactions.iter().map(|tx| tx.signer.clone()).collect();
+    accounts.push(details.block.leader.clone());
+    details.labels = labels::lookup(&state.db, user.map(|u| u.0), &accounts).await;
+    Some(details)
+}
+
+/// Lists the most recent slots, newest first. Slots whose leader did not
+/// produce a block are returned with `skipped: true` rather than omitted.
+/// If the produced slots can't be read, no slots are listed, since none
+/// could be told apart from skipped ones.
 pub async fn list_blocks(
     State(state): State<AppState>,
+    user: Option<AuthUser>,
     Query(params): Query<ListParams>,
 ) -> Json<BlocksResponse> {
     // Get latest blocks from Solana
     let slot = state.solana_client.get_slot().unwrap_or(0);
-    
+    let limit = params.limit.clamp(1, 100) as u64;
+    let end = slot.saturating_sub((params.page.max(1) as u64 - 1) * limit);
+    let start = end.saturating_sub(limit - 1);
+
+    let produced: Option<HashSet<u64>> = state.cache
+        .get_or_fetch(&format!("blocks:{}:{}", start, end), cache::FINALIZED, || {
+            state.solana_client.get_blocks(start, Some(end)).ok()
+        })
+        .await
+        .map(|slots| slots.into_iter().collect());
+    let Some(produced) = produced else {
+        return Json(BlocksResponse {
+            blocks: Vec::new(),
+            labels: HashMap::new(),
+            total: slot as i64,
+            page: params.page,
+            limit: limit as i32,
+        });
+    };
+    let leaders = slot_leaders(&state, start, end - start + 1).await;
+
     let mut blocks = Vec::new();
-    for i in 0..params.limit {
-        let block_slot = slot - (params.page as u64 - 1) * params.limit as u64 - i as u64;
-        if let Ok(block) = state.solana_client.get_block(block_slot) {
-            blocks.push(Block {
-                block_number: block_slot as i64,
-                slot: block_slot as i64,
-                timestamp: block.block_time.unwrap_or(0),
-                leader: block.rewards.first()
-                    .map(|r| r.pubkey.clone())
-                    .unwrap_or_default(),
-                transactions_count: block.transactions.len() as i32,
-                blockhash: Some(block.blockhash),
-                parent_slot: Some(block.parent_slot as i64),
-                previous_blockhash: Some(block.previous_blockhash),
-            });
+    for block_slot in (start..=end).rev() {
+        let leader = leaders
+            .get((block_slot - start) as usize)
+            .cloned()
+            .unwrap_or_default();
+
+        if !produced.contains(&block_slot) {
+            blocks.push(skipped_block(block_slot, leader));
+        } else if let Some(block) = fetch_block(&state, block_slot).await {
+            blocks.push(to_block(block_slot, &block, leader));
         }
     }
     
+    let leaders: Vec<String> = blocks.iter().map(|b| b.leader.clone()).collect();
     Json(BlocksResponse {
+        labels: labels::lookup(&state.db, user.map(|u| u.0), &leaders).await,
         blocks,
         total: slot as i64,
         page: params.page,
-        limit: params.limit,
+        limit: limit as i32,
     })
 }
 
+/// A block with its summary statistics and one page of its transactions.
+/// `id` is either a slot number or a base58 blockhash.
 pub async fn get_block(
     State(state): State<AppState>,
-    Path(number): Path<u64>,
-) -> Json<Option<Block>> {
-    if let Ok(block) = state.solana_client.get_block(number) {
-        Json(Some(Block {
-            block_number: number as i64,
-            slot: number as i64,
-            timestamp: block.block_time.unwrap_or(0),
-            leader: block.rewards.first()
-                .map(|r| r.pubkey.clone())
-                .unwrap_or_default(),
-            transactions_count: block.transactions.len() as i32,
-            blockhash: Some(block.blockhash),
-            parent_slot: Some(block.parent_slot as i64),
-            previous_blockhash: Some(block.previous_blockhash),
-        }))
-    } else {
-        Json(None)
+    user: Option<AuthUser>,
+    Path(id): Path<String>,
+    Query(params): Query<BlockParams>,
+) -> Json<Option<BlockDetails>> {
+    let slot = match id.parse::<u64>() {
+        Ok(slot) => Some(slot),
+        Err(_) if Hash::from_str(&id).is_ok() => blocks::slot_for_hash(&state.db, &id).await,
+        Err(_) => None,
+    };
+
+    match slot {
+        Some(slot) => Json(block_details(&state, slot, &params, user).await),
+        None => Json(None),
+    }
+}
+
+pub async fn get_block_by_height(
+    State(state): State<AppState>,
+    user: Option<AuthUser>,
+    Path(height): Path<u64>,
+    Query(params): Query<BlockParams>,
+) -> Json<Option<BlockDetails>> {
+    match blocks::slot_for_height(&state, height).await {
+        Some(slot) => Json(block_details(&state, slot, &params, user).await),
+        None => Json(None),
     }
 }
+
+/// The block closest in time to `timestamp` (unix seconds).
+pub async fn get_block_at(
+    State(state): State<AppState>,
+    user: Option<AuthUser>,
+    Query(at): Query<AtParams>,
+    Query(params): Query<BlockParams>,
+) -> Json<Option<BlockDetails>> {
+    match blocks::slot_at(&state, at.timestamp) {
+        Some(slot) => Json(block_details(&state, slot, &params, user).await),
+        None => Json(None),
+    }
+}
+
+/// Leader slots assigned vs blocks produced, per validator identity. Defaults
+/// to the current epoch; narrow with `identity` and `first_slot`/`last_slot`.
+pub async fn get_block_production(
+    State(state): State<AppState>,
+    Query(params): Query<ProductionParams>,
+) -> Json<Option<ProductionResponse>> {
+    let config = RpcBlockProductionConfig {
+        identity: params.identity.clone(),
+        range: params.first_slot.map(|first_slot| RpcBlockProductionConfigRange {
+            first_slot,
+            last_slot: params.last_slot,
+        }),
+        commitment: None,
+    };
+
+    let Ok(production) = state.solana_client.get_block_production_with_config(config) else {
+        return Json(None);
+    };
+    let production = production.value;
+
+    let mut leaders: Vec<LeaderProduction> = production.by_identity
+        .into_iter()
+        .filter(|(identity, _)| params.identity.as_ref().map_or(true, |wanted| wanted == identity))
+        .map(|(identity, (leader_slots, blocks_produced))| LeaderProduction::new(identity, leader_slots, blocks_produced))
+        .collect();
+    leaders.sort_by(|a, b| b.leader_slots.cmp(&a.leader_slots).then_with(|| a.identity.cmp(&b.identity)));
+
+    let total_leader_slots = leaders.iter().map(|l| l.leader_slots).sum();
+    let total_blocks_produced = leaders.iter().map(|l| l.blocks_produced).sum();
+
+    Json(Some(ProductionResponse {
+        first_slot: production.range.first_slot as i64,
+        last_slot: production.range.last_slot as i64,
+        total_leader_slots,
+        total_blocks_produced,
+        total_skipped_slots: total_leader_slots - total_blocks_produced,
+        leaders,
+    }))
+}
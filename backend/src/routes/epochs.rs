@@ -0,0 +1,163 @@
+use axum::{extract::{Path, Query, State}, Json};
+use serde::{Deserialize, Serialize};
+use solana_sdk::epoch_schedule::EpochSchedule;
+use crate::{
+    AppState,
+    models::{EpochDetail, EpochSummary, UpcomingLeader},
+    routes::blocks::ListParams,
+    services::{cache, epochs},
+};
+
+/// `getSlotLeaders` refuses larger requests.
+const MAX_UPCOMING_LEADERS: u64 = 5000;
+
+#[derive(Debug, Serialize)]
+pub struct EpochsResponse {
+    pub epochs: Vec<EpochSummary>,
+    pub total: i64,
+    pub page: i32,
+    pub limit: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpcomingParams {
+    #[serde(default = "default_upcoming")]
+    pub limit: u64,
+}
+
+fn default_upcoming() -> u64 { 20 }
+
+/// The epoch schedule is fixed at genesis.
+async fn epoch_schedule(state: &AppState) -> Option<EpochSchedule> {
+    state.cache
+        .get_or_fetch("epochs:schedule", cache::FINALIZED, || {
+            state.solana_client.get_epoch_schedule().ok()
+        })
+        .await
+}
+
+async fn slot_duration(state: &AppState) -> f64 {
+    state.cache
+        .get_or_fetch("network:slot-duration", cache::SLOT_DURATION, || {
+            Some(epochs::slot_duration(state))
+        })
+        .await
+        .unwrap_or(epochs::DEFAULT_SLOT_DURATION)
+}
+
+/// Epochs from the current one backwards.
+pub async fn list_epochs(
+    State(state): State<AppState>,
+    Query(params): Query<ListParams>,
+) -> Json<EpochsResponse> {
+    let current_slot = state.solana_client.get_slot().unwrap_or(0);
+    let duration = slot_duration(&state).await;
+    let now = chrono::Utc::now().timestamp();
+    let limit = params.limit.clamp(1, 100) as u64;
+
+    let (epochs, total) = match epoch_schedule(&state).await {
+        Some(schedule) => {
+            let current = schedule.get_epoch(current_slot);
+            let offset = (params.page.max(1) as u64 - 1) * limit;
+            let epochs = match current.checked_sub(offset) {
+                Some(newest) => (0..=newest)
+                    .rev()
+                    .take(limit as usize)
+                    .map(|epoch| epochs::summary(&schedule, epoch, current_slot, duration, now))
+                    .collect(),
+                None => vec![],
+            };
+            (epochs, current as i64 + 1)
+        }
+        None => (vec![], 0),
+    };
+
+    Json(EpochsResponse {
+        epochs,
+        total,
+        page: params.page,
+        limit: limit as i32,
+    })
+}
+
+pub async fn get_epoch(
+    State(state): State<AppState>,
+    Path(epoch): Path<u64>,
+) -> Json<Option<EpochDetail>> {
+    let Some(schedule) = epoch_schedule(&state).await else {
+        return Json(None);
+    };
+    let current_slot = state.solana_client.get_slot().unwrap_or(0);
+    let current = schedule.get_epoch(current_slot);
+    if epoch > current + 1 {
+        return Json(None);
+    }
+
+    let duration = slot_duration(&state).await;
+    let summary = epochs::summary(&schedule, epoch, current_slot, duration, chrono::Utc::now().timestamp());
+
+    let inflation_rate = if epoch == current {
+        state.solana_client.get_inflation_rate().ok().map(|rate| rate.total)
+    } else {
+        None
+    };
+
+    let rewards = if epoch < current {
+        state.cache
+            .get_or_fetch(&format!("epochs:rewards:{}", epoch), cache::FINALIZED, || {
+                epochs::rewards(&state, &schedule, epoch)
+            })
+            .await
+    } else {
+        None
+    };
+
+    let first_slot = schedule.get_first_slot_in_epoch(epoch);
+    let leader_schedule = state.cache
+        .get_or_fetch(&format!("epochs:leaders:{}", epoch), cache::FINALIZED, || {
+            state.solana_client
+                .get_leader_schedule(Some(first_slot))
+                .ok()
+                .flatten()
+                .map(|leaders| epochs::group_schedule(leaders, first_slot))
+        })
+        .await
+        .unwrap_or_default();
+
+    Json(Some(EpochDetail {
+        summary,
+        inflation_rate,
+        rewards,
+        leader_schedule,
+    }))
+}
+
+/// Leaders for the next `limit` slots, starting at the current slot.
+pub async fn upcoming_leaders(
+    State(state): State<AppState>,
+    Query(params): Query<UpcomingParams>,
+) -> Json<Vec<UpcomingLeader>> {
+    let current_slot = state.solana_client.get_slot().unwrap_or(0);
+    let limit = params.limit.clamp(1, MAX_UPCOMING_LEADERS);
+    let duration = slot_duration(&state).await;
+    let now = chrono::Utc::now().timestamp();
+
+    let leaders = state.solana_client
+        .get_slot_leaders(current_slot, limit)
+        .unwrap_or_default();
+
+    Json(
+        leaders
+            .iter()
+            .enumerate()
+            .map(|(i, leader)| {
+                let slot = current_slot + i as u64;
+                UpcomingLeader {
+                    slot: slot as i64,
+                    leader: leader.to_string(),
+                    estimated_time: epochs::estimate_time(slot, current_slot, duration, now),
+                }
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,109 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use crate::{
+    AppState,
+    middleware::auth::{AdminUser, AuthUser},
+    models::{AddressLabel, UserAddressLabel},
+    routes::blocks::ListParams,
+    services::labels::{self, LabelError, NewLabel},
+};
+
+impl IntoResponse for LabelError {
+    fn into_response(self) -> Response {
+        match self {
+            LabelError::Invalid(message) => {
+                (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "message": message }))).into_response()
+            }
+            LabelError::Db(e) => {
+                tracing::error!("label write failed: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "message": "Label update failed" }))).into_response()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LabelsResponse {
+    pub labels: Vec<AddressLabel>,
+    pub page: i32,
+    pub limit: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PrivateLabelRequest {
+    pub address: String,
+    pub label: String,
+}
+
+pub async fn list_labels(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Query(params): Query<ListParams>,
+) -> Json<LabelsResponse> {
+    let limit = params.limit.clamp(1, 500) as i64;
+    let offset = (params.page.max(1) as i64 - 1) * limit;
+
+    Json(LabelsResponse {
+        labels: labels::list(&state.db, limit, offset).await.unwrap_or_default(),
+        page: params.page.max(1),
+        limit: limit as i32,
+    })
+}
+
+pub async fn upsert_label(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Json(payload): Json<NewLabel>,
+) -> Result<Json<AddressLabel>, LabelError> {
+    Ok(Json(labels::upsert(&state.db, &payload).await?))
+}
+
+pub async fn delete_label(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(address): Path<String>,
+) -> Result<Json<serde_json::Value>, LabelError> {
+    let deleted = labels::delete(&state.db, &address).await?;
+    Ok(Json(serde_json::json!({ "message": "Label deleted", "deleted": deleted })))
+}
+
+/// Bulk import from a CSV (`address,label[,category]`) or JSON array body.
+/// Nothing is written unless every row is valid.
+pub async fn import_labels(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    body: String,
+) -> Result<Json<serde_json::Value>, LabelError> {
+    let rows = labels::parse_import(&body).map_err(LabelError::Invalid)?;
+    let imported = labels::import(&state.db, &rows).await?;
+    Ok(Json(serde_json::json!({ "message": "Labels imported", "imported": imported })))
+}
+
+pub async fn list_private_labels(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Json<Vec<UserAddressLabel>> {
+    Json(labels::list_private(&state.db, user_id).await.unwrap_or_default())
+}
+
+pub async fn set_private_label(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(payload): Json<PrivateLabelRequest>,
+) -> Result<Json<UserAddressLabel>, LabelError> {
+    Ok(Json(labels::set_private(&state.db, user_id, &payload.address, &payload.label).await?))
+}
+
+pub async fn delete_private_label(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(address): Path<String>,
+) -> Result<Json<serde_json::Value>, LabelError> {
+    let deleted = labels::delete_private(&state.db, user_id, &address).await?;
+    Ok(Json(serde_json::json!({ "message": "Label deleted", "deleted": deleted })))
+}
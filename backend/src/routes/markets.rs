@@ -1,6 +1,55 @@
 use axum::{extract::{Path, Query, State}, Json};
-use serde::Serialize;
-use crate::{AppState, routes::tokens::TokenParams};
+use serde::{Deserialize, Serialize};
+use crate::{
+    AppState,
+    models::{Market, MarketPair, RouteQuote, Trade},
+    services::{
+        markets::{self, MarketFilter},
+        trades::{self, TradeFilter},
+    },
+};
+
+#[derive(Debug, Deserialize)]
+pub struct MarketParams {
+    #[serde(default = "default_page")]
+    pub page: i32,
+    #[serde(default = "default_limit")]
+    pub limit: i32,
+    /// One of volume_24h, liquidity, price_change_24h or updated_at.
+    #[serde(default = "default_sort")]
+    pub sort: String,
+    /// "asc" or "desc" (the default).
+    pub order: Option<String>,
+    /// "raydium", "raydium-cpmm", "raydium-clmm" or "orca".
+    pub dex: Option<String>,
+    /// Markets with this mint on either side.
+    pub mint: Option<String>,
+    pub min_liquidity: Option<f64>,
+}
+
+fn default_page() -> i32 { 1 }
+fn default_limit() -> i32 { 20 }
+fn default_sort() -> String { "volume_24h".to_string() }
+
+#[derive(Debug, Deserialize)]
+pub struct TradeParams {
+    #[serde(default = "default_page")]
+    pub page: i32,
+    #[serde(default = "default_limit")]
+    pub limit: i32,
+    /// Fee payer of the swap.
+    pub trader: Option<String>,
+    /// "buy" or "sell", from the trader's side.
+    pub side: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuoteParams {
+    /// Input amount in UI units.
+    pub amount: f64,
+    /// Side sold: "base" (the default), "quote", or either mint.
+    pub input: Option<String>,
+}
 
 #[derive(Debug, Serialize)]
 pub struct MarketsResponse {
@@ -11,32 +60,113 @@ pub struct MarketsResponse {
 }
 
 #[derive(Debug, Serialize)]
-pub struct Market {
-    pub pair: String,
-    pub base_token: String,
-    pub quote_token: String,
-    pub price: f64,
-    pub price_change_24h: f64,
-    pub volume_24h: f64,
-    pub liquidity: f64,
-    pub dex: String,
+pub struct TradesResponse {
+    pub trades: Vec<Trade>,
+    pub total: i64,
+    pub page: i32,
+    pub limit: i32,
 }
 
 pub async fn list_markets(
-    State(_state): State<AppState>,
-    Query(params): Query<TokenParams>,
+    State(state): State<AppState>,
+    Query(params): Query<MarketParams>,
 ) -> Json<MarketsResponse> {
+    let limit = params.limit.clamp(1, 100) as i64;
+    let offset = (params.page.max(1) as i64 - 1) * limit;
+    let filter = MarketFilter {
+        dex: params.dex,
+        mint: params.mint,
+        min_liquidity: params.min_liquidity,
+    };
+    let ascending = params.order.as_deref() == Some("asc");
+
+    let (markets, total) = markets::list(&state.db, &filter, &params.sort, ascending, limit, offset)
+        .await
+        .unwrap_or_default();
+
     Json(MarketsResponse {
-        markets: vec![],
-        total: 0,
-        page: params.page,
-        limit: params.limit,
+        markets,
+        total,
+        page: params.page.max(1),
+        limit: limit as i32,
     })
 }
 
+/// Every pool of a pair, by pool address or `BASE-QUOTE` mints or symbols.
 pub async fn get_market(
-    State(_state): State<AppState>,
-    Path(_pair): Path<String>,
-) -> Json<Option<Market>> {
-    Json(None)
+    State(state): State<AppState>,
+    Path(pair): Path<String>,
+) -> Json<Option<MarketPair>> {
+    Json(markets::pair(&state.db, &pair).await.ok().flatten())
+}
+
+/// Indexed swaps through a pool, or through every pool of a `BASE-QUOTE`
+/// pair, newest first.
+pub async fn get_market_trades(
+    State(state): State<AppState>,
+    Path(pair): Path<String>,
+    Query(params): Query<TradeParams>,
+) -> Json<TradesResponse> {
+    let limit = params.limit.clamp(1, 100) as i64;
+    let offset = (params.page.max(1) as i64 - 1) * limit;
+    let filter = TradeFilter {
+        trader: params.trader,
+        side: params.side,
+    };
+
+    let (trades, total) = match markets::resolve(&state.db, &pair).await.ok().flatten() {
+        Some(pair) => {
+            let pools = match pair.pool {
+                Some(pool) => vec![pool],
+                None => markets::pair_markets(&state.db, &pair.base_mint, &pair.quote_mint)
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|market| market.address)
+                    .collect(),
+            };
+            trades::list(&state.db, &pools, &pair.base_mint, &filter, limit, offset)
+                .await
+                .unwrap_or_default()
+        }
+        None => (vec![], 0),
+    };
+
+    Json(TradesResponse {
+        trades,
+        total,
+        page: params.page.max(1),
+        limit: limit as i32,
+    })
+}
+
+/// Estimated output of selling `amount` of one side of a pair through each
+/// of its pools. None if the pair doesn't resolve, has no pools or the
+/// amount isn't positive.
+pub async fn get_market_quote(
+    State(state): State<AppState>,
+    Path(pair): Path<String>,
+    Query(params): Query<QuoteParams>,
+) -> Json<Option<RouteQuote>> {
+    if !(params.amount > 0.0 && params.amount.is_finite()) {
+        return Json(None);
+    }
+    let Some(pair) = markets::pair(&state.db, &pair).await.ok().flatten() else {
+        return Json(None);
+    };
+    let sell_quote = matches!(params.input.as_deref(), Some(input) if input == "quote" || input == pair.quote_mint);
+    let routes = markets::quote(&pair.markets, params.amount, sell_quote);
+    let (input_mint, output_mint) = if sell_quote {
+        (pair.quote_mint, pair.base_mint)
+    } else {
+        (pair.base_mint, pair.quote_mint)
+    };
+
+    Json(Some(RouteQuote {
+        input_mint,
+        output_mint,
+        amount_in: params.amount,
+        amount_out: routes.first().map(|route| route.amount_out),
+        routes,
+    }))
 }
@@ -4,8 +4,12 @@ pub mod transactions;
 pub mod addresses;
 pub mod tokens;
 pub mod markets;
+pub mod nfts;
 pub mod network;
+pub mod epochs;
+pub mod validators;
 pub mod search;
+pub mod labels;
 pub mod auth;
 pub mod user;
 pub mod admin;
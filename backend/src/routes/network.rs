@@ -1,19 +1,139 @@
-use axum::{extract::State, Json};
-use crate::{AppState, models::NetworkStats};
+use axum::{extract::{Query, State}, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use crate::{
+    AppState,
+    models::{NetworkMetricPoint, NetworkStats, ThroughputSample},
+    services::{cache, network_history, throughput},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct ThroughputParams {
+    #[serde(default = "default_interval")]
+    pub interval: String,
+}
+
+fn default_interval() -> String { "1m".to_string() }
+
+#[derive(Debug, Serialize)]
+pub struct ThroughputResponse {
+    pub interval: String,
+    pub samples: Vec<ThroughputSample>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryParams {
+    pub metric: String,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub interval: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistoryResponse {
+    pub metric: String,
+    pub interval: String,
+    pub from: i64,
+    pub to: i64,
+    pub points: Vec<NetworkMetricPoint>,
+}
 
 pub async fn get_stats(State(state): State<AppState>) -> Json<NetworkStats> {
-    let slot = state.solana_client.get_slot().unwrap_or(0);
-    let block_height = state.solana_client.get_block_height().unwrap_or(0);
-    let epoch_info = state.solana_client.get_epoch_info().ok();
+    let stats = state.cache
+        .get_or_fetch("network:stats", cache::NETWORK_STATS, || fetch_stats(&state))
+        .await
+        .unwrap_or_default();
+
+    Json(stats)
+}
+
+/// Throughput series for charting: one point per minute over the last hour
+/// (`1m`), per five minutes over 12 hours (`5m`) or per hour over 12 hours (`1h`).
+pub async fn get_throughput(
+    State(state): State<AppState>,
+    Query(params): Query<ThroughputParams>,
+) -> Json<ThroughputResponse> {
+    let (bucket, count) = match params.interval.as_str() {
+        "5m" => (5, throughput::MAX_SAMPLES),
+        "1h" => (60, throughput::MAX_SAMPLES),
+        _ => (1, 60),
+    };
+
+    let samples = state.cache
+        .get_or_fetch("network:perf-samples", cache::PERFORMANCE_SAMPLES, || {
+            state.solana_client
+                .get_recent_performance_samples(Some(throughput::MAX_SAMPLES))
+                .ok()
+        })
+        .await
+        .unwrap_or_default();
+    let samples = &samples[..samples.len().min(count)];
+
+    Json(ThroughputResponse {
+        interval: params.interval,
+        samples: throughput::series(samples, bucket, chrono::Utc::now().timestamp()),
+    })
+}
+
+/// Recorded history of one metric. `from`/`to` are unix timestamps (default:
+/// the last 24 hours); `interval` is `raw`, `1h` or `1d`, chosen from the span
+/// when omitted.
+pub async fn get_history(
+    State(state): State<AppState>,
+    Query(params): Query<HistoryParams>,
+) -> Result<Json<HistoryResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let out_of_range = || (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "message": "Time out of range" })));
+    let to = match params.to {
+        Some(t) => DateTime::<Utc>::from_timestamp(t, 0).ok_or_else(out_of_range)?,
+        None => Utc::now(),
+    };
+    let from = match params.from {
+        Some(t) => DateTime::<Utc>::from_timestamp(t, 0).ok_or_else(out_of_range)?,
+        None => to.checked_sub_signed(chrono::Duration::days(1)).ok_or_else(out_of_range)?,
+    };
+
+    let interval = match params.interval.as_deref() {
+        Some(interval @ ("raw" | "1h" | "1d")) => interval,
+        _ => network_history::resolution_for(from, to),
+    };
+
+    let points = if network_history::METRICS.contains(&params.metric.as_str()) {
+        network_history::history(&state.db, &params.metric, interval, from, to)
+            .await
+            .unwrap_or_default()
+    } else {
+        vec![]
+    };
+
+    Ok(Json(HistoryResponse {
+        metric: params.metric,
+        interval: interval.to_string(),
+        from: from.timestamp(),
+        to: to.timestamp(),
+        points,
+    }))
+}
+
+/// Current network stats, or None when the RPC node can't be read, so
+/// zeroes are never cached in their place.
+fn fetch_stats(state: &AppState) -> Option<NetworkStats> {
+    let slot = state.solana_client.get_slot().ok()?;
+    let block_height = state.solana_client.get_block_height().ok()?;
+    let epoch_info = state.solana_client.get_epoch_info().ok()?;
+    let transaction_count = state.solana_client.get_transaction_count().ok()?;
+    let samples = state.solana_client
+        .get_recent_performance_samples(Some(60))
+        .ok()?;
+    let (tps, non_vote_tps) = throughput::average(&samples[..samples.len().min(1)]);
     
-    Json(NetworkStats {
+    Some(NetworkStats {
         slot: slot as i64,
         block_height: block_height as i64,
-        tps: 2500.0, // Mock TPS
-        total_transactions: 1_000_000_000,
-        epoch: epoch_info.as_ref().map(|e| e.epoch as i64).unwrap_or(0),
-        epoch_progress: epoch_info
-            .map(|e| (e.slot_index as f64 / e.slots_in_epoch as f64) * 100.0)
-            .unwrap_or(0.0),
+        tps,
+        non_vote_tps,
+        total_transactions: transaction_count as i64,
+        epoch: epoch_info.epoch as i64,
+        epoch_progress: (epoch_info.slot_index as f64 / epoch_info.slots_in_epoch as f64) * 100.0,
+        throughput: throughput::windows(&samples),
     })
 }
@@ -0,0 +1,58 @@
+use axum::{extract::{Path, Query, State}, Json};
+use serde::{Deserialize, Serialize};
+use crate::{
+    AppState,
+    models::{Nft, NftDetails},
+    services::nfts,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CollectionParams {
+    #[serde(default = "default_page")]
+    pub page: i32,
+    #[serde(default = "default_limit")]
+    pub limit: i32,
+}
+
+fn default_page() -> i32 { 1 }
+fn default_limit() -> i32 { 50 }
+
+#[derive(Debug, Serialize)]
+pub struct CollectionResponse {
+    pub collection: String,
+    pub nfts: Vec<Nft>,
+    pub total: i64,
+    pub page: i32,
+    pub limit: i32,
+}
+
+/// An NFT by mint, or a compressed NFT by asset id, with its traits and
+/// ownership history.
+pub async fn get_nft(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+) -> Json<Option<NftDetails>> {
+    Json(nfts::details(&state, &mint).await)
+}
+
+/// Known NFTs of a collection, by name.
+pub async fn get_collection(
+    State(state): State<AppState>,
+    Path(collection): Path<String>,
+    Query(params): Query<CollectionParams>,
+) -> Json<CollectionResponse> {
+    let limit = params.limit.clamp(1, 100) as i64;
+    let offset = (params.page.max(1) as i64 - 1) * limit;
+
+    let (nfts, total) = nfts::collection(&state.db, &collection, limit, offset)
+        .await
+        .unwrap_or_default();
+
+    Json(CollectionResponse {
+        collection,
+        nfts,
+        total,
+        page: params.page.max(1),
+        limit: limit as i32,
+    })
+}
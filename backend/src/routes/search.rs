@@ -1,38 +1,198 @@
 use axum::{extract::{Query, State}, Json};
 use serde::{Deserialize, Serialize};
-use crate::AppState;
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Signature};
+use std::str::FromStr;
+use uuid::Uuid;
+use crate::{
+    AppState,
+    middleware::auth::AuthUser,
+    models::{ProgramAccount, SearchEntry},
+    routes::{addresses, blocks, transactions},
+    services::{blocks as block_service, cache, search_index, sns, solana, token_metadata},
+};
+
+/// How many name matches a search returns as candidates.
+const NAME_MATCHES: i64 = 10;
 
 #[derive(Debug, Deserialize)]
 pub struct SearchQuery {
     pub q: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SuggestQuery {
+    pub q: String,
+    #[serde(default = "default_suggest_limit")]
+    pub limit: i64,
+}
+
+fn default_suggest_limit() -> i64 { 8 }
+
 #[derive(Debug, Serialize)]
 pub struct SearchResult {
     #[serde(rename = "type")]
     pub result_type: String,
     pub result: Option<serde_json::Value>,
+    /// Every entity the query resolved to, best match first. More than one
+    /// when the query is ambiguous, e.g. a number that is both a slot and a
+    /// block height.
+    pub candidates: Vec<SearchMatch>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchMatch {
+    #[serde(rename = "type")]
+    pub result_type: String,
+    pub result: serde_json::Value,
 }
 
+impl SearchMatch {
+    fn new(result_type: &str, result: impl Serialize) -> Option<Self> {
+        Some(Self {
+            result_type: result_type.to_string(),
+            result: serde_json::to_value(result).ok()?,
+        })
+    }
+}
+
+/// Resolves `q` against the chain: a number is a slot or block height, a
+/// 64-byte base58 string a transaction signature, and a 32-byte one an
+/// account (token mint, program or plain address) or a known blockhash.
+/// A `.sol` domain resolves to its owner's address; anything else is looked
+/// up by name in the search index.
 pub async fn search(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
+    user: Option<AuthUser>,
     Query(query): Query<SearchQuery>,
 ) -> Json<SearchResult> {
     let q = query.q.trim();
-    
-    // Detect type based on query format
-    let result_type = if q.parse::<u64>().is_ok() {
-        "block"
-    } else if q.len() == 87 || q.len() == 88 {
-        "transaction"
-    } else if q.len() >= 32 && q.len() <= 44 {
-        "address"
+    let user = user.map(|u| u.0);
+
+    let candidates = if let Ok(number) = q.parse::<u64>() {
+        search_number(&state, number).await
+    } else if Signature::from_str(q).is_ok() {
+        search_signature(&state, q, user).await
+    } else if let Ok(pubkey) = Pubkey::from_str(q) {
+        search_pubkey(&state, &pubkey, user).await
+    } else if sns::is_domain(q) {
+        search_domain(&state, q, user).await
     } else {
-        "unknown"
+        search_names(&state, q).await
     };
-    
+
+    let best = candidates.first();
     Json(SearchResult {
-        result_type: result_type.to_string(),
-        result: None,
+        result_type: best.map(|m| m.result_type.clone()).unwrap_or_else(|| "unknown".to_string()),
+        result: best.map(|m| m.result.clone()),
+        candidates,
     })
 }
+
+/// Prefix autocomplete over token, label, program and domain names.
+pub async fn suggest(
+    State(state): State<AppState>,
+    Query(query): Query<SuggestQuery>,
+) -> Json<Vec<SearchEntry>> {
+    let q = query.q.trim();
+    if q.is_empty() {
+        return Json(vec![]);
+    }
+
+    Json(search_index::suggest(&state.db, q, query.limit.clamp(1, 50)).await.unwrap_or_default())
+}
+
+async fn search_names(state: &AppState, q: &str) -> Vec<SearchMatch> {
+    if q.is_empty() {
+        return vec![];
+    }
+
+    search_index::search(&state.db, q, NAME_MATCHES)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entry| {
+            let kind = entry.kind.clone();
+            SearchMatch::new(&kind, entry)
+        })
+        .collect()
+}
+
+/// Registered domains resolve to their owner; unregistered ones may still
+/// match indexed names.
+async fn search_domain(state: &AppState, domain: &str, user: Option<Uuid>) -> Vec<SearchMatch> {
+    match addresses::fetch_address(state, domain, user).await {
+        Some(details) => SearchMatch::new("address", details).into_iter().collect(),
+        None => search_names(state, domain).await,
+    }
+}
+
+async fn search_number(state: &AppState, number: u64) -> Vec<SearchMatch> {
+    let mut matches = Vec::new();
+    if let Some(block) = blocks::block_summary(state, number).await {
+        matches.extend(SearchMatch::new("block", block));
+    }
+
+    match block_service::slot_for_height(state, number).await {
+        Some(slot) if slot != number => {
+            if let Some(block) = blocks::block_summary(state, slot).await {
+                matches.extend(SearchMatch::new("block", block));
+            }
+        }
+        _ => {}
+    }
+    matches
+}
+
+async fn search_signature(state: &AppState, signature: &str, user: Option<Uuid>) -> Vec<SearchMatch> {
+    transactions::fetch_transaction(state, signature, user)
+        .await
+        .and_then(|tx| SearchMatch::new("transaction", tx))
+        .into_iter()
+        .collect()
+}
+
+async fn search_pubkey(state: &AppState, pubkey: &Pubkey, user: Option<Uuid>) -> Vec<SearchMatch> {
+    let address = pubkey.to_string();
+    let mut matches = Vec::new();
+
+    let account: Option<Account> = state.cache
+        .get_or_fetch(&format!("account:{}", address), cache::BALANCE, || {
+            state.solana_client
+                .get_account_with_commitment(pubkey, state.solana_client.commitment())
+                .ok()
+                .and_then(|response| response.value)
+        })
+        .await;
+
+    if let Some(account) = &account {
+        if solana::decode_mint(&account.owner, &account.data).is_some() {
+            if let Some(token) = token_metadata::fetch(state, pubkey).await {
+                matches.extend(SearchMatch::new("token", token));
+            }
+        } else if account.executable {
+            matches.extend(SearchMatch::new("program", ProgramAccount {
+                address: address.clone(),
+                loader: account.owner.to_string(),
+                lamports: account.lamports,
+                data_len: account.data.len(),
+            }));
+        }
+    }
+
+    // Blockhashes share the pubkey encoding. Only hashes of blocks the
+    // follower has indexed are known.
+    if let Some(slot) = block_service::slot_for_hash(&state.db, &address).await {
+        if let Some(block) = blocks::block_summary(state, slot).await {
+            matches.extend(SearchMatch::new("block", block));
+        }
+    }
+
+    // Any valid pubkey is a valid address, funded or not, so fall back to it
+    // when nothing more specific matched.
+    if account.is_some() || matches.is_empty() {
+        if let Some(details) = addresses::fetch_address(state, &address, user).await {
+            matches.extend(SearchMatch::new("address", details));
+        }
+    }
+    matches
+}
@@ -1,6 +1,41 @@
-use axum::{extract::{Path, Query, State}, Json};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use crate::{AppState, routes::blocks::ListParams};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use crate::{
+    AppState,
+    middleware::auth::AdminUser,
+    models::{Candles, Token, TokenDetails, TokenHolders, TokenRisk, TokenTransfer},
+    services::{
+        candles,
+        solana,
+        token_holders,
+        token_list::{self, ImportError, TokenFilter},
+        token_metadata,
+        token_risk,
+        transfers::{self, TransferFilter},
+    },
+};
+
+impl IntoResponse for ImportError {
+    fn into_response(self) -> Response {
+        match self {
+            ImportError::Invalid(message) => {
+                (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "message": message }))).into_response()
+            }
+            ImportError::Db(e) => {
+                tracing::error!("token list import failed: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "message": "Import failed" }))).into_response()
+            }
+        }
+    }
+}
 
 #[derive(Debug, Deserialize)]
 pub struct TokenParams {
@@ -8,8 +43,15 @@ pub struct TokenParams {
     pub page: i32,
     #[serde(default = "default_limit")]
     pub limit: i32,
+    /// One of volume_24h, market_cap, price, price_change_24h or holders.
     #[serde(default = "default_sort")]
     pub sort: String,
+    /// "asc" or "desc" (the default).
+    pub order: Option<String>,
+    pub verified: Option<bool>,
+    /// "spl-token", "token-2022" or a token program id.
+    pub program: Option<String>,
+    pub min_liquidity: Option<f64>,
 }
 
 fn default_page() -> i32 { 1 }
@@ -24,47 +66,162 @@ pub struct TokensResponse {
     pub limit: i32,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CandleParams {
+    /// 1m, 5m, 1h or 1d.
+    #[serde(default = "default_interval")]
+    pub interval: String,
+    /// Unix seconds.
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+fn default_interval() -> String { "1h".to_string() }
+
+#[derive(Debug, Deserialize)]
+pub struct TransferParams {
+    #[serde(default = "default_page")]
+    pub page: i32,
+    #[serde(default = "default_limit")]
+    pub limit: i32,
+    /// Owner wallet of the sending account.
+    pub from: Option<String>,
+    /// Owner wallet of the receiving account.
+    pub to: Option<String>,
+    /// UI amount bounds, inclusive.
+    pub min_amount: Option<f64>,
+    pub max_amount: Option<f64>,
+    /// Unix seconds, inclusive.
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+}
+
 #[derive(Debug, Serialize)]
-pub struct Token {
-    pub mint: String,
-    pub symbol: String,
-    pub name: String,
-    pub price: f64,
-    pub price_change_24h: f64,
-    pub volume_24h: f64,
-    pub market_cap: f64,
-    pub holders: i32,
+pub struct TransfersResponse {
+    pub transfers: Vec<TokenTransfer>,
+    pub total: i64,
+    pub page: i32,
+    pub limit: i32,
 }
 
 pub async fn list_tokens(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Query(params): Query<TokenParams>,
 ) -> Json<TokensResponse> {
-    // Mock data - in production, fetch from Jupiter/DexScreener API
-    let tokens = vec![
-        Token {
-            mint: "So11111111111111111111111111111111111111112".to_string(),
-            symbol: "SOL".to_string(),
-            name: "Solana".to_string(),
-            price: 100.0,
-            price_change_24h: 5.2,
-            volume_24h: 1_000_000.0,
-            market_cap: 50_000_000_000.0,
-            holders: 1_000_000,
-        },
-    ];
-    
+    let limit = params.limit.clamp(1, 100) as i64;
+    let offset = (params.page.max(1) as i64 - 1) * limit;
+    let program = params.program.as_deref().map(|program| match program {
+        "spl-token" => solana::TOKEN_PROGRAM_ID.to_string(),
+        "token-2022" => solana::TOKEN_2022_PROGRAM_ID.to_string(),
+        other => other.to_string(),
+    });
+    let filter = TokenFilter {
+        verified: params.verified,
+        program,
+        min_liquidity: params.min_liquidity,
+    };
+    let ascending = params.order.as_deref() == Some("asc");
+
+    let (tokens, total) = token_list::list(&state.db, &filter, &params.sort, ascending, limit, offset)
+        .await
+        .unwrap_or_default();
+
     Json(TokensResponse {
         tokens,
-        total: 1,
-        page: params.page,
-        limit: params.limit,
+        total,
+        page: params.page.max(1),
+        limit: limit as i32,
     })
 }
 
+/// Bulk verification from a token list: a JSON array of entries or a
+/// document with a `tokens` array. Nothing is written unless every mint is
+/// valid.
+pub async fn import_verified_tokens(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    body: String,
+) -> Result<Json<serde_json::Value>, ImportError> {
+    let tokens = token_list::parse_verified_list(&body).map_err(ImportError::Invalid)?;
+    let imported = token_list::import_verified(&state.db, &tokens).await?;
+    Ok(Json(serde_json::json!({ "message": "Tokens imported", "imported": imported })))
+}
+
+/// Mint fields plus on- and off-chain metadata. None if `mint` isn't a
+/// token mint.
 pub async fn get_token(
-    State(_state): State<AppState>,
-    Path(_mint): Path<String>,
-) -> Json<Option<Token>> {
-    Json(None)
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+) -> Json<Option<TokenDetails>> {
+    match Pubkey::from_str(&mint) {
+        Ok(mint) => Json(token_metadata::fetch(&state, &mint).await),
+        Err(_) => Json(None),
+    }
+}
+
+/// Largest holders of `mint` and its indexed holder count. None if `mint`
+/// isn't a token mint.
+pub async fn get_token_holders(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+) -> Json<Option<TokenHolders>> {
+    match Pubkey::from_str(&mint) {
+        Ok(mint) => Json(token_holders::top_holders(&state, &mint).await),
+        Err(_) => Json(None),
+    }
+}
+
+/// Risk report for `mint`. None if `mint` isn't a token mint.
+pub async fn get_token_risk(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+) -> Json<Option<TokenRisk>> {
+    match Pubkey::from_str(&mint) {
+        Ok(mint) => Json(token_risk::report(&state, &mint).await),
+        Err(_) => Json(None),
+    }
+}
+
+/// OHLCV candles for `mint`. None for an invalid mint or interval.
+pub async fn get_token_candles(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(params): Query<CandleParams>,
+) -> Json<Option<Candles>> {
+    if Pubkey::from_str(&mint).is_err() {
+        return Json(None);
+    }
+    let from = params.from.and_then(|t| DateTime::<Utc>::from_timestamp(t, 0));
+    let to = params.to.and_then(|t| DateTime::<Utc>::from_timestamp(t, 0));
+
+    Json(candles::candles(&state, &mint, &params.interval, from, to).await)
+}
+
+/// Indexed transfers, mints and burns of `mint`, newest first.
+pub async fn get_token_transfers(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(params): Query<TransferParams>,
+) -> Json<TransfersResponse> {
+    let limit = params.limit.clamp(1, 100) as i64;
+    let offset = (params.page.max(1) as i64 - 1) * limit;
+    let filter = TransferFilter {
+        from_owner: params.from,
+        to_owner: params.to,
+        min_amount: params.min_amount,
+        max_amount: params.max_amount,
+        start: params.start_time.and_then(|t| DateTime::<Utc>::from_timestamp(t, 0)),
+        end: params.end_time.and_then(|t| DateTime::<Utc>::from_timestamp(t, 0)),
+    };
+
+    let (transfers, total) = transfers::list(&state.db, &mint, &filter, limit, offset)
+        .await
+        .unwrap_or_default();
+
+    Json(TransfersResponse {
+        transfers,
+        total,
+        page: params.page.max(1),
+        limit: limit as i32,
+    })
 }
@@ -1,6 +1,13 @@
 use axum::{extract::{Path, Query, State}, Json};
 use serde::Serialize;
-use crate::{AppState, models::Transaction, routes::blocks::ListParams};
+use uuid::Uuid;
+use crate::{
+    AppState,
+    middleware::auth::AuthUser,
+    models::Transaction,
+    routes::blocks::ListParams,
+    services::{blocks, cache, labels, solana},
+};
 
 #[derive(Debug, Serialize)]
 pub struct TransactionsResponse {
@@ -27,32 +34,45 @@ pub async fn list_transactions(
 
 pub async fn get_transaction(
     State(state): State<AppState>,
+    user: Option<AuthUser>,
     Path(signature): Path<String>,
 ) -> Json<Option<Transaction>> {
+    Json(fetch_transaction(&state, &signature, user.map(|u| u.0)).await)
+}
+
+/// Looks up a transaction by signature. Shared with search, which needs the
+/// same entity without going through the HTTP extractors.
+pub async fn fetch_transaction(state: &AppState, signature: &str, user: Option<Uuid>) -> Option<Transaction> {
     use solana_sdk::signature::Signature;
     use std::str::FromStr;
     
-    if let Ok(sig) = Signature::from_str(&signature) {
-        if let Ok(tx) = state.solana_client.get_transaction(
-            &sig,
-            solana_transaction_status::UiTransactionEncoding::Json,
-        ) {
-            let meta = tx.transaction.meta.as_ref();
-            return Json(Some(Transaction {
-                signature: signature.clone(),
-                block_number: tx.slot as i64,
-                slot: tx.slot as i64,
-                timestamp: tx.block_time.unwrap_or(0),
-                fee: meta.map(|m| m.fee as i64).unwrap_or(0),
-                status: if meta.map(|m| m.err.is_none()).unwrap_or(false) {
-                    "success".to_string()
-                } else {
-                    "failed".to_string()
-                },
-                signer: String::new(),
-            }));
-        }
-    }
-    
-    Json(None)
+    let sig = Signature::from_str(signature).ok()?;
+    let tx = state.cache
+        .get_or_fetch(&format!("tx:{}", signature), cache::FINALIZED, || {
+            state.solana_client.get_transaction_with_config(&sig, solana::transaction_config()).ok()
+        })
+        .await?;
+
+    let block_number = state.cache
+        .get_or_fetch(&format!("block-height:{}", tx.slot), cache::FINALIZED, || {
+            blocks::block_height(state, tx.slot)
+        })
+        .await;
+
+    let meta = tx.transaction.meta.as_ref();
+    let accounts = solana::account_keys(&tx.transaction.transaction);
+    Some(Transaction {
+        signature: signature.to_string(),
+        block_number: block_number.map(|height| height as i64),
+        slot: tx.slot as i64,
+        timestamp: tx.block_time.unwrap_or(0),
+        fee: meta.map(|m| m.fee as i64).unwrap_or(0),
+        status: if meta.map(|m| m.err.is_none()).unwrap_or(false) {
+            "success".to_string()
+        } else {
+            "failed".to_string()
+        },
+        signer: accounts.first().cloned().unwrap_or_default(),
+        labels: labels::lookup(&state.db, user, &accounts).await,
+    })
 }
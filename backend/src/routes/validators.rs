@@ -0,0 +1,70 @@
+use axum::{extract::{Path, Query, State}, Json};
+use serde::Serialize;
+use std::cmp::Ordering;
+use crate::{AppState, models::Validator, routes::blocks::ListParams, services::{cache, validators}};
+
+#[derive(Debug, Serialize)]
+pub struct ValidatorsResponse {
+    pub validators: Vec<Validator>,
+    pub total: i64,
+    pub page: i32,
+    pub limit: i32,
+}
+
+async fn load_directory(state: &AppState) -> Vec<Validator> {
+    state.cache
+        .get_or_fetch("validators:directory", cache::VALIDATORS, || validators::directory(state))
+        .await
+        .unwrap_or_default()
+}
+
+/// Lists validators. `sort` is one of `stake` (default), `commission`,
+/// `skip_rate`, `last_vote` or `root_slot`; `order` defaults to `desc`.
+pub async fn list_validators(
+    State(state): State<AppState>,
+    Query(params): Query<ListParams>,
+) -> Json<ValidatorsResponse> {
+    let mut validators = load_directory(&state).await;
+
+    let key = |v: &Validator| -> f64 {
+        match params.sort.as_deref() {
+            Some("commission") => v.commission as f64,
+            Some("skip_rate") => v.skip_rate.unwrap_or(0.0),
+            Some("last_vote") => v.last_vote as f64,
+            Some("root_slot") => v.root_slot as f64,
+            _ => v.activated_stake,
+        }
+    };
+    validators.sort_by(|a, b| key(a).partial_cmp(&key(b)).unwrap_or(Ordering::Equal));
+    if params.order.as_deref() != Some("asc") {
+        validators.reverse();
+    }
+
+    let total = validators.len() as i64;
+    let limit = params.limit.clamp(1, 100) as i64;
+    let offset = (params.page.max(1) as i64 - 1) * limit;
+    let validators = validators
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect();
+
+    Json(ValidatorsResponse {
+        validators,
+        total,
+        page: params.page,
+        limit: limit as i32,
+    })
+}
+
+pub async fn get_validator(
+    State(state): State<AppState>,
+    Path(vote_pubkey): Path<String>,
+) -> Json<Option<Validator>> {
+    Json(
+        load_directory(&state)
+            .await
+            .into_iter()
+            .find(|v| v.vote_pubkey == vote_pubkey),
+    )
+}
@@ -0,0 +1,126 @@
+// Block follower: walks the chain from a saved cursor, fetching each produced
+// block once and handing it to the token transfer, DEX trade and compressed
+// NFT indexes, and recording its hash.
+use solana_client::{client_error::ClientError, rpc_config::RpcBlockConfig};
+use solana_transaction_status::{TransactionDetails, UiConfirmedBlock, UiTransactionEncoding};
+use std::{fmt, time::Duration};
+use crate::{
+    AppState,
+    services::{blocks, bubblegum, solana, trades, transfers},
+};
+
+const CURSOR: &str = "blocks";
+/// Most blocks indexed per catch-up pass, so one pass stays short.
+const MAX_BLOCKS_PER_PASS: u64 = 50;
+
+/// Why a block couldn't be indexed. Either way the cursor stays before it,
+/// so it is retried on the next pass.
+#[derive(Debug)]
+pub enum IndexError {
+    Rpc(ClientError),
+    Db(sqlx::Error),
+}
+
+impl From<sqlx::Error> for IndexError {
+    fn from(e: sqlx::Error) -> Self {
+        IndexError::Db(e)
+    }
+}
+
+impl From<ClientError> for IndexError {
+    fn from(e: ClientError) -> Self {
+        IndexError::Rpc(e)
+    }
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexError::Rpc(e) => write!(f, "rpc: {}", e),
+            IndexError::Db(e) => write!(f, "db: {}", e),
+        }
+    }
+}
+
+/// The block at `slot` with its full transactions, jsonParsed.
+pub fn fetch(state: &AppState, slot: u64) -> Result<UiConfirmedBlock, ClientError> {
+    let config = RpcBlockConfig {
+        encoding: Some(UiTransactionEncoding::JsonParsed),
+        ..solana::block_config(TransactionDetails::Full, false)
+    };
+    state.solana_client.get_block_with_config(slot, config)
+}
+
+/// Runs every index over the block at `slot` and returns how many records
+/// they wrote between them.
+pub async fn index_block(state: &AppState, slot: u64) -> Result<usize, IndexError> {
+    let block = fetch(state, slot)?;
+    let mut recorded = transfers::index(&state.db, slot, &block).await?;
+    recorded += trades::index(&state.db, slot, &block).await?;
+    recorded += bubblegum::index(&state.db, slot, &block).await?;
+    blocks::record_hash(&state.db, &block.blockhash, slot).await?;
+    Ok(recorded)
+}
+
+async fn save_cursor(state: &AppState, slot: u64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO indexer_cursors (name, slot) VALUES ($1, $2)
+         ON CONFLICT (name) DO UPDATE SET slot = EXCLUDED.slot, updated_at = NOW()"
+    )
+    .bind(CURSOR)
+    .bind(slot as i64)
+    .execute(&state.db)
+    .await?;
+    Ok(())
+}
+
+/// Indexes produced blocks after the saved cursor, up to
+/// `MAX_BLOCKS_PER_PASS` of them. A fresh follower starts at the tip. When a
+/// block fails, the cursor only moves past the blocks before it.
+pub async fn catch_up(state: &AppState) -> Result<usize, IndexError> {
+    let tip = state.solana_client.get_slot()?;
+    let cursor: Option<i64> = sqlx::query_scalar("SELECT slot FROM indexer_cursors WHERE name = $1")
+        .bind(CURSOR)
+        .fetch_optional(&state.db)
+        .await?;
+    let start = cursor.map(|slot| slot as u64 + 1).unwrap_or(tip);
+    if start > tip {
+        return Ok(0);
+    }
+    let end = tip.min(start + MAX_BLOCKS_PER_PASS - 1);
+
+    let slots = state.solana_client.get_blocks(start, Some(end))?;
+    let mut recorded = 0;
+    for slot in slots {
+        match index_block(state, slot).await {
+            Ok(count) => recorded += count,
+            Err(e) => {
+                if slot > start {
+                    save_cursor(state, slot - 1).await?;
+                }
+                return Err(e);
+            }
+        }
+    }
+    save_cursor(state, end).await?;
+    Ok(recorded)
+}
+
+/// Starts the background task that follows the chain every
+/// `BLOCK_INDEX_INTERVAL_SECS` (default 5).
+pub fn spawn_follower(state: AppState) {
+    let every = std::env::var("BLOCK_INDEX_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(every));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = catch_up(&state).await {
+                tracing::warn!("failed to index blocks: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,206 @@
+use solana_sdk::reward_type::RewardType;
+use solana_transaction_status::{EncodedTransactionWithStatusMeta, TransactionDetails, UiConfirmedBlock};
+use std::collections::HashMap;
+
+use crate::{
+    AppState,
+    models::{Block, BlockDetails, BlockRewards, BlockTransaction},
+    services::{cache, solana},
+};
+
+pub fn transaction_row(tx: &EncodedTransactionWithStatusMeta) -> BlockTransaction {
+    let meta = tx.meta.as_ref();
+
+    BlockTransaction {
+        signature: solana::signature(&tx.transaction).unwrap_or_default(),
+        signer: solana::account_keys(&tx.transaction).into_iter().next().unwrap_or_default(),
+        fee: meta.map(|m| m.fee as i64).unwrap_or(0),
+        status: if meta.map(|m| m.err.is_none()).unwrap_or(false) {
+            "success".to_string()
+        } else {
+            "failed".to_string()
+        },
+        compute_units_consumed: meta
+            .and_then(|m| Option::<u64>::from(m.compute_units_consumed.clone()))
+            .map(|units| units as i64),
+        vote: solana::is_vote(&tx.transaction),
+    }
+}
+
+pub fn rewards(block: &UiConfirmedBlock) -> BlockRewards {
+    let mut rewards = BlockRewards::default();
+    for reward in block.rewards.iter().flatten() {
+        let sol = reward.lamports as f64 / 1_000_000_000.0;
+        match reward.reward_type {
+            Some(RewardType::Fee) => rewards.fee += sol,
+            Some(RewardType::Rent) => rewards.rent += sol,
+            Some(RewardType::Staking) => rewards.staking += sol,
+            Some(RewardType::Voting) => rewards.voting += sol,
+            None => {}
+        }
+    }
+    rewards
+}
+
+/// Summarises every transaction in `block` and returns one page of them,
+/// optionally leaving out validator votes.
+pub fn details(
+    block: Block,
+    full: &UiConfirmedBlock,
+    include_votes: bool,
+    page: i32,
+    limit: i32,
+) -> BlockDetails {
+    let rows: Vec<BlockTransaction> = full.transactions
+        .iter()
+        .flatten()
+        .map(transaction_row)
+        .collect();
+
+    let total_fees = rows.iter().map(|tx| tx.fee).sum();
+    let compute_units_consumed = rows.iter().filter_map(|tx| tx.compute_units_consumed).sum();
+    let failed_transactions = rows.iter().filter(|tx| tx.status == "failed").count() as i32;
+    let vote_transactions = rows.iter().filter(|tx| tx.vote).count() as i32;
+
+    let filtered: Vec<BlockTransaction> = rows
+        .into_iter()
+        .filter(|tx| include_votes || !tx.vote)
+        .collect();
+    let transactions_total = filtered.len() as i64;
+    let limit = limit.clamp(1, 100);
+    let offset = (page.max(1) as i64 - 1) * limit as i64;
+
+    BlockDetails {
+        successful_transactions: block.transactions_count - failed_transactions,
+        block,
+        total_fees,
+        compute_units_consumed,
+        failed_transactions,
+        vote_transactions,
+        rewards: rewards(full),
+        transactions: filtered.into_iter().skip(offset as usize).take(limit as usize).collect(),
+        transactions_total,
+        page,
+        limit,
+        labels: HashMap::new(),
+    }
+}
+
+/// `getBlocks` refuses wider ranges.
+const MAX_BLOCKS_RANGE: u64 = 500_000;
+
+/// Height of the block at `slot`, read without its transactions.
+pub fn block_height(state: &AppState, slot: u64) -> Option<u64> {
+    state.solana_client
+        .get_block_with_config(slot, solana::block_config(TransactionDetails::None, false))
+        .ok()?
+        .block_height
+}
+
+/// Produced slots in `start..=end`, oldest first.
+fn produced_slots(state: &AppState, start: u64, end: u64) -> Vec<u64> {
+    state.solana_client
+        .get_blocks(start, Some(end))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|slot| (start..=end).contains(slot))
+        .collect()
+}
+
+/// Resolves a block height to its slot. Only heights up to the finalized tip
+/// resolve, so a resolved slot never changes and is cached for good.
+pub async fn slot_for_height(state: &AppState, height: u64) -> Option<u64> {
+    state.cache
+        .get_or_fetch(&format!("height-slot:{}", height), cache::FINALIZED, || find_slot_for_height(state, height))
+        .await
+}
+
+/// Every skipped slot widens the gap between slot and height, so the target
+/// slot is at most `tip_slot - (tip_height - height)`; from there we count
+/// produced blocks backwards.
+fn find_slot_for_height(state: &AppState, height: u64) -> Option<u64> {
+    let tip = state.solana_client.get_epoch_info().ok()?;
+    if height > tip.block_height {
+        return None;
+    }
+
+    let mut upper = tip.absolute_slot - (tip.block_height - height);
+    for _ in 0..8 {
+        let slot = *produced_slots(state, upper.saturating_sub(512), upper).last()?;
+        let slot_height = block_height(state, slot)?;
+        if slot_height == height {
+            return Some(slot);
+        }
+
+        let missing = slot_height.checked_sub(height)?;
+        let start = slot.saturating_sub((missing * 2 + 64).min(MAX_BLOCKS_RANGE));
+        let produced = produced_slots(state, start, slot - 1);
+        if produced.len() as u64 >= missing {
+            return Some(produced[produced.len() - missing as usize]);
+        }
+        upper = start;
+    }
+
+    None
+}
+
+/// Records that the block at `slot` has hash `blockhash`.
+pub async fn record_hash(db: &sqlx::PgPool, blockhash: &str, slot: u64) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO block_hashes (blockhash, slot) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+        .bind(blockhash)
+        .bind(slot as i64)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// The slot of a block the follower has indexed, by its hash.
+pub async fn slot_for_hash(db: &sqlx::PgPool, blockhash: &str) -> Option<u64> {
+    sqlx::query_scalar::<_, i64>("SELECT slot FROM block_hashes WHERE blockhash = $1")
+        .bind(blockhash)
+        .fetch_optional(db)
+        .await
+        .ok()?
+        .map(|slot| slot as u64)
+}
+
+/// The produced slot whose block time is closest to `timestamp`, found by
+/// binary search over the ledger history the node still has.
+pub fn slot_at(state: &AppState, timestamp: i64) -> Option<u64> {
+    let next_produced = |slot: u64| -> Option<u64> {
+        state.solana_client.get_blocks_with_limit(slot, 1).ok()?.first().copied()
+    };
+    let time = |slot: u64| state.solana_client.get_block_time(slot).ok();
+
+    let tip = state.solana_client.get_slot().ok()?;
+    let first = next_produced(state.solana_client.get_first_available_block().unwrap_or(0))?;
+    if time(first)? >= timestamp {
+        return Some(first);
+    }
+    let tip = *produced_slots(state, tip.saturating_sub(512), tip).last()?;
+    if time(tip)? <= timestamp {
+        return Some(tip);
+    }
+
+    // `lo` and `hi` are produced blocks either side of `timestamp`; every
+    // produced slot in `bound..hi` is known to be later than `timestamp`.
+    let (mut lo, mut hi, mut bound) = (first, tip, tip);
+    while bound - lo > 1 {
+        let mid = lo + (bound - lo) / 2;
+        match next_produced(mid) {
+            Some(probe) if probe < bound => match time(probe)? {
+                t if t == timestamp => return Some(probe),
+                t if t < timestamp => lo = probe,
+                _ => {
+                    hi = probe;
+                    bound = mid;
+                }
+            },
+            // Nothing produced in mid..bound.
+            _ => bound = mid,
+        }
+    }
+
+    let (lo_time, hi_time) = (time(lo)?, time(hi)?);
+    Some(if timestamp - lo_time <= hi_time - timestamp { lo } else { hi })
+}
@@ -0,0 +1,285 @@
+// Compressed NFTs: records the Bubblegum mints, transfers, delegations and
+// burns in the blocks the block follower hands it, and decodes the account-compression
+// merkle trees that hold the NFTs' leaves.
+use chrono::{DateTime, Utc};
+use solana_sdk::{
+    bs58,
+    hash::{hashv, Hash},
+    pubkey,
+    pubkey::Pubkey,
+};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedTransaction, EncodedTransactionWithStatusMeta,
+    UiConfirmedBlock, UiInstruction, UiMessage, UiParsedInstruction,
+};
+use std::str::FromStr;
+use crate::{
+    models::MerkleTree,
+    services::{
+        nfts::{self, NftMetadata},
+        solana,
+        token_metadata::Reader,
+    },
+};
+
+pub const BUBBLEGUM_PROGRAM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+pub const NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+pub const COMPRESSION_PROGRAM_ID: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+
+/// Account type and header version of a concurrent merkle tree account.
+const TREE_ACCOUNT: u8 = 1;
+const TREE_HEADER_V1: u8 = 0;
+const TREE_HEADER_LEN: usize = 56;
+/// `AccountCompressionEvent::ApplicationData` at version 1, the envelope
+/// Bubblegum logs its events in through noop.
+const APPLICATION_DATA_V1: [u8; 2] = [1, 0];
+/// `LeafSchemaEvent` at version 1 carrying a `LeafSchema::V1`.
+const LEAF_SCHEMA_V1: [u8; 3] = [1, 0, 0];
+
+/// Decodes an account-compression tree account: its header, the latest root
+/// and the number of leaves appended, and the depth of its canopy.
+pub fn decode_tree(address: &Pubkey, data: &[u8]) -> Option<MerkleTree> {
+    let mut reader = Reader::new(data);
+    if reader.u8()? != TREE_ACCOUNT || reader.u8()? != TREE_HEADER_V1 {
+        return None;
+    }
+    let max_buffer_size = reader.u32()?;
+    let max_depth = reader.u32()?;
+    let authority = reader.pubkey()?;
+    let creation_slot = reader.u64()?;
+
+    let mut reader = Reader::new(data.get(TREE_HEADER_LEN..)?);
+    let sequence_number = reader.u64()?;
+    let active_index = reader.u64()?;
+    // Buffer size.
+    reader.u64()?;
+    // Each change log is a root, a path and a leaf index with padding.
+    let change_log_len = 32 + 32 * max_depth as usize + 8;
+    let change_logs = reader.bytes(change_log_len.checked_mul(max_buffer_size as usize)?)?;
+    let root = change_logs.get(active_index as usize * change_log_len..)?.get(..32)?;
+    // The rightmost proof's index is the next leaf to append.
+    reader.bytes(32 * max_depth as usize + 32)?;
+    let leaves = reader.u32()? as u64;
+    reader.u32()?;
+
+    // The canopy caches the 2^(d+1) - 2 nodes of the top d levels.
+    let tree_len = 24 + change_logs.len() + 32 * max_depth as usize + 40;
+    let canopy_nodes = data.len().saturating_sub(TREE_HEADER_LEN + tree_len) / 32;
+    let canopy_depth = (canopy_nodes as u64 + 2).ilog2().saturating_sub(1);
+
+    Some(MerkleTree {
+        address: address.to_string(),
+        max_depth,
+        max_buffer_size,
+        authority: authority.to_string(),
+        creation_slot,
+        sequence_number,
+        leaves,
+        root: Hash::new(root).to_string(),
+        canopy_depth,
+    })
+}
+
+/// Id of the compressed NFT minted into `tree` with `nonce`.
+pub fn asset_id(tree: &Pubkey, nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"asset", tree.as_ref(), &nonce.to_le_bytes()], &BUBBLEGUM_PROGRAM_ID).0
+}
+
+/// Anchor discriminator of the Bubblegum instruction `name`.
+fn discriminator(name: &str) -> [u8; 8] {
+    let hash = hashv(&[format!("global:{}", name).as_bytes()]);
+    hash.to_bytes()[..8].try_into().unwrap()
+}
+
+/// A Bubblegum instruction that changes a compressed NFT, before it is
+/// stored.
+#[derive(Debug, Clone)]
+pub struct NftEvent {
+    /// Position of the instruction in the transaction's execution order.
+    pub position: i16,
+    pub asset: String,
+    /// "mint", "transfer", "delegate" or "burn".
+    pub kind: &'static str,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub tree: String,
+    pub leaf_index: u64,
+    /// Metadata of a minted NFT.
+    pub metadata: Option<NftMetadata>,
+}
+
+/// Instructions of `tx` with their accounts and data in execution order:
+/// each top-level instruction followed by the ones it invoked.
+fn instructions(tx: &EncodedTransactionWithStatusMeta) -> Vec<(String, Vec<String>, Vec<u8>)> {
+    let decoded = |ix: &UiInstruction| match ix {
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(ix)) => {
+            Some((ix.program_id.clone(), ix.accounts.clone(), bs58::decode(&ix.data).into_vec().ok()?))
+        }
+        _ => None,
+    };
+    let EncodedTransaction::Json(ui) = &tx.transaction else { return vec![] };
+    let UiMessage::Parsed(message) = &ui.message else { return vec![] };
+    let inner = match tx.meta.as_ref().map(|m| &m.inner_instructions) {
+        Some(OptionSerializer::Some(inner)) => inner.as_slice(),
+        _ => &[],
+    };
+
+    let mut found = Vec::new();
+    for (i, ix) in message.instructions.iter().enumerate() {
+        found.extend(decoded(ix));
+        for group in inner.iter().filter(|group| group.index as usize == i) {
+            found.extend(group.instructions.iter().filter_map(decoded));
+        }
+    }
+    found
+}
+
+/// Owner and nonce of the leaf in a noop-logged `ApplicationData` event
+/// wrapping a `LeafSchemaEvent`: the envelope's tags and data length, the
+/// event's tags, then id, owner, delegate and nonce.
+fn leaf_event(data: &[u8]) -> Option<(Pubkey, u64)> {
+    let mut reader = Reader::new(data);
+    if reader.bytes(2)? != APPLICATION_DATA_V1 {
+        return None;
+    }
+    let len = reader.u32()? as usize;
+    let mut reader = Reader::new(reader.bytes(len)?);
+    if reader.bytes(3)? != LEAF_SCHEMA_V1 {
+        return None;
+    }
+    reader.bytes(32)?;
+    let owner = reader.pubkey()?;
+    reader.bytes(32)?;
+    Some((owner, reader.u64()?))
+}
+
+/// Compressed NFT mints, transfers, delegations and burns made by `tx`.
+/// Mints take the asset's owner and nonce from the leaf the program logs
+/// through noop. Failed transactions yield nothing.
+pub fn extract(tx: &EncodedTransactionWithStatusMeta) -> Vec<NftEvent> {
+    if tx.meta.as_ref().map_or(true, |meta| meta.err.is_some()) {
+        return vec![];
+    }
+    let bubblegum = BUBBLEGUM_PROGRAM_ID.to_string();
+    let noop = NOOP_PROGRAM_ID.to_string();
+    let instructions = instructions(tx);
+    let (mint_v1, mint_to_collection) = (discriminator("mint_v1"), discriminator("mint_to_collection_v1"));
+    let (transfer, delegate, burn) = (discriminator("transfer"), discriminator("delegate"), discriminator("burn"));
+
+    instructions
+        .iter()
+        .enumerate()
+        .filter(|(_, (program, _, data))| *program == bubblegum && data.len() >= 8)
+        .filter_map(|(position, (_, accounts, data))| {
+            let account = |i: usize| accounts.get(i).cloned();
+            let (method, args) = data.split_at(8);
+            let (kind, tree, from, to, nonce, metadata) = if method == mint_v1 || method == mint_to_collection {
+                // The leaf the mint logged, before the next Bubblegum call.
+                let (owner, nonce) = instructions[position + 1..]
+                    .iter()
+                    .take_while(|(program, ..)| *program != bubblegum)
+                    .filter(|(program, ..)| *program == noop)
+                    .find_map(|(_, _, data)| leaf_event(data))?;
+
+                let mut metadata = nfts::parse_metadata_args(args)?;
+                if method == mint_to_collection {
+                    // Bubblegum verifies the collection as it mints.
+                    metadata.collection = metadata.collection.map(|(key, _)| (key, true));
+                }
+                ("mint", account(3)?, None, Some(owner.to_string()), nonce, Some(metadata))
+            } else {
+                // Root, data hash and creator hash, then the leaf's nonce.
+                let nonce = Reader::new(args.get(96..)?).u64()?;
+                if method == transfer {
+                    ("transfer", account(4)?, account(1), account(3), nonce, None)
+                } else if method == delegate {
+                    ("delegate", account(4)?, account(2), account(3), nonce, None)
+                } else if method == burn {
+                    ("burn", account(3)?, account(1), None, nonce, None)
+                } else {
+                    return None;
+                }
+            };
+
+            Some(NftEvent {
+                position: position as i16,
+                asset: asset_id(&Pubkey::from_str(&tree).ok()?, nonce).to_string(),
+                kind,
+                from,
+                to,
+                tree,
+                leaf_index: nonce,
+                metadata,
+            })
+        })
+        .collect()
+}
+
+/// Applies an event to its NFT's row.
+async fn apply<'e>(db: impl sqlx::PgExecutor<'e>, event: &NftEvent) -> Result<(), sqlx::Error> {
+    match event.kind {
+        "mint" => {
+            let Some(metadata) = &event.metadata else { return Ok(()) };
+            let owner = event.to.as_deref();
+            nfts::store(db, &event.asset, metadata, owner, owner, Some((event.tree.as_str(), event.leaf_index))).await
+        }
+        "burn" => {
+            sqlx::query("UPDATE nfts SET burned = true, updated_at = NOW() WHERE mint = $1")
+                .bind(&event.asset)
+                .execute(db)
+                .await?;
+            Ok(())
+        }
+        kind => {
+            let query = if kind == "transfer" {
+                // A transfer also hands delegation back to the new owner.
+                "UPDATE nfts SET owner = $2, delegate = $2, updated_at = NOW() WHERE mint = $1"
+            } else {
+                "UPDATE nfts SET delegate = $2, updated_at = NOW() WHERE mint = $1"
+            };
+            sqlx::query(query).bind(&event.asset).bind(&event.to).execute(db).await?;
+            Ok(())
+        }
+    }
+}
+
+/// Records the Bubblegum instructions in `block`, produced at `slot`, and
+/// returns how many were new. Each event is recorded and applied in one
+/// transaction, so re-indexing a block is a no-op.
+pub async fn index(db: &sqlx::PgPool, slot: u64, block: &UiConfirmedBlock) -> Result<usize, sqlx::Error> {
+    let block_time = block.block_time.and_then(|t| DateTime::<Utc>::from_timestamp(t, 0));
+
+    let mut recorded = 0;
+    for tx in block.transactions.iter().flatten() {
+        let events = extract(tx);
+        if events.is_empty() {
+            continue;
+        }
+        let Some(signature) = solana::signature(&tx.transaction) else { continue };
+        for event in &events {
+            let mut tx = db.begin().await?;
+            let inserted = sqlx::query(
+                "INSERT INTO nft_events (signature, asset, position, slot, block_time, kind, from_owner, to_owner)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                 ON CONFLICT (signature, asset, position) DO NOTHING"
+            )
+            .bind(&signature)
+            .bind(&event.asset)
+            .bind(event.position)
+            .bind(slot as i64)
+            .bind(block_time)
+            .bind(event.kind)
+            .bind(&event.from)
+            .bind(&event.to)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if inserted > 0 {
+                apply(&mut *tx, event).await?;
+                recorded += 1;
+            }
+            tx.commit().await?;
+        }
+    }
+    Ok(recorded)
+}
@@ -0,0 +1,215 @@
+use redis::AsyncCommands;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::OnceCell;
+
+/// How long a cached entry is kept in Redis.
+#[derive(Debug, Clone, Copy)]
+pub enum Ttl {
+    /// Never expires. Only for data that cannot change, such as finalized
+    /// blocks and transactions.
+    Forever,
+    Seconds(u64),
+}
+
+pub const FINALIZED: Ttl = Ttl::Forever;
+pub const BALANCE: Ttl = Ttl::Seconds(5);
+pub const NETWORK_STATS: Ttl = Ttl::Seconds(2);
+pub const PERFORMANCE_SAMPLES: Ttl = Ttl::Seconds(30);
+pub const VALIDATORS: Ttl = Ttl::Seconds(30);
+pub const SLOT_DURATION: Ttl = Ttl::Seconds(60);
+pub const DOMAIN: Ttl = Ttl::Seconds(300);
+pub const TOKEN: Ttl = Ttl::Seconds(60);
+pub const TOKEN_JSON: Ttl = Ttl::Seconds(86400);
+/// Off-chain JSON that couldn't be fetched, retried after a few minutes.
+pub const TOKEN_JSON_FAILURE: Ttl = Ttl::Seconds(300);
+pub const PRICE: Ttl = Ttl::Seconds(30);
+/// Candle ranges that include the still-open bucket.
+pub const CANDLES: Ttl = Ttl::Seconds(10);
+/// Candle ranges made only of closed buckets, which no longer change.
+pub const CANDLES_CLOSED: Ttl = Ttl::Seconds(86400);
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct NamespaceStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Requests that missed but piggybacked on an in-flight fetch instead of
+    /// hitting RPC themselves.
+    pub coalesced: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub coalesced: u64,
+    pub hit_rate: f64,
+    pub namespaces: HashMap<String, NamespaceStats>,
+}
+
+type InFlight = Arc<OnceCell<Option<String>>>;
+
+/// Read-through Redis cache for RPC-backed responses.
+///
+/// Keys are namespaced as `<prefix>:<namespace>:<id>` (e.g. `cache:block:250000000`);
+/// the namespace is used to group hit/miss metrics. Concurrent misses for the
+/// same key are coalesced so only one caller runs the fetch.
+#[derive(Clone)]
+pub struct Cache {
+    redis: redis::aio::ConnectionManager,
+    prefix: String,
+    inflight: Arc<Mutex<HashMap<String, InFlight>>>,
+    stats: Arc<Mutex<HashMap<String, NamespaceStats>>>,
+}
+
+enum Outcome {
+    Hit,
+    Miss,
+    Coalesced,
+}
+
+impl Cache {
+    pub fn new(redis: redis::aio::ConnectionManager, prefix: impl Into<String>) -> Self {
+        Self {
+            redis,
+            prefix: prefix.into(),
+            inflight: Arc::default(),
+            stats: Arc::default(),
+        }
+    }
+
+    /// Returns the cached value for `key`, or runs `fetch` and caches its
+    /// result. `None` results (missing data, RPC errors) are never cached.
+    pub async fn get_or_fetch<T, F>(&self, key: &str, ttl: Ttl, fetch: F) -> Option<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Option<T>,
+    {
+        let redis_key = self.redis_key(key);
+
+        if let Some(value) = self.read(&redis_key).await.and_then(|raw| serde_json::from_str(&raw).ok()) {
+            self.record(key, Outcome::Hit);
+            return Some(value);
+        }
+
+        let cell = self.inflight
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_default()
+            .clone();
+
+        let mut leader = false;
+        let raw = cell
+            .get_or_init(|| async {
+                leader = true;
+                let raw = serde_json::to_string(&fetch()?).ok()?;
+                self.write(&redis_key, &raw, ttl).await;
+                Some(raw)
+            })
+            .await
+            .clone();
+
+        if leader {
+            self.inflight.lock().unwrap().remove(key);
+            self.record(key, Outcome::Miss);
+        } else {
+            self.record(key, Outcome::Coalesced);
+        }
+
+        raw.and_then(|raw| serde_json::from_str(&raw).ok())
+    }
+
+    /// Reads a value stored with `put`, without counting towards the metrics.
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.read(&self.redis_key(key))
+            .await
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+    }
+
+    /// Stores `value` directly, for secondary indexes that are filled as a
+    /// side effect of other lookups rather than fetched on demand.
+    pub async fn put<T: Serialize>(&self, key: &str, value: &T, ttl: Ttl) {
+        if let Ok(raw) = serde_json::to_string(value) {
+            self.write(&self.redis_key(key), &raw, ttl).await;
+        }
+    }
+
+    /// Deletes every cached key matching the Redis glob `pattern` (relative to
+    /// this cache's prefix) and returns how many were removed.
+    pub async fn purge(&self, pattern: &str) -> redis::RedisResult<u64> {
+        let mut conn = self.redis.clone();
+        let keys: Vec<String> = {
+            let mut iter = conn.scan_match::<_, String>(self.redis_key(pattern)).await?;
+            let mut keys = Vec::new();
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+            keys
+        };
+
+        let mut deleted = 0;
+        let mut conn = self.redis.clone();
+        for chunk in keys.chunks(500) {
+            deleted += conn.del::<_, u64>(chunk).await?;
+        }
+
+        Ok(deleted)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let namespaces = self.stats.lock().unwrap().clone();
+        let hits = namespaces.values().map(|s| s.hits).sum::<u64>();
+        let misses = namespaces.values().map(|s| s.misses).sum::<u64>();
+        let coalesced = namespaces.values().map(|s| s.coalesced).sum::<u64>();
+        let lookups = hits + misses + coalesced;
+
+        CacheStats {
+            hits,
+            misses,
+            coalesced,
+            hit_rate: if lookups > 0 { hits as f64 / lookups as f64 } else { 0.0 },
+            namespaces,
+        }
+    }
+
+    fn redis_key(&self, key: &str) -> String {
+        format!("{}:{}", self.prefix, key)
+    }
+
+    fn record(&self, key: &str, outcome: Outcome) {
+        let namespace = key.split(':').next().unwrap_or(key).to_string();
+        let mut stats = self.stats.lock().unwrap();
+        let entry = stats.entry(namespace).or_default();
+        match outcome {
+            Outcome::Hit => entry.hits += 1,
+            Outcome::Miss => entry.misses += 1,
+            Outcome::Coalesced => entry.coalesced += 1,
+        }
+    }
+
+    async fn read(&self, redis_key: &str) -> Option<String> {
+        let mut conn = self.redis.clone();
+        match conn.get::<_, Option<String>>(redis_key).await {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::warn!("cache read failed for {}: {}", redis_key, e);
+                None
+            }
+        }
+    }
+
+    async fn write(&self, redis_key: &str, raw: &str, ttl: Ttl) {
+        let mut conn = self.redis.clone();
+        let result = match ttl {
+            Ttl::Forever => conn.set::<_, _, ()>(redis_key, raw).await,
+            Ttl::Seconds(seconds) => conn.set_ex::<_, _, ()>(redis_key, raw, seconds).await,
+        };
+        if let Err(e) = result {
+            tracing::warn!("cache write failed for {}: {}", redis_key, e);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+// Token price candles: periodic price ticks for tracked mints, carrying the
+// volume traded since the previous tick, aggregated into OHLCV buckets on
+// request.
+use chrono::{DateTime, Duration, Utc};
+use crate::{
+    AppState,
+    models::{Candle, Candles},
+    services::{cache, prices},
+};
+
+/// Ticks older than this are thinned to the ones hourly and daily candles
+/// need.
+const RAW_TICK_RETENTION: &str = "7 days";
+
+/// Supported candle intervals and their length in seconds.
+pub const INTERVALS: [(&str, i64); 4] = [("1m", 60), ("5m", 300), ("1h", 3600), ("1d", 86400)];
+
+/// Candles returned when the request gives no `from`.
+const DEFAULT_CANDLES: i64 = 300;
+/// Longest range served in one request; older buckets are cut off.
+const MAX_CANDLES: i64 = 1000;
+
+pub fn interval_seconds(interval: &str) -> Option<i64> {
+    INTERVALS.iter().find(|(name, _)| *name == interval).map(|(_, seconds)| *seconds)
+}
+
+fn bucket_start(t: DateTime<Utc>, step: i64) -> DateTime<Utc> {
+    let secs = t.timestamp().div_euclid(step) * step;
+    DateTime::<Utc>::from_timestamp(secs, 0).unwrap_or(t)
+}
+
+/// Prices the most active listed tokens, which records a tick for each one
+/// that currently has a price. Returns how many were priced.
+pub async fn record_ticks(state: &AppState, limit: i64) -> Result<usize, sqlx::Error> {
+    let mints: Vec<String> = sqlx::query_scalar(
+        "SELECT mint FROM tokens ORDER BY volume_24h DESC, market_cap DESC NULLS LAST LIMIT $1"
+    )
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await?;
+    Ok(prices::prices(state, &mints).await.len())
+}
+
+/// Each mint's ticks in every hour before `RAW_TICK_RETENTION`, ranked as
+/// the hour's open, close, high and low, with the hour's total volume.
+const RANKED_TICKS: &str =
+    "SELECT mint, recorded_at,
+            ROW_NUMBER() OVER (w ORDER BY recorded_at) AS open_rank,
+            ROW_NUMBER() OVER (w ORDER BY recorded_at DESC) AS close_rank,
+            ROW_NUMBER() OVER (w ORDER BY price DESC, recorded_at) AS high_rank,
+            ROW_NUMBER() OVER (w ORDER BY price, recorded_at) AS low_rank,
+            SUM(volume) OVER w AS hour_volume
+     FROM token_price_history
+     WHERE recorded_at < date_trunc('hour', NOW() - $1::interval)
+       AND recorded_at >= date_trunc('hour', NOW() - $1::interval) - INTERVAL '1 day'
+     WINDOW w AS (PARTITION BY mint, date_trunc('hour', recorded_at))";
+
+/// Thins ticks older than `RAW_TICK_RETENTION` to each hour's open, high,
+/// low and close, with the hour's volume moved onto the close, so hourly and
+/// daily candles come out the same while minute ticks stop piling up. Only
+/// the day past the cutoff is scanned, so this is cheap to run every tick.
+pub async fn compact_history(db: &sqlx::PgPool) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+    sqlx::query(&format!(
+        "UPDATE token_price_history h
+         SET volume = CASE WHEN r.close_rank = 1 THEN r.hour_volume ELSE 0 END
+         FROM ({}) r
+         WHERE h.mint = r.mint AND h.recorded_at = r.recorded_at
+           AND (r.open_rank = 1 OR r.close_rank = 1 OR r.high_rank = 1 OR r.low_rank = 1)
+           AND h.volume <> CASE WHEN r.close_rank = 1 THEN r.hour_volume ELSE 0 END",
+        RANKED_TICKS
+    ))
+    .bind(RAW_TICK_RETENTION)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(&format!(
+        "DELETE FROM token_price_history h
+         USING ({}) r
+         WHERE h.mint = r.mint AND h.recorded_at = r.recorded_at
+           AND r.open_rank > 1 AND r.close_rank > 1 AND r.high_rank > 1 AND r.low_rank > 1",
+        RANKED_TICKS
+    ))
+    .bind(RAW_TICK_RETENTION)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await
+}
+
+/// Starts the background task that records price ticks every
+/// `PRICE_TICK_INTERVAL_SECS` (default 60) for the top `PRICE_TICK_MINTS`
+/// (default 100) tokens and compacts old ticks.
+pub fn spawn_ticker(state: AppState) {
+    let every = std::env::var("PRICE_TICK_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60);
+    let limit = std::env::var("PRICE_TICK_MINTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(100);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(every));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = record_ticks(&state, limit).await {
+                tracing::warn!("failed to record price ticks: {}", e);
+            }
+            if let Err(e) = compact_history(&state.db).await {
+                tracing::warn!("failed to compact price history: {}", e);
+            }
+        }
+    });
+}
+
+async fn aggregate(
+    db: &sqlx::PgPool,
+    mint: &str,
+    step: i64,
+    from: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Result<Vec<Candle>, sqlx::Error> {
+    sqlx::query_as::<_, Candle>(
+        "SELECT to_timestamp(floor(extract(epoch FROM recorded_at) / $2) * $2) AS bucket,
+                (array_agg(price ORDER BY recorded_at))[1] AS open,
+                MAX(price) AS high,
+                MIN(price) AS low,
+                (array_agg(price ORDER BY recorded_at DESC))[1] AS close,
+                SUM(volume) AS volume
+         FROM token_price_history
+         WHERE mint = $1 AND recorded_at >= $3 AND recorded_at < $4
+         GROUP BY 1
+         ORDER BY 1"
+    )
+    .bind(mint)
+    .bind(step as f64)
+    .bind(from)
+    .bind(until)
+    .fetch_all(db)
+    .await
+}
+
+/// Buckets without ticks repeat the previous close with no volume. Leading
+/// buckets are filled from the last tick before the range, and dropped if
+/// there is none.
+fn fill_gaps(
+    candles: Vec<Candle>,
+    mut previous_close: Option<f64>,
+    step: i64,
+    from: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Vec<Candle> {
+    let mut ticks = candles.into_iter().peekable();
+    let mut filled = Vec::new();
+    let mut bucket = from;
+    while bucket < until {
+        match ticks.next_if(|candle| candle.bucket == bucket) {
+            Some(candle) => {
+                previous_close = Some(candle.close);
+                filled.push(candle);
+            }
+            None => {
+                if let Some(close) = previous_close {
+                    filled.push(Candle { bucket, open: close, high: close, low: close, close, volume: 0.0 });
+                }
+            }
+        }
+        let Some(next) = bucket.checked_add_signed(Duration::seconds(step)) else { break };
+        bucket = next;
+    }
+    filled
+}
+
+/// OHLCV candles of `mint` at `interval` covering `from..=to`, aligned to
+/// interval boundaries. None for an unknown interval or a range at the edge
+/// of representable time.
+pub async fn candles(
+    state: &AppState,
+    mint: &str,
+    interval: &str,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Option<Candles> {
+    let step = interval_seconds(interval)?;
+    let last = bucket_start(to.unwrap_or_else(Utc::now), step);
+    let earliest = last.checked_sub_signed(Duration::seconds(step * (MAX_CANDLES - 1)))?;
+    let first = match from {
+        Some(from) => bucket_start(from, step),
+        None => last.checked_sub_signed(Duration::seconds(step * (DEFAULT_CANDLES - 1)))?,
+    }
+    .max(earliest);
+    let until = last.checked_add_signed(Duration::seconds(step))?;
+
+    let key = format!("candles:{}:{}:{}:{}", mint, interval, first.timestamp(), last.timestamp());
+    if let Some(cached) = state.cache.get::<Candles>(&key).await {
+        return Some(cached);
+    }
+
+    let rows = aggregate(&state.db, mint, step, first, until).await.unwrap_or_default();
+    let previous_close: Option<f64> = sqlx::query_scalar(
+        "SELECT price FROM token_price_history WHERE mint = $1 AND recorded_at < $2
+         ORDER BY recorded_at DESC LIMIT 1"
+    )
+    .bind(mint)
+    .bind(first)
+    .fetch_optional(&state.db)
+    .await
+    .ok()
+    .flatten();
+
+    let result = Candles {
+        mint: mint.to_string(),
+        interval: interval.to_string(),
+        from: first.timestamp(),
+        to: last.timestamp(),
+        candles: fill_gaps(rows, previous_close, step, first, until),
+    };
+    let ttl = if until > Utc::now() { cache::CANDLES } else { cache::CANDLES_CLOSED };
+    state.cache.put(&key, &result, ttl).await;
+    Some(result)
+}
@@ -0,0 +1,109 @@
+use solana_client::rpc_response::RpcLeaderSchedule;
+use solana_sdk::{epoch_schedule::EpochSchedule, reward_type::RewardType};
+use solana_transaction_status::TransactionDetails;
+
+use crate::{
+    AppState,
+    models::{EpochRewards, EpochSummary, ScheduledLeader},
+    services::{solana, throughput},
+};
+
+/// Used for time estimates when the node has no performance samples.
+pub const DEFAULT_SLOT_DURATION: f64 = 0.4;
+
+/// Average slot time over the last hour of performance samples.
+pub fn slot_duration(state: &AppState) -> f64 {
+    state.solana_client
+        .get_recent_performance_samples(Some(60))
+        .ok()
+        .and_then(|samples| throughput::slot_duration(&samples))
+        .unwrap_or(DEFAULT_SLOT_DURATION)
+}
+
+/// Unix time at which `slot` started or is expected to start, extrapolated
+/// from the current slot.
+pub fn estimate_time(slot: u64, current_slot: u64, slot_duration: f64, now: i64) -> i64 {
+    let offset = (slot as f64 - current_slot as f64) * slot_duration;
+    now + offset.round() as i64
+}
+
+pub fn summary(
+    schedule: &EpochSchedule,
+    epoch: u64,
+    current_slot: u64,
+    slot_duration: f64,
+    now: i64,
+) -> EpochSummary {
+    let first_slot = schedule.get_first_slot_in_epoch(epoch);
+    let last_slot = schedule.get_last_slot_in_epoch(epoch);
+    let current_epoch = schedule.get_epoch(current_slot);
+
+    EpochSummary {
+        epoch: epoch as i64,
+        first_slot: first_slot as i64,
+        last_slot: last_slot as i64,
+        slots: schedule.get_slots_in_epoch(epoch) as i64,
+        start_time: estimate_time(first_slot, current_slot, slot_duration, now),
+        end_time: estimate_time(last_slot + 1, current_slot, slot_duration, now),
+        status: match epoch.cmp(&current_epoch) {
+            std::cmp::Ordering::Less => "completed",
+            std::cmp::Ordering::Equal => "current",
+            std::cmp::Ordering::Greater => "upcoming",
+        }
+        .to_string(),
+    }
+}
+
+/// Staking and voting rewards paid for `epoch`. They are credited in the first
+/// block produced in the following epoch, so this only resolves for completed
+/// epochs still within the node's ledger history.
+pub fn rewards(state: &AppState, schedule: &EpochSchedule, epoch: u64) -> Option<EpochRewards> {
+    let next_first_slot = schedule.get_first_slot_in_epoch(epoch + 1);
+    let slot = *state.solana_client
+        .get_blocks_with_limit(next_first_slot, 1)
+        .ok()?
+        .first()?;
+    if schedule.get_epoch(slot) != epoch + 1 {
+        return None;
+    }
+
+    let block = state.solana_client
+        .get_block_with_config(slot, solana::block_config(TransactionDetails::None, true))
+        .ok()?;
+
+    let mut staking = 0i64;
+    let mut voting = 0i64;
+    for reward in block.rewards.unwrap_or_default() {
+        match reward.reward_type {
+            Some(RewardType::Staking) => staking += reward.lamports,
+            Some(RewardType::Voting) => voting += reward.lamports,
+            _ => {}
+        }
+    }
+
+    Some(EpochRewards {
+        slot: slot as i64,
+        staking: staking as f64 / 1_000_000_000.0,
+        voting: voting as f64 / 1_000_000_000.0,
+        total: (staking + voting) as f64 / 1_000_000_000.0,
+    })
+}
+
+/// Collapses an RPC leader schedule (identity -> slot indices) into one row
+/// per validator, busiest first.
+pub fn group_schedule(schedule: RpcLeaderSchedule, first_slot: u64) -> Vec<ScheduledLeader> {
+    let total: usize = schedule.values().map(Vec::len).sum();
+
+    let mut leaders: Vec<ScheduledLeader> = schedule
+        .into_iter()
+        .map(|(identity, indices)| ScheduledLeader {
+            identity,
+            leader_slots: indices.len() as i64,
+            share: if total > 0 { indices.len() as f64 / total as f64 * 100.0 } else { 0.0 },
+            first_slot: indices.iter().min().map(|i| (first_slot + *i as u64) as i64),
+            last_slot: indices.iter().max().map(|i| (first_slot + *i as u64) as i64),
+        })
+        .collect();
+    leaders.sort_by(|a, b| b.leader_slots.cmp(&a.leader_slots).then_with(|| a.identity.cmp(&b.identity)));
+    leaders
+}
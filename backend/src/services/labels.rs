@@ -0,0 +1,254 @@
+// Address labels: curated public labels managed by admins and private labels
+// set by individual users.
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::{collections::HashMap, str::FromStr};
+use uuid::Uuid;
+use crate::{
+    models::{AddressLabel, Label, UserAddressLabel},
+    services::search_index,
+};
+
+fn default_category() -> String { "other".to_string() }
+
+/// A curated label as submitted by an admin or read from an import file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewLabel {
+    pub address: String,
+    pub label: String,
+    #[serde(default = "default_category")]
+    pub category: String,
+}
+
+impl NewLabel {
+    fn validate(&self) -> Result<(), String> {
+        Pubkey::from_str(&self.address).map_err(|_| format!("invalid address {}", self.address))?;
+        if self.label.trim().is_empty() {
+            return Err(format!("empty label for {}", self.address));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum LabelError {
+    Invalid(String),
+    Db(sqlx::Error),
+}
+
+impl From<sqlx::Error> for LabelError {
+    fn from(e: sqlx::Error) -> Self {
+        LabelError::Db(e)
+    }
+}
+
+/// Splits one CSV record, honouring double-quoted fields so labels may
+/// contain commas.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+/// Parses `address,label[,category]` rows. A header row and blank or `#`
+/// lines are skipped.
+pub fn parse_csv(text: &str) -> Result<Vec<NewLabel>, String> {
+    let mut labels = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields = csv_fields(line);
+        if i == 0 && fields[0].eq_ignore_ascii_case("address") {
+            continue;
+        }
+        match fields.as_slice() {
+            [address, label] => labels.push(NewLabel {
+                address: address.clone(),
+                label: label.clone(),
+                category: default_category(),
+            }),
+            [address, label, category] => labels.push(NewLabel {
+                address: address.clone(),
+                label: label.clone(),
+                category: if category.is_empty() { default_category() } else { category.clone() },
+            }),
+            _ => return Err(format!("line {}: expected address,label[,category]", i + 1)),
+        }
+    }
+    Ok(labels)
+}
+
+/// Parses an import file: a JSON array of labels or CSV.
+pub fn parse_import(text: &str) -> Result<Vec<NewLabel>, String> {
+    if text.trim_start().starts_with('[') {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    } else {
+        parse_csv(text)
+    }
+}
+
+pub async fn list(db: &sqlx::PgPool, limit: i64, offset: i64) -> Result<Vec<AddressLabel>, sqlx::Error> {
+    sqlx::query_as::<_, AddressLabel>(
+        "SELECT * FROM address_labels ORDER BY label LIMIT $1 OFFSET $2"
+    )
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(db)
+    .await
+}
+
+/// Writes a validated label and its search entry.
+async fn write(conn: &mut sqlx::PgConnection, label: &NewLabel) -> Result<AddressLabel, sqlx::Error> {
+    let row = sqlx::query_as::<_, AddressLabel>(
+        "INSERT INTO address_labels (address, label, category)
+         VALUES ($1, $2, $3)
+         ON CONFLICT (address) DO UPDATE
+         SET label = EXCLUDED.label, category = EXCLUDED.category, updated_at = NOW()
+         RETURNING *"
+    )
+    .bind(&label.address)
+    .bind(label.label.trim())
+    .bind(&label.category)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    search_index::upsert(&mut *conn, "label", &row.address, &row.label, None, 0.0).await?;
+    Ok(row)
+}
+
+/// Adds or replaces a curated label and makes it searchable by name.
+pub async fn upsert(db: &sqlx::PgPool, label: &NewLabel) -> Result<AddressLabel, LabelError> {
+    label.validate().map_err(LabelError::Invalid)?;
+
+    let mut tx = db.begin().await?;
+    let row = write(&mut tx, label).await?;
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// Upserts every label in one transaction, so a bad file or a failed write
+/// doesn't half-apply. Returns how many were written.
+pub async fn import(db: &sqlx::PgPool, labels: &[NewLabel]) -> Result<usize, LabelError> {
+    for label in labels {
+        label.validate().map_err(LabelError::Invalid)?;
+    }
+    let mut tx = db.begin().await?;
+    for label in labels {
+        write(&mut tx, label).await?;
+    }
+    tx.commit().await?;
+    Ok(labels.len())
+}
+
+/// Deletes a curated label and its search entry together.
+pub async fn delete(db: &sqlx::PgPool, address: &str) -> Result<bool, sqlx::Error> {
+    let mut tx = db.begin().await?;
+    let deleted = sqlx::query("DELETE FROM address_labels WHERE address = $1")
+        .bind(address)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    search_index::remove(&mut *tx, "label", address).await?;
+    tx.commit().await?;
+    Ok(deleted > 0)
+}
+
+pub async fn list_private(db: &sqlx::PgPool, user_id: Uuid) -> Result<Vec<UserAddressLabel>, sqlx::Error> {
+    sqlx::query_as::<_, UserAddressLabel>(
+        "SELECT * FROM user_address_labels WHERE user_id = $1 ORDER BY label"
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await
+}
+
+pub async fn set_private(
+    db: &sqlx::PgPool,
+    user_id: Uuid,
+    address: &str,
+    label: &str,
+) -> Result<UserAddressLabel, LabelError> {
+    Pubkey::from_str(address).map_err(|_| LabelError::Invalid(format!("invalid address {}", address)))?;
+    if label.trim().is_empty() {
+        return Err(LabelError::Invalid(format!("empty label for {}", address)));
+    }
+
+    Ok(sqlx::query_as::<_, UserAddressLabel>(
+        "INSERT INTO user_address_labels (user_id, address, label)
+         VALUES ($1, $2, $3)
+         ON CONFLICT (user_id, address) DO UPDATE SET label = EXCLUDED.label
+         RETURNING *"
+    )
+    .bind(user_id)
+    .bind(address)
+    .bind(label.trim())
+    .fetch_one(db)
+    .await?)
+}
+
+pub async fn delete_private(db: &sqlx::PgPool, user_id: Uuid, address: &str) -> Result<bool, sqlx::Error> {
+    let deleted = sqlx::query("DELETE FROM user_address_labels WHERE user_id = $1 AND address = $2")
+        .bind(user_id)
+        .bind(address)
+        .execute(db)
+        .await?
+        .rows_affected();
+    Ok(deleted > 0)
+}
+
+/// Labels for `addresses` as seen by `user`: their private labels where set,
+/// curated ones otherwise. Unlabelled addresses are left out.
+pub async fn lookup(db: &sqlx::PgPool, user: Option<Uuid>, addresses: &[String]) -> HashMap<String, Label> {
+    let mut labels = HashMap::new();
+    if addresses.is_empty() {
+        return labels;
+    }
+
+    let curated = sqlx::query_as::<_, AddressLabel>(
+        "SELECT * FROM address_labels WHERE address = ANY($1)"
+    )
+    .bind(addresses)
+    .fetch_all(db)
+    .await
+    .unwrap_or_default();
+    for row in curated {
+        labels.insert(row.address, Label {
+            name: row.label,
+            category: Some(row.category),
+            private: false,
+        });
+    }
+
+    if let Some(user_id) = user {
+        let private = sqlx::query_as::<_, UserAddressLabel>(
+            "SELECT * FROM user_address_labels WHERE user_id = $1 AND address = ANY($2)"
+        )
+        .bind(user_id)
+        .bind(addresses)
+        .fetch_all(db)
+        .await
+        .unwrap_or_default();
+        for row in private {
+            labels.insert(row.address, Label {
+                name: row.label,
+                category: None,
+                private: true,
+            });
+        }
+    }
+    labels
+}
@@ -0,0 +1,525 @@
+// DEX markets: pools of listed tokens found on-chain, priced from their
+// vault balances or sqrt price and valued in USD from token prices, kept in
+// the `markets` table.
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{account::Account, pubkey, pubkey::Pubkey};
+use std::{collections::HashMap, str::FromStr, time::Duration};
+use crate::{
+    AppState,
+    models::{Market, MarketPair, PoolQuote},
+    services::{
+        pools::{self, Pool},
+        solana,
+    },
+};
+
+/// Accounts per getMultipleAccounts call, the RPC maximum.
+const ACCOUNTS_BATCH: usize = 100;
+
+/// Preferred quote currencies, strongest first. A pool is shown priced in
+/// whichever of its mints ranks higher here.
+const QUOTES: [Pubkey; 3] = [
+    pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"),
+    pubkey!("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB"),
+    pubkey!("So11111111111111111111111111111111111111112"),
+];
+
+/// Sortable columns by `sort` value. Anything else sorts by volume.
+const SORTS: [&str; 4] = ["volume_24h", "liquidity", "price_change_24h", "updated_at"];
+
+#[derive(Debug, Default)]
+pub struct MarketFilter {
+    pub dex: Option<String>,
+    /// Markets with this mint on either side.
+    pub mint: Option<String>,
+    pub min_liquidity: Option<f64>,
+}
+
+/// A pool's reserves and price, oriented towards its preferred quote.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub address: Pubkey,
+    pub dex: &'static str,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub base_reserve: f64,
+    pub quote_reserve: f64,
+    /// Quote per base in UI units.
+    pub price: f64,
+    pub fee_rate: f64,
+}
+
+fn quote_rank(mint: &Pubkey) -> usize {
+    QUOTES.iter().position(|quote| quote == mint).map_or(0, |i| QUOTES.len() - i)
+}
+
+/// Reads `keys` in batches. Missing accounts are left out.
+fn accounts(state: &AppState, keys: &[Pubkey]) -> HashMap<Pubkey, Account> {
+    keys.chunks(ACCOUNTS_BATCH)
+        .flat_map(|batch| {
+            let accounts = state.solana_client.get_multiple_accounts(batch).unwrap_or_default();
+            batch.iter().copied().zip(accounts).collect::<Vec<_>>()
+        })
+        .filter_map(|(key, account)| Some((key, account?)))
+        .collect()
+}
+
+/// Reserves and price of each pool, read from its vaults and mints. Pools
+/// with an empty or unreadable side are skipped.
+pub fn snapshots(state: &AppState, pools: &[(Pubkey, Pool)]) -> Vec<Snapshot> {
+    let mut keys: Vec<Pubkey> = Vec::new();
+    for pool in pools.iter().map(|(_, pool)| pool) {
+        for key in [pool.base_vault, pool.quote_vault, pool.base_mint, pool.quote_mint].into_iter().chain(pool.fee_config) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+    let accounts = accounts(state, &keys);
+    let balance = |vault: &Pubkey, owed: u64| {
+        let account = accounts.get(vault)?;
+        Some(solana::decode_token_account(&account.owner, &account.data)?.amount.saturating_sub(owed))
+    };
+    let decimals = |mint: &Pubkey| {
+        let account = accounts.get(mint)?;
+        Some(solana::decode_mint(&account.owner, &account.data)?.decimals)
+    };
+    let fee_rate = |pool: &Pool| {
+        pool.fee_config
+            .and_then(|config| pools::config_fee_rate(pool.dex, &accounts.get(&config)?.data))
+            .unwrap_or(pool.fee_rate)
+    };
+
+    pools
+        .iter()
+        .filter_map(|(address, pool)| {
+            let (base_decimals, quote_decimals) = (decimals(&pool.base_mint)?, decimals(&pool.quote_mint)?);
+            let base_reserve = balance(&pool.base_vault, pool.base_owed)? as f64 / 10f64.powi(base_decimals as i32);
+            let quote_reserve = balance(&pool.quote_vault, pool.quote_owed)? as f64 / 10f64.powi(quote_decimals as i32);
+            let price = match pool.sqrt_price {
+                Some(sqrt_price) => pools::sqrt_price_to_price(sqrt_price, base_decimals, quote_decimals),
+                None if base_reserve > 0.0 => quote_reserve / base_reserve,
+                None => return None,
+            };
+            if price <= 0.0 || !price.is_finite() {
+                return None;
+            }
+
+            let snapshot = Snapshot {
+                address: *address,
+                dex: pool.dex,
+                base_mint: pool.base_mint,
+                quote_mint: pool.quote_mint,
+                base_vault: pool.base_vault,
+                quote_vault: pool.quote_vault,
+                base_reserve,
+                quote_reserve,
+                price,
+                fee_rate: fee_rate(pool),
+            };
+            Some(if quote_rank(&pool.base_mint) > quote_rank(&pool.quote_mint) {
+                Snapshot {
+                    base_mint: pool.quote_mint,
+                    quote_mint: pool.base_mint,
+                    base_vault: pool.quote_vault,
+                    quote_vault: pool.base_vault,
+                    base_reserve: quote_reserve,
+                    quote_reserve: base_reserve,
+                    price: 1.0 / price,
+                    ..snapshot
+                }
+            } else {
+                snapshot
+            })
+        })
+        .collect()
+}
+
+/// Prices `pools` and stores them, valuing both sides in USD from the token
+/// listing. A side without a listed price is valued through the pool price.
+/// Returns how many were stored.
+pub async fn index_pools(state: &AppState, pools: &[(Pubkey, Pool)]) -> Result<usize, sqlx::Error> {
+    let snapshots = snapshots(state, pools);
+    let mints: Vec<String> = snapshots
+        .iter()
+        .flat_map(|s| [s.base_mint.to_string(), s.quote_mint.to_string()])
+        .collect();
+    let usd: HashMap<String, f64> = sqlx::query_as::<_, (String, f64)>(
+        "SELECT mint, price FROM tokens WHERE mint = ANY($1) AND price IS NOT NULL"
+    )
+    .bind(&mints)
+    .fetch_all(&state.db)
+    .await?
+    .into_iter()
+    .collect();
+
+    for snapshot in &snapshots {
+        let base_usd = usd.get(&snapshot.base_mint.to_string()).copied();
+        let quote_usd = usd.get(&snapshot.quote_mint.to_string()).copied();
+        let base_usd = base_usd.or(quote_usd.map(|quote| quote * snapshot.price));
+        let quote_usd = quote_usd.or(base_usd.map(|base| base / snapshot.price));
+        let liquidity = snapshot.base_reserve * base_usd.unwrap_or(0.0) + snapshot.quote_reserve * quote_usd.unwrap_or(0.0);
+
+        sqlx::query(
+            "INSERT INTO markets
+                (address, dex, base_mint, quote_mint, base_vault, quote_vault, base_reserve, quote_reserve, price, liquidity, fee_rate)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+             ON CONFLICT (address) DO UPDATE
+             SET base_mint = EXCLUDED.base_mint, quote_mint = EXCLUDED.quote_mint,
+                 base_vault = EXCLUDED.base_vault, quote_vault = EXCLUDED.quote_vault,
+                 base_reserve = EXCLUDED.base_reserve, quote_reserve = EXCLUDED.quote_reserve,
+                 price = EXCLUDED.price, liquidity = EXCLUDED.liquidity, fee_rate = EXCLUDED.fee_rate,
+                 updated_at = NOW()"
+        )
+        .bind(snapshot.address.to_string())
+        .bind(snapshot.dex)
+        .bind(snapshot.base_mint.to_string())
+        .bind(snapshot.quote_mint.to_string())
+        .bind(snapshot.base_vault.to_string())
+        .bind(snapshot.quote_vault.to_string())
+        .bind(snapshot.base_reserve)
+        .bind(snapshot.quote_reserve)
+        .bind(snapshot.price)
+        .bind(liquidity)
+        .bind(snapshot.fee_rate)
+        .execute(&state.db)
+        .await?;
+    }
+
+    // A token's liquidity is the depth of every pool it trades in.
+    sqlx::query(
+        "UPDATE tokens t SET liquidity = l.liquidity
+         FROM (
+             SELECT mint, SUM(liquidity) AS liquidity
+             FROM (SELECT base_mint AS mint, liquidity FROM markets
+                   UNION ALL
+                   SELECT quote_mint, liquidity FROM markets) sides
+             WHERE mint = ANY($1)
+             GROUP BY mint
+         ) l
+         WHERE t.mint = l.mint"
+    )
+    .bind(&mints)
+    .execute(&state.db)
+    .await?;
+
+    Ok(snapshots.len())
+}
+
+/// Every pool of a known DEX with `mint` on either side.
+fn find_pools(state: &AppState, mint: &Pubkey) -> Vec<(Pubkey, Pool)> {
+    let mut found: Vec<(Pubkey, Pool)> = Vec::new();
+    for dex in pools::DEXES.iter() {
+        for offset in [dex.base_mint_offset, dex.quote_mint_offset] {
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(dex.size as u64),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(offset, mint.as_ref())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            };
+            let accounts = match state.solana_client.get_program_accounts_with_config(&dex.program, config) {
+                Ok(accounts) => accounts,
+                Err(e) => {
+                    tracing::warn!("failed to scan {} pools of {}: {}", dex.name, mint, e);
+                    continue;
+                }
+            };
+            for (address, account) in accounts {
+                if found.iter().any(|(known, _)| *known == address) {
+                    continue;
+                }
+                if let Some(pool) = pools::decode(&account.owner, &account.data) {
+                    found.push((address, pool));
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Finds and indexes the pools of `mint`. Returns how many were indexed.
+pub async fn discover(state: &AppState, mint: &Pubkey) -> Result<usize, sqlx::Error> {
+    let pools = find_pools(state, mint);
+    index_pools(state, &pools).await
+}
+
+/// Re-reads every indexed pool and reprices it.
+pub async fn refresh(state: &AppState) -> Result<usize, sqlx::Error> {
+    let addresses: Vec<String> = sqlx::query_scalar("SELECT address FROM markets")
+        .fetch_all(&state.db)
+        .await?;
+    let keys: Vec<Pubkey> = addresses.iter().filter_map(|a| Pubkey::from_str(a).ok()).collect();
+
+    let accounts = accounts(state, &keys);
+    let pools: Vec<(Pubkey, Pool)> = keys
+        .iter()
+        .filter_map(|key| {
+            let account = accounts.get(key)?;
+            Some((*key, pools::decode(&account.owner, &account.data)?))
+        })
+        .collect();
+    index_pools(state, &pools).await
+}
+
+/// Starts the background tasks that look for new pools of the top
+/// `MARKET_DISCOVERY_MINTS` (default 100) listed tokens every
+/// `MARKET_DISCOVERY_INTERVAL_SECS` (default 3600), and reprice indexed
+/// pools every `MARKET_REFRESH_INTERVAL_SECS` (default 60).
+pub fn spawn_indexer(state: AppState) {
+    let env = |name: &str, default: u64| {
+        std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+    };
+    let discovery_every = env("MARKET_DISCOVERY_INTERVAL_SECS", 3600);
+    let discovery_mints = env("MARKET_DISCOVERY_MINTS", 100) as i64;
+    let refresh_every = env("MARKET_REFRESH_INTERVAL_SECS", 60);
+
+    let discovery_state = state.clone();
+    tokio::spawn(async move {
+        let state = discovery_state;
+        let mut ticker = tokio::time::interval(Duration::from_secs(discovery_every));
+        loop {
+            ticker.tick().await;
+            let mints: Vec<String> = sqlx::query_scalar(
+                "SELECT mint FROM tokens ORDER BY market_cap DESC NULLS LAST LIMIT $1"
+            )
+            .bind(discovery_mints)
+            .fetch_all(&state.db)
+            .await
+            .unwrap_or_default();
+
+            for mint in mints.iter().filter_map(|mint| Pubkey::from_str(mint).ok()) {
+                if let Err(e) = discover(&state, &mint).await {
+                    tracing::warn!("failed to index pools of {}: {}", mint, e);
+                }
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(refresh_every));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = refresh(&state).await {
+                tracing::warn!("failed to refresh markets: {}", e);
+            }
+        }
+    });
+}
+
+const MARKET_COLUMNS: &str =
+    "m.address,
+     COALESCE(NULLIF(b.symbol, ''), LEFT(m.base_mint, 4)) || '/' || COALESCE(NULLIF(q.symbol, ''), LEFT(m.quote_mint, 4)) AS pair,
+     COALESCE(NULLIF(b.symbol, ''), m.base_mint) AS base_token,
+     COALESCE(NULLIF(q.symbol, ''), m.quote_mint) AS quote_token,
+     m.base_mint, m.quote_mint, m.base_reserve, m.quote_reserve, m.price, m.price_change_24h,
+     m.volume_24h, m.liquidity, m.fee_rate, m.dex, m.updated_at";
+
+/// One page of markets and the number matching `filter`. Markets missing
+/// the sort value come last; ties go to the deeper pool.
+pub async fn list(
+    db: &sqlx::PgPool,
+    filter: &MarketFilter,
+    sort: &str,
+    ascending: bool,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<Market>, i64), sqlx::Error> {
+    let column = SORTS.iter().find(|s| **s == sort).copied().unwrap_or("volume_24h");
+    let direction = if ascending { "ASC" } else { "DESC" };
+    let filters = "($1::text IS NULL OR m.dex = $1)
+         AND ($2::text IS NULL OR m.base_mint = $2 OR m.quote_mint = $2)
+         AND ($3::float8 IS NULL OR m.liquidity >= $3)";
+
+    let markets = sqlx::query_as::<_, Market>(&format!(
+        "SELECT {}
+         FROM markets m
+         LEFT JOIN tokens b ON b.mint = m.base_mint
+         LEFT JOIN tokens q ON q.mint = m.quote_mint
+         WHERE {}
+         ORDER BY m.{} {} NULLS LAST, m.liquidity DESC, m.address
+         LIMIT $4 OFFSET $5",
+        MARKET_COLUMNS, filters, column, direction
+    ))
+    .bind(&filter.dex)
+    .bind(&filter.mint)
+    .bind(filter.min_liquidity)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(db)
+    .await?;
+
+    let (total,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM markets m WHERE {}", filters))
+        .bind(&filter.dex)
+        .bind(&filter.mint)
+        .bind(filter.min_liquidity)
+        .fetch_one(db)
+        .await?;
+
+    Ok((markets, total))
+}
+
+/// The market of the pool at `address`.
+pub async fn get(db: &sqlx::PgPool, address: &str) -> Result<Option<Market>, sqlx::Error> {
+    sqlx::query_as::<_, Market>(&format!(
+        "SELECT {}
+         FROM markets m
+         LEFT JOIN tokens b ON b.mint = m.base_mint
+         LEFT JOIN tokens q ON q.mint = m.quote_mint
+         WHERE m.address = $1",
+        MARKET_COLUMNS
+    ))
+    .bind(address)
+    .fetch_optional(db)
+    .await
+}
+
+/// The mints a pair id names, and the pool it names if it is a pool address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairRef {
+    pub base_mint: String,
+    pub quote_mint: String,
+    pub pool: Option<String>,
+}
+
+/// The mint of one side of a `BASE-QUOTE` id: a mint address as is, or a
+/// symbol resolved to the verified, then most liquid, token using it.
+async fn resolve_mint(db: &sqlx::PgPool, side: &str) -> Result<Option<String>, sqlx::Error> {
+    if Pubkey::from_str(side).is_ok() {
+        return Ok(Some(side.to_string()));
+    }
+    sqlx::query_scalar(
+        "SELECT mint FROM tokens
+         WHERE UPPER(symbol) = UPPER($1)
+         ORDER BY verified DESC, liquidity DESC, market_cap DESC NULLS LAST
+         LIMIT 1"
+    )
+    .bind(side)
+    .fetch_optional(db)
+    .await
+}
+
+/// Resolves `id`, either a pool address or `BASE-QUOTE` with each side a
+/// mint address or symbol. A pool keeps its stored orientation.
+pub async fn resolve(db: &sqlx::PgPool, id: &str) -> Result<Option<PairRef>, sqlx::Error> {
+    let Some((base, quote)) = id.split_once('-') else {
+        return Ok(get(db, id).await?.map(|market| PairRef {
+            base_mint: market.base_mint,
+            quote_mint: market.quote_mint,
+            pool: Some(market.address),
+        }));
+    };
+    let (Some(base_mint), Some(quote_mint)) = (resolve_mint(db, base).await?, resolve_mint(db, quote).await?) else {
+        return Ok(None);
+    };
+    Ok(Some(PairRef { base_mint, quote_mint, pool: None }))
+}
+
+/// `market` priced in the other side when `base_mint` is its quote.
+fn orient(market: Market, base_mint: &str) -> Market {
+    if market.base_mint == base_mint {
+        return market;
+    }
+    let pair = match market.pair.split_once('/') {
+        Some((base, quote)) => format!("{}/{}", quote, base),
+        None => market.pair,
+    };
+    Market {
+        pair,
+        base_token: market.quote_token,
+        quote_token: market.base_token,
+        base_mint: market.quote_mint,
+        quote_mint: market.base_mint,
+        base_reserve: market.quote_reserve,
+        quote_reserve: market.base_reserve,
+        price: 1.0 / market.price,
+        price_change_24h: market.price_change_24h.map(|change| (100.0 / (100.0 + change) - 1.0) * 100.0),
+        ..market
+    }
+}
+
+/// Every pool trading `base_mint` against `quote_mint` in either
+/// orientation, priced in `quote_mint`, deepest first.
+pub async fn pair_markets(db: &sqlx::PgPool, base_mint: &str, quote_mint: &str) -> Result<Vec<Market>, sqlx::Error> {
+    let markets = sqlx::query_as::<_, Market>(&format!(
+        "SELECT {}
+         FROM markets m
+         LEFT JOIN tokens b ON b.mint = m.base_mint
+         LEFT JOIN tokens q ON q.mint = m.quote_mint
+         WHERE (m.base_mint = $1 AND m.quote_mint = $2) OR (m.base_mint = $2 AND m.quote_mint = $1)
+         ORDER BY m.liquidity DESC, m.address",
+        MARKET_COLUMNS
+    ))
+    .bind(base_mint)
+    .bind(quote_mint)
+    .fetch_all(db)
+    .await?;
+    Ok(markets.into_iter().map(|market| orient(market, base_mint)).collect())
+}
+
+/// The pools of the pair `id` names, with their best prices and combined
+/// depth. None if `id` doesn't resolve or no pool trades the pair.
+pub async fn pair(db: &sqlx::PgPool, id: &str) -> Result<Option<MarketPair>, sqlx::Error> {
+    let Some(pair) = resolve(db, id).await? else { return Ok(None) };
+    let markets = pair_markets(db, &pair.base_mint, &pair.quote_mint).await?;
+    let Some(deepest) = markets.first() else { return Ok(None) };
+
+    Ok(Some(MarketPair {
+        pair: deepest.pair.clone(),
+        base_token: deepest.base_token.clone(),
+        quote_token: deepest.quote_token.clone(),
+        base_mint: pair.base_mint,
+        quote_mint: pair.quote_mint,
+        best_buy_price: markets.iter().map(|m| m.price).fold(f64::INFINITY, f64::min),
+        best_sell_price: markets.iter().map(|m| m.price).fold(0.0, f64::max),
+        liquidity: markets.iter().map(|m| m.liquidity).sum(),
+        volume_24h: markets.iter().map(|m| m.volume_24h).sum(),
+        markets,
+    }))
+}
+
+/// Estimates swapping `amount_in` of the base side (or the quote side if
+/// `sell_quote`) through each of `markets`, best output first. Every pool is
+/// treated as constant product at its price, over reserves whose product
+/// matches its vaults; for concentrated pools this understates the depth
+/// near the current price.
+pub fn quote(markets: &[Market], amount_in: f64, sell_quote: bool) -> Vec<PoolQuote> {
+    let mut routes: Vec<PoolQuote> = markets
+        .iter()
+        .filter_map(|market| {
+            let base = (market.base_reserve * market.quote_reserve / market.price).sqrt();
+            let quote = base * market.price;
+            let (reserve_in, reserve_out, rate) = if sell_quote {
+                (quote, base, 1.0 / market.price)
+            } else {
+                (base, quote, market.price)
+            };
+            if !(reserve_in > 0.0 && reserve_out > 0.0 && rate.is_finite()) {
+                return None;
+            }
+
+            let fee = amount_in * market.fee_rate;
+            let net = amount_in - fee;
+            let amount_out = reserve_out * net / (reserve_in + net);
+            Some(PoolQuote {
+                market: market.address.clone(),
+                dex: market.dex.clone(),
+                amount_out,
+                fee,
+                price_impact: (1.0 - amount_out / (net * rate)) * 100.0,
+            })
+        })
+        .collect();
+    routes.sort_by(|a, b| b.amount_out.total_cmp(&a.amount_out));
+    routes
+}
@@ -1,7 +1,26 @@
 // Service layer for business logic
-// This would contain services for:
-// - Solana blockchain data fetching
-// - Token price aggregation from Jupiter/DexScreener
-// - Market data aggregation
-// - Analytics calculations
-// - Caching strategies
+
+pub mod block_follower;
+pub mod blocks;
+pub mod bubblegum;
+pub mod cache;
+pub mod candles;
+pub mod epochs;
+pub mod labels;
+pub mod markets;
+pub mod network_history;
+pub mod nfts;
+pub mod pools;
+pub mod prices;
+pub mod search_index;
+pub mod sns;
+pub mod solana;
+pub mod throughput;
+pub mod token_extensions;
+pub mod token_holders;
+pub mod token_list;
+pub mod token_metadata;
+pub mod token_risk;
+pub mod trades;
+pub mod transfers;
+pub mod validators;
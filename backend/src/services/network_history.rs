@@ -0,0 +1,167 @@
+use chrono::{DateTime, Utc};
+use std::{collections::HashSet, time::Duration};
+use solana_transaction_status::TransactionDetails;
+
+use crate::{AppState, models::NetworkMetricPoint, services::{solana, throughput}};
+
+pub const METRICS: [&str; 5] = ["tps", "non_vote_tps", "block_time", "avg_fee", "active_accounts"];
+
+/// Raw samples older than this are dropped once they have been rolled up.
+const RAW_RETENTION: &str = "2 days";
+/// Hourly rollups older than this are dropped; daily rollups are kept forever.
+const HOURLY_RETENTION: &str = "90 days";
+/// How many slots to walk back from the tip looking for a produced block.
+const BLOCK_LOOKBACK: u64 = 10;
+
+/// Starts the background task that samples network metrics every
+/// `NETWORK_SAMPLE_INTERVAL_SECS` (default 60) and maintains the rollups.
+pub fn spawn_sampler(state: AppState) {
+    let every = std::env::var("NETWORK_SAMPLE_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(every));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = record_sample(&state).await {
+                tracing::warn!("failed to record network metrics: {}", e);
+            }
+            if let Err(e) = rollup(&state.db).await {
+                tracing::warn!("failed to roll up network metrics: {}", e);
+            }
+        }
+    });
+}
+
+/// Reads the current value of every metric from RPC. Metrics whose source is
+/// unavailable are left out rather than recorded as zero.
+pub fn take_sample(state: &AppState) -> Vec<(&'static str, f64)> {
+    let mut values = Vec::new();
+
+    let latest = state.solana_client
+        .get_recent_performance_samples(Some(1))
+        .ok()
+        .and_then(|samples| samples.into_iter().next());
+    if let Some(sample) = latest {
+        let (tps, non_vote_tps) = throughput::average(std::slice::from_ref(&sample));
+        values.push(("tps", tps));
+        if let Some(non_vote_tps) = non_vote_tps {
+            values.push(("non_vote_tps", non_vote_tps));
+        }
+        if let Some(block_time) = throughput::slot_duration(std::slice::from_ref(&sample)) {
+            values.push(("block_time", block_time));
+        }
+    }
+
+    let tip = state.solana_client.get_slot().unwrap_or(0);
+    let config = solana::block_config(TransactionDetails::Accounts, false);
+    let block = (0..BLOCK_LOOKBACK)
+        .filter_map(|i| tip.checked_sub(i))
+        .find_map(|slot| state.solana_client.get_block_with_config(slot, config.clone()).ok());
+    if let Some(transactions) = block.and_then(|b| b.transactions) {
+        if !transactions.is_empty() {
+            let fees: u64 = transactions.iter()
+                .filter_map(|tx| tx.meta.as_ref().map(|m| m.fee))
+                .sum();
+            values.push(("avg_fee", fees as f64 / transactions.len() as f64));
+
+            let accounts: HashSet<String> = transactions.iter()
+                .flat_map(|tx| solana::account_keys(&tx.transaction))
+                .collect();
+            values.push(("active_accounts", accounts.len() as f64));
+        }
+    }
+
+    values
+}
+
+pub async fn record_sample(state: &AppState) -> Result<usize, sqlx::Error> {
+    let values = take_sample(state);
+    let now = Utc::now();
+
+    for (metric, value) in &values {
+        sqlx::query(
+            "INSERT INTO network_metrics (metric, resolution, bucket, avg, min, max, samples)
+             VALUES ($1, 'raw', $2, $3, $3, $3, 1)
+             ON CONFLICT (metric, resolution, bucket) DO NOTHING"
+        )
+        .bind(metric)
+        .bind(now)
+        .bind(value)
+        .execute(&state.db)
+        .await?;
+    }
+
+    Ok(values.len())
+}
+
+/// Re-aggregates raw samples into hourly buckets and hourly buckets into
+/// daily ones, then prunes expired rows. Only buckets touched since the
+/// previous period are recomputed, so this is cheap to run every tick.
+pub async fn rollup(db: &sqlx::PgPool) -> Result<(), sqlx::Error> {
+    for (source, target, unit) in [("raw", "1h", "hour"), ("1h", "1d", "day")] {
+        sqlx::query(
+            "INSERT INTO network_metrics (metric, resolution, bucket, avg, min, max, samples)
+             SELECT metric, $2, date_trunc($3, bucket),
+                    SUM(avg * samples) / SUM(samples), MIN(min), MAX(max), SUM(samples)
+             FROM network_metrics
+             WHERE resolution = $1
+               AND bucket >= date_trunc($3, NOW() - ('1 ' || $3)::interval)
+             GROUP BY metric, date_trunc($3, bucket)
+             ON CONFLICT (metric, resolution, bucket) DO UPDATE
+             SET avg = EXCLUDED.avg, min = EXCLUDED.min, max = EXCLUDED.max, samples = EXCLUDED.samples"
+        )
+        .bind(source)
+        .bind(target)
+        .bind(unit)
+        .execute(db)
+        .await?;
+    }
+
+    sqlx::query(
+        "DELETE FROM network_metrics
+         WHERE (resolution = 'raw' AND bucket < NOW() - $1::interval)
+            OR (resolution = '1h' AND bucket < NOW() - $2::interval)"
+    )
+    .bind(RAW_RETENTION)
+    .bind(HOURLY_RETENTION)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Picks the finest resolution that keeps a chart over `from..to` to a
+/// sensible number of points.
+pub fn resolution_for(from: DateTime<Utc>, to: DateTime<Utc>) -> &'static str {
+    let span = to - from;
+    if span <= chrono::Duration::days(1) {
+        "raw"
+    } else if span <= chrono::Duration::days(30) {
+        "1h"
+    } else {
+        "1d"
+    }
+}
+
+pub async fn history(
+    db: &sqlx::PgPool,
+    metric: &str,
+    resolution: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<NetworkMetricPoint>, sqlx::Error> {
+    sqlx::query_as::<_, NetworkMetricPoint>(
+        "SELECT bucket, avg, min, max, samples FROM network_metrics
+         WHERE metric = $1 AND resolution = $2 AND bucket >= $3 AND bucket <= $4
+         ORDER BY bucket"
+    )
+    .bind(metric)
+    .bind(resolution)
+    .bind(from)
+    .bind(to)
+    .fetch_all(db)
+    .await
+}
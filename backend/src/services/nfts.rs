@@ -0,0 +1,440 @@
+// NFTs: Metaplex NFTs (a supply of one at zero decimals, with metadata),
+// found among an owner's holdings or looked up by mint, and compressed NFTs
+// indexed from Bubblegum, kept in the `nfts` table and grouped by
+// collection.
+use serde_json::Value;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::str::FromStr;
+use crate::{
+    AppState,
+    models::{Nft, NftAttribute, NftCollection, NftCreator, NftDetails, NftOwnership},
+    services::{
+        bubblegum,
+        cache,
+        solana,
+        token_holders,
+        token_metadata::{self, Reader, METADATA_PROGRAM_ID},
+        transfers::{self, TransferFilter},
+    },
+};
+
+/// Metaplex token standards that aren't NFTs: `FungibleAsset` and
+/// `Fungible`.
+const FUNGIBLE_STANDARDS: [u8; 2] = [1, 2];
+/// Mints checked per getMultipleAccounts call, each with its metadata
+/// account.
+const DETECT_BATCH: usize = 50;
+/// Ownership changes returned with an NFT.
+const HISTORY_LIMIT: i64 = 100;
+
+/// Metaplex metadata of an NFT, from a metadata account or the
+/// `MetadataArgs` of a Bubblegum mint.
+#[derive(Debug, Clone)]
+pub struct NftMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub update_authority: Option<Pubkey>,
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<NftCreator>,
+    pub token_standard: Option<u8>,
+    /// Collection mint and whether membership is verified.
+    pub collection: Option<(Pubkey, bool)>,
+}
+
+/// A Borsh `Option`: a tag byte, then the value if the tag is set.
+fn option<T>(reader: &mut Reader, read: impl FnOnce(&mut Reader) -> Option<T>) -> Option<Option<T>> {
+    match reader.u8()? {
+        0 => Some(None),
+        _ => Some(Some(read(reader)?)),
+    }
+}
+
+fn creators(reader: &mut Reader) -> Option<Vec<NftCreator>> {
+    (0..reader.u32()?)
+        .map(|_| {
+            Some(NftCreator {
+                address: reader.pubkey()?.to_string(),
+                verified: reader.bool()?,
+                share: reader.u8()?,
+            })
+        })
+        .collect()
+}
+
+fn collection(reader: &mut Reader) -> Option<(Pubkey, bool)> {
+    let verified = reader.bool()?;
+    Some((reader.pubkey()?, verified))
+}
+
+/// Parses a Metaplex `MetadataV1` account down to its collection. Fields
+/// added after an old account was written read as empty.
+pub fn parse_metadata(data: &[u8]) -> Option<NftMetadata> {
+    let base = token_metadata::parse_metaplex(data)?;
+    let mut reader = Reader::new(data);
+    // Key, update authority, mint, then the strings parsed above.
+    reader.bytes(65)?;
+    for _ in 0..3 {
+        reader.string()?;
+    }
+    let seller_fee_basis_points = reader.u16()?;
+    let creators = option(&mut reader, creators)?.unwrap_or_default();
+    // Primary sale and mutability, then the edition nonce.
+    let token_standard = reader
+        .bytes(2)
+        .and_then(|_| option(&mut reader, |r| r.u8()))
+        .and_then(|_| option(&mut reader, |r| r.u8()))
+        .flatten();
+    let collection = option(&mut reader, collection).flatten();
+
+    Some(NftMetadata {
+        name: base.name,
+        symbol: base.symbol,
+        uri: base.uri,
+        update_authority: base.update_authority,
+        seller_fee_basis_points,
+        creators,
+        token_standard,
+        collection,
+    })
+}
+
+/// Parses Bubblegum `MetadataArgs`. Compressed NFTs have no update
+/// authority of their own.
+pub fn parse_metadata_args(data: &[u8]) -> Option<NftMetadata> {
+    let mut reader = Reader::new(data);
+    let (name, symbol, uri) = (reader.string()?, reader.string()?, reader.string()?);
+    let seller_fee_basis_points = reader.u16()?;
+    // Primary sale and mutability, then the edition nonce.
+    reader.bytes(2)?;
+    option(&mut reader, |r| r.u8())?;
+    let token_standard = option(&mut reader, |r| r.u8())?;
+    let collection = option(&mut reader, collection)?;
+    // Uses: method, remaining, total.
+    option(&mut reader, |r| r.bytes(17))?;
+    // Token program version.
+    reader.u8()?;
+
+    Some(NftMetadata {
+        name,
+        symbol,
+        uri,
+        update_authority: None,
+        seller_fee_basis_points,
+        creators: creators(&mut reader)?,
+        token_standard,
+        collection,
+    })
+}
+
+/// The metadata of an NFT mint: a supply of one at zero decimals and
+/// Metaplex metadata of a non-fungible standard.
+fn nft_metadata(mint: &Account, metadata: &Account) -> Option<NftMetadata> {
+    let fields = solana::decode_mint(&mint.owner, &mint.data)?;
+    if fields.decimals != 0 || fields.supply != 1 || metadata.owner != METADATA_PROGRAM_ID {
+        return None;
+    }
+    let parsed = parse_metadata(&metadata.data)?;
+    if parsed.token_standard.is_some_and(|standard| FUNGIBLE_STANDARDS.contains(&standard)) {
+        return None;
+    }
+    Some(parsed)
+}
+
+/// Which of `mints` are NFTs, with their metadata. Each batch of mints is
+/// read with its metadata accounts in one call.
+fn detect(state: &AppState, mints: &[Pubkey]) -> Vec<(Pubkey, NftMetadata)> {
+    mints
+        .chunks(DETECT_BATCH)
+        .flat_map(|batch| {
+            let keys: Vec<Pubkey> = batch
+                .iter()
+                .copied()
+                .chain(batch.iter().map(token_metadata::metadata_key))
+                .collect();
+            let accounts = state.solana_client.get_multiple_accounts(&keys).unwrap_or_default();
+            let (mint_accounts, metadata_accounts) = accounts.split_at(accounts.len().min(batch.len()));
+            batch
+                .iter()
+                .zip(mint_accounts.iter().zip(metadata_accounts))
+                .filter_map(|(mint, (mint_account, metadata_account))| {
+                    Some((*mint, nft_metadata(mint_account.as_ref()?, metadata_account.as_ref()?)?))
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Records an NFT. `leaf` is the tree and leaf index of a compressed NFT.
+/// A known NFT keeps its image and, if none is given, its owner.
+pub async fn store<'e>(
+    db: impl sqlx::PgExecutor<'e>,
+    mint: &str,
+    metadata: &NftMetadata,
+    owner: Option<&str>,
+    delegate: Option<&str>,
+    leaf: Option<(&str, u64)>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO nfts
+            (mint, name, symbol, uri, collection, collection_verified, update_authority,
+             seller_fee_basis_points, creators, owner, delegate, compressed, tree, leaf_index)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+         ON CONFLICT (mint) DO UPDATE
+         SET name = EXCLUDED.name, symbol = EXCLUDED.symbol, uri = EXCLUDED.uri,
+             collection = EXCLUDED.collection, collection_verified = EXCLUDED.collection_verified,
+             update_authority = EXCLUDED.update_authority,
+             seller_fee_basis_points = EXCLUDED.seller_fee_basis_points, creators = EXCLUDED.creators,
+             owner = COALESCE(EXCLUDED.owner, nfts.owner), delegate = COALESCE(EXCLUDED.delegate, nfts.delegate),
+             updated_at = NOW()"
+    )
+    .bind(mint)
+    .bind(&metadata.name)
+    .bind(&metadata.symbol)
+    .bind(&metadata.uri)
+    .bind(metadata.collection.map(|(key, _)| key.to_string()))
+    .bind(metadata.collection.is_some_and(|(_, verified)| verified))
+    .bind(metadata.update_authority.map(|key| key.to_string()))
+    .bind(metadata.seller_fee_basis_points as i32)
+    .bind(serde_json::to_string(&metadata.creators).unwrap_or_else(|_| "[]".to_string()))
+    .bind(owner)
+    .bind(delegate)
+    .bind(leaf.is_some())
+    .bind(leaf.map(|(tree, _)| tree))
+    .bind(leaf.map(|(_, index)| index as i64))
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+const NFT_COLUMNS: &str =
+    "n.mint, n.name, n.symbol, n.uri, n.image, n.collection, NULLIF(c.name, '') AS collection_name,
+     n.collection_verified, n.owner, n.compressed, n.tree, n.leaf_index, n.burned";
+
+/// NFTs of `owner`: the Metaplex NFTs among `mints`, the mints of its
+/// single-unit, zero-decimal holdings, and its unburned compressed NFTs,
+/// grouped by collection with uncollected NFTs last. Detected mints are
+/// cached for a minute.
+pub async fn owned(state: &AppState, owner: &str, mints: &[String]) -> Vec<NftCollection> {
+    let key = format!("nft-mints:{}", owner);
+    let nft_mints = match state.cache.get::<Vec<String>>(&key).await {
+        Some(cached) => cached,
+        None => {
+            let candidates: Vec<Pubkey> = mints.iter().filter_map(|mint| Pubkey::from_str(mint).ok()).collect();
+            let detected = if candidates.is_empty() { vec![] } else { detect(state, &candidates) };
+            for (mint, metadata) in &detected {
+                if let Err(e) = store(&state.db, &mint.to_string(), metadata, Some(owner), None, None).await {
+                    tracing::warn!("failed to store nft {}: {}", mint, e);
+                }
+            }
+            let nft_mints: Vec<String> = detected.iter().map(|(mint, _)| mint.to_string()).collect();
+            state.cache.put(&key, &nft_mints, cache::TOKEN).await;
+            nft_mints
+        }
+    };
+
+    let nfts = sqlx::query_as::<_, Nft>(&format!(
+        "SELECT {}
+         FROM nfts n
+         LEFT JOIN nfts c ON c.mint = n.collection
+         WHERE (n.mint = ANY($2) OR (n.compressed AND n.owner = $1)) AND NOT n.burned
+         ORDER BY n.collection NULLS LAST, n.name, n.mint",
+        NFT_COLUMNS
+    ))
+    .bind(owner)
+    .bind(&nft_mints)
+    .fetch_all(&state.db)
+    .await
+    .unwrap_or_default();
+
+    let mut groups: Vec<NftCollection> = Vec::new();
+    for nft in nfts {
+        match groups.last_mut() {
+            Some(group) if group.collection == nft.collection => group.nfts.push(nft),
+            _ => groups.push(NftCollection {
+                collection: nft.collection.clone(),
+                name: nft.collection_name.clone(),
+                nfts: vec![nft],
+            }),
+        }
+    }
+    groups
+}
+
+/// Traits from the off-chain JSON's `attributes`; malformed entries are
+/// skipped.
+fn attributes(value: Option<Value>) -> Vec<NftAttribute> {
+    let Some(Value::Array(entries)) = value else { return vec![] };
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            Some(NftAttribute {
+                trait_type: entry["trait_type"].as_str()?.to_string(),
+                value: entry.get("value")?.clone(),
+            })
+        })
+        .collect()
+}
+
+async fn row(db: &sqlx::PgPool, mint: &str) -> Option<Nft> {
+    sqlx::query_as::<_, Nft>(&format!(
+        "SELECT {} FROM nfts n LEFT JOIN nfts c ON c.mint = n.collection WHERE n.mint = $1",
+        NFT_COLUMNS
+    ))
+    .bind(mint)
+    .fetch_optional(db)
+    .await
+    .ok()?
+}
+
+/// Ownership changes of a Metaplex NFT, from the token transfer index.
+async fn token_history(db: &sqlx::PgPool, mint: &str) -> Vec<NftOwnership> {
+    let (transfers, _) = transfers::list(db, mint, &TransferFilter::default(), HISTORY_LIMIT, 0)
+        .await
+        .unwrap_or_default();
+    transfers
+        .into_iter()
+        .map(|transfer| NftOwnership {
+            signature: transfer.signature,
+            slot: transfer.slot,
+            block_time: transfer.block_time,
+            kind: transfer.kind,
+            from: transfer.from_owner,
+            to: transfer.to_owner,
+        })
+        .collect()
+}
+
+/// Ownership changes of a compressed NFT, from the Bubblegum index.
+async fn compressed_history(db: &sqlx::PgPool, asset: &str) -> Vec<NftOwnership> {
+    sqlx::query_as::<_, (String, i64, Option<chrono::DateTime<chrono::Utc>>, String, Option<String>, Option<String>)>(
+        "SELECT signature, slot, block_time, kind, from_owner, to_owner
+         FROM nft_events
+         WHERE asset = $1
+         ORDER BY slot DESC, position DESC
+         LIMIT $2"
+    )
+    .bind(asset)
+    .bind(HISTORY_LIMIT)
+    .fetch_all(db)
+    .await
+    .unwrap_or_default()
+    .into_iter()
+    .map(|(signature, slot, block_time, kind, from, to)| NftOwnership { signature, slot, block_time, kind, from, to })
+    .collect()
+}
+
+/// Reads a Metaplex NFT from chain and records it with its current owner,
+/// at most once per `cache::TOKEN`. None if `mint` isn't an NFT.
+async fn refresh_token_nft(state: &AppState, mint: &Pubkey) -> Option<()> {
+    let key = format!("nft-refresh:{}", mint);
+    if let Some(is_nft) = state.cache.get::<bool>(&key).await {
+        return is_nft.then_some(());
+    }
+    let refreshed = read_token_nft(state, mint).await;
+    state.cache.put(&key, &refreshed.is_some(), cache::TOKEN).await;
+    refreshed
+}
+
+async fn read_token_nft(state: &AppState, mint: &Pubkey) -> Option<()> {
+    let mint_account = state.solana_client.get_account(mint).ok()?;
+    let metadata_account = state.solana_client.get_account(&token_metadata::metadata_key(mint)).ok()?;
+    let metadata = nft_metadata(&mint_account, &metadata_account)?;
+    let owner = token_holders::top_holders(state, mint)
+        .await
+        .and_then(|holders| holders.holders.into_iter().find(|holder| holder.amount == "1"))
+        .and_then(|holder| holder.owner);
+
+    if let Err(e) = store(&state.db, &mint.to_string(), &metadata, owner.as_deref(), None, None).await {
+        tracing::warn!("failed to store nft {}: {}", mint, e);
+    }
+    Some(())
+}
+
+/// An NFT by mint, or by asset id for compressed NFTs, with its traits,
+/// creators and ownership history. Metaplex NFTs are re-read from chain;
+/// compressed ones come from the Bubblegum index, with their tree read
+/// from chain.
+pub async fn details(state: &AppState, id: &str) -> Option<NftDetails> {
+    let known = row(&state.db, id).await;
+    if !known.as_ref().is_some_and(|nft| nft.compressed) {
+        let mint = Pubkey::from_str(id).ok()?;
+        if refresh_token_nft(state, &mint).await.is_none() && known.is_none() {
+            return None;
+        }
+    }
+    let mut nft = row(&state.db, id).await?;
+    let (update_authority, seller_fee, creators, delegate) = sqlx::query_as::<_, (Option<String>, i32, String, Option<String>)>(
+        "SELECT update_authority, seller_fee_basis_points, creators, delegate FROM nfts WHERE mint = $1"
+    )
+    .bind(&nft.mint)
+    .fetch_one(&state.db)
+    .await
+    .ok()?;
+
+    let off_chain = token_metadata::off_chain(state, &nft.uri).await;
+    if off_chain.image.is_some() && off_chain.image != nft.image {
+        nft.image = off_chain.image;
+        if let Err(e) = sqlx::query("UPDATE nfts SET image = $2 WHERE mint = $1")
+            .bind(&nft.mint)
+            .bind(&nft.image)
+            .execute(&state.db)
+            .await
+        {
+            tracing::warn!("failed to store nft image of {}: {}", nft.mint, e);
+        }
+    }
+
+    let (history, merkle_tree) = if nft.compressed {
+        let tree = nft.tree.as_deref().and_then(|tree| Pubkey::from_str(tree).ok());
+        let merkle_tree = tree.and_then(|tree| {
+            let account = state.solana_client.get_account(&tree).ok()?;
+            bubblegum::decode_tree(&tree, &account.data)
+        });
+        (compressed_history(&state.db, &nft.mint).await, merkle_tree)
+    } else {
+        (token_history(&state.db, &nft.mint).await, None)
+    };
+
+    Some(NftDetails {
+        description: off_chain.description,
+        attributes: attributes(off_chain.attributes),
+        update_authority,
+        seller_fee_basis_points: seller_fee as u16,
+        creators: serde_json::from_str(&creators).unwrap_or_default(),
+        delegate,
+        history,
+        merkle_tree,
+        nft,
+    })
+}
+
+/// One page of a collection's unburned NFTs by name, and their number.
+pub async fn collection(
+    db: &sqlx::PgPool,
+    collection: &str,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<Nft>, i64), sqlx::Error> {
+    let nfts = sqlx::query_as::<_, Nft>(&format!(
+        "SELECT {}
+         FROM nfts n
+         LEFT JOIN nfts c ON c.mint = n.collection
+         WHERE n.collection = $1 AND NOT n.burned
+         ORDER BY n.name, n.mint
+         LIMIT $2 OFFSET $3",
+        NFT_COLUMNS
+    ))
+    .bind(collection)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(db)
+    .await?;
+
+    let (total,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM nfts WHERE collection = $1 AND NOT burned")
+        .bind(collection)
+        .fetch_one(db)
+        .await?;
+
+    Ok((nfts, total))
+}
@@ -0,0 +1,203 @@
+// DEX pool accounts: layouts of the major constant-product and
+// concentrated-liquidity pools, decoded down to their mints, vaults and
+// price state.
+use solana_sdk::{pubkey, pubkey::Pubkey};
+
+pub const RAYDIUM_AMM_V4: Pubkey = pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+pub const RAYDIUM_CPMM: Pubkey = pubkey!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C");
+pub const RAYDIUM_CLMM: Pubkey = pubkey!("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK");
+pub const ORCA_WHIRLPOOL: Pubkey = pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+
+/// A pool layout: where its mints live, for getProgramAccounts filters, and
+/// how to decode it.
+pub struct Dex {
+    pub name: &'static str,
+    pub program: Pubkey,
+    /// Account size of a pool, to tell pools from the program's other
+    /// accounts.
+    pub size: usize,
+    pub base_mint_offset: usize,
+    pub quote_mint_offset: usize,
+    decode: fn(&[u8]) -> Option<Pool>,
+}
+
+/// The price-relevant state of a pool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pool {
+    pub dex: &'static str,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    /// Q64.64 square root of the raw quote-per-base price, for
+    /// concentrated-liquidity pools. Constant-product pools are priced from
+    /// their reserves.
+    pub sqrt_price: Option<u128>,
+    /// Vault balances owed to the protocol rather than to liquidity
+    /// providers.
+    pub base_owed: u64,
+    pub quote_owed: u64,
+    /// Share of each swap's input taken as fees.
+    pub fee_rate: f64,
+    /// Config account holding the fee rate, for pools that don't keep their
+    /// own. `fee_rate` is a fallback until it is read.
+    pub fee_config: Option<Pubkey>,
+}
+
+pub static DEXES: [Dex; 4] = [
+    Dex {
+        name: "raydium",
+        program: RAYDIUM_AMM_V4,
+        size: 752,
+        base_mint_offset: 400,
+        quote_mint_offset: 432,
+        decode: decode_raydium_amm,
+    },
+    Dex {
+        name: "raydium-cpmm",
+        program: RAYDIUM_CPMM,
+        size: 637,
+        base_mint_offset: 168,
+        quote_mint_offset: 200,
+        decode: decode_raydium_cpmm,
+    },
+    Dex {
+        name: "raydium-clmm",
+        program: RAYDIUM_CLMM,
+        size: 1544,
+        base_mint_offset: 73,
+        quote_mint_offset: 105,
+        decode: decode_raydium_clmm,
+    },
+    Dex {
+        name: "orca",
+        program: ORCA_WHIRLPOOL,
+        size: 653,
+        base_mint_offset: 101,
+        quote_mint_offset: 181,
+        decode: decode_whirlpool,
+    },
+];
+
+fn pubkey_at(data: &[u8], offset: usize) -> Option<Pubkey> {
+    Pubkey::try_from(data.get(offset..offset + 32)?).ok()
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn u64_at(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+fn u128_at(data: &[u8], offset: usize) -> Option<u128> {
+    Some(u128::from_le_bytes(data.get(offset..offset + 16)?.try_into().ok()?))
+}
+
+/// Fee of pools that keep theirs in a shared config account rather than
+/// the pool, until the config is read: the most common Raydium CP-Swap and
+/// CLMM tier.
+const RAYDIUM_CONFIG_FEE: f64 = 0.0025;
+
+/// Raydium AMM v4 `AmmInfo`. Pending PnL sits in the vaults but belongs to
+/// the protocol.
+fn decode_raydium_amm(data: &[u8]) -> Option<Pool> {
+    let (fee, fee_denominator) = (u64_at(data, 176)?, u64_at(data, 184)?);
+    Some(Pool {
+        dex: "raydium",
+        base_vault: pubkey_at(data, 336)?,
+        quote_vault: pubkey_at(data, 368)?,
+        base_mint: pubkey_at(data, 400)?,
+        quote_mint: pubkey_at(data, 432)?,
+        sqrt_price: None,
+        base_owed: u64_at(data, 192)?,
+        quote_owed: u64_at(data, 200)?,
+        fee_rate: if fee_denominator > 0 { fee as f64 / fee_denominator as f64 } else { RAYDIUM_CONFIG_FEE },
+        fee_config: None,
+    })
+}
+
+/// Raydium CP-Swap `PoolState`. Protocol and fund fees accrue in the vaults.
+fn decode_raydium_cpmm(data: &[u8]) -> Option<Pool> {
+    Some(Pool {
+        fee_config: Some(pubkey_at(data, 8)?),
+        dex: "raydium-cpmm",
+        base_vault: pubkey_at(data, 72)?,
+        quote_vault: pubkey_at(data, 104)?,
+        base_mint: pubkey_at(data, 168)?,
+        quote_mint: pubkey_at(data, 200)?,
+        sqrt_price: None,
+        base_owed: u64_at(data, 341)?.saturating_add(u64_at(data, 357)?),
+        quote_owed: u64_at(data, 349)?.saturating_add(u64_at(data, 365)?),
+        fee_rate: RAYDIUM_CONFIG_FEE,
+    })
+}
+
+/// Raydium concentrated-liquidity `PoolState`.
+fn decode_raydium_clmm(data: &[u8]) -> Option<Pool> {
+    Some(Pool {
+        fee_config: Some(pubkey_at(data, 9)?),
+        dex: "raydium-clmm",
+        base_mint: pubkey_at(data, 73)?,
+        quote_mint: pubkey_at(data, 105)?,
+        base_vault: pubkey_at(data, 137)?,
+        quote_vault: pubkey_at(data, 169)?,
+        sqrt_price: Some(u128_at(data, 253)?),
+        base_owed: 0,
+        quote_owed: 0,
+        fee_rate: RAYDIUM_CONFIG_FEE,
+    })
+}
+
+/// Orca `Whirlpool`. The fee rate is in hundredths of a basis point.
+fn decode_whirlpool(data: &[u8]) -> Option<Pool> {
+    Some(Pool {
+        dex: "orca",
+        fee_rate: u16_at(data, 45)? as f64 / 1_000_000.0,
+        sqrt_price: Some(u128_at(data, 65)?),
+        base_mint: pubkey_at(data, 101)?,
+        base_vault: pubkey_at(data, 133)?,
+        quote_mint: pubkey_at(data, 181)?,
+        quote_vault: pubkey_at(data, 213)?,
+        base_owed: 0,
+        quote_owed: 0,
+        fee_config: None,
+    })
+}
+
+/// Trade fee rate from the `AmmConfig` of a Raydium CP-Swap or CLMM pool,
+/// stored in millionths of the input.
+pub fn config_fee_rate(dex: &str, data: &[u8]) -> Option<f64> {
+    let rate = match dex {
+        "raydium-cpmm" => u64_at(data, 12)?,
+        "raydium-clmm" => u32_at(data, 47)? as u64,
+        _ => return None,
+    };
+    Some(rate as f64 / 1_000_000.0)
+}
+
+/// The layout of pools owned by `program`.
+pub fn dex(program: &Pubkey) -> Option<&'static Dex> {
+    DEXES.iter().find(|dex| dex.program == *program)
+}
+
+/// Decodes `data` as a pool if `owner` is a known DEX program and the
+/// account has its pool size.
+pub fn decode(owner: &Pubkey, data: &[u8]) -> Option<Pool> {
+    let dex = dex(owner)?;
+    if data.len() != dex.size {
+        return None;
+    }
+    (dex.decode)(data)
+}
+
+/// Quote per base in UI units from a Q64.64 sqrt price.
+pub fn sqrt_price_to_price(sqrt_price: u128, base_decimals: u8, quote_decimals: u8) -> f64 {
+    let ratio = sqrt_price as f64 / 2f64.powi(64);
+    ratio * ratio * 10f64.powi(base_decimals as i32 - quote_decimals as i32)
+}
@@ -0,0 +1,453 @@
+// Token prices: USD quotes from several sources reduced to one price per mint
+// by taking the median of the quotes that agree with each other. Every
+// aggregated price is recorded, so the last known price and its 24h change
+// survive a source outage.
+use axum::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+use crate::{AppState, models::TokenPrice, services::{cache, solana}};
+
+/// Quotes further than this fraction from the median are dropped as outliers.
+const MAX_DEVIATION: f64 = 0.1;
+/// A price that hasn't been refreshed for this long is reported as stale.
+const STALE_AFTER_SECS: i64 = 300;
+/// Jupiter accepts at most this many mints per request.
+const JUPITER_BATCH: usize = 100;
+/// DexScreener accepts at most this many mints per request.
+const DEXSCREENER_BATCH: usize = 30;
+
+/// A source of USD token prices.
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Prices for whichever of `mints` the source knows. Mints it can't
+    /// price, and every mint when the source is down, are left out.
+    async fn prices(&self, mints: &[String]) -> HashMap<String, f64>;
+}
+
+/// The configured price sources.
+#[derive(Clone)]
+pub struct PriceService {
+    sources: Arc<Vec<Arc<dyn PriceSource>>>,
+}
+
+impl PriceService {
+    pub fn new(sources: Vec<Arc<dyn PriceSource>>) -> Self {
+        Self { sources: Arc::new(sources) }
+    }
+
+    /// Sources named in `PRICE_SOURCES` (default "jupiter,dexscreener"):
+    /// `jupiter`, `dexscreener`, `pools` (pools listed in `PRICE_POOLS`) and
+    /// `stub` (fixed prices from `STUB_PRICES`).
+    pub fn from_env(http: &reqwest::Client, solana_client: Arc<RpcClient>) -> Self {
+        let names = std::env::var("PRICE_SOURCES").unwrap_or_else(|_| "jupiter,dexscreener".to_string());
+        let mut sources: Vec<Arc<dyn PriceSource>> = Vec::new();
+        for name in names.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            match name {
+                "jupiter" => sources.push(Arc::new(JupiterSource::new(
+                    http.clone(),
+                    std::env::var("JUPITER_PRICE_URL").unwrap_or_else(|_| "https://api.jup.ag/price/v2".to_string()),
+                ))),
+                "dexscreener" => sources.push(Arc::new(DexScreenerSource::new(
+                    http.clone(),
+                    std::env::var("DEXSCREENER_URL").unwrap_or_else(|_| "https://api.dexscreener.com/latest/dex".to_string()),
+                ))),
+                "pools" => sources.push(Arc::new(PoolSource::new(
+                    solana_client.clone(),
+                    PoolSource::parse_pools(&std::env::var("PRICE_POOLS").unwrap_or_default()),
+                ))),
+                "stub" => {
+                    let stub = StubSource::new("stub");
+                    for (mint, price) in std::env::var("STUB_PRICES")
+                        .unwrap_or_default()
+                        .split(',')
+                        .filter_map(|entry| entry.split_once('='))
+                    {
+                        if let Ok(price) = price.trim().parse() {
+                            stub.set(mint.trim(), price);
+                        }
+                    }
+                    sources.push(Arc::new(stub));
+                }
+                other => tracing::warn!("unknown price source {}", other),
+            }
+        }
+        Self::new(sources)
+    }
+
+    /// How many agreeing quotes a price needs: two once more than one source
+    /// is configured, so a single source can't set a price unchecked.
+    fn quorum(&self) -> usize {
+        self.sources.len().clamp(1, 2)
+    }
+
+    /// Quotes for `mints` from every source, queried concurrently.
+    async fn quotes(&self, mints: &[String]) -> HashMap<String, Vec<f64>> {
+        let mut tasks = tokio::task::JoinSet::new();
+        for source in self.sources.iter().cloned() {
+            let mints = mints.to_vec();
+            tasks.spawn(async move {
+                let prices = source.prices(&mints).await;
+                tracing::debug!("{} priced {} of {} mints", source.name(), prices.len(), mints.len());
+                prices
+            });
+        }
+
+        let mut quotes: HashMap<String, Vec<f64>> = HashMap::new();
+        while let Some(result) = tasks.join_next().await {
+            for (mint, price) in result.unwrap_or_default() {
+                quotes.entry(mint).or_default().push(price);
+            }
+        }
+        quotes
+    }
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// The median of the quotes within `MAX_DEVIATION` of the overall median,
+/// and how many quotes that was. None if fewer than `quorum` quotes agree
+/// closely enough to trust.
+fn aggregate(mut quotes: Vec<f64>, quorum: usize) -> Option<(f64, usize)> {
+    quotes.retain(|price| price.is_finite() && *price > 0.0);
+    if quotes.is_empty() {
+        return None;
+    }
+    quotes.sort_by(|a, b| a.total_cmp(b));
+
+    let overall = median(&quotes);
+    let agreeing: Vec<f64> = quotes
+        .into_iter()
+        .filter(|price| ((price - overall) / overall).abs() <= MAX_DEVIATION)
+        .collect();
+    (agreeing.len() >= quorum).then(|| (median(&agreeing), agreeing.len()))
+}
+
+async fn record(db: &sqlx::PgPool, mint: &str, price: f64, sources: usize) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+    sqlx::query(
+        "INSERT INTO token_prices (mint, price, sources, updated_at)
+         VALUES ($1, $2, $3, NOW())
+         ON CONFLICT (mint) DO UPDATE
+         SET price = EXCLUDED.price, sources = EXCLUDED.sources, updated_at = NOW()"
+    )
+    .bind(mint)
+    .bind(price)
+    .bind(sources as i32)
+    .execute(&mut *tx)
+    .await?;
+    // The tick carries the USD volume of indexed trades on any market of
+    // `mint` since its previous tick, or over the last minute for its first.
+    sqlx::query(
+        "INSERT INTO token_price_history (mint, price, volume)
+         SELECT $1, $2, COALESCE(SUM(t.volume_usd), 0)
+         FROM trades t
+         JOIN markets m ON m.address = t.market
+         WHERE (m.base_mint = $1 OR m.quote_mint = $1)
+           AND t.block_time > COALESCE(
+               (SELECT MAX(recorded_at) FROM token_price_history WHERE mint = $1),
+               NOW() - INTERVAL '1 minute'
+           )
+         ON CONFLICT DO NOTHING"
+    )
+    .bind(mint)
+    .bind(price)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await
+}
+
+/// The last recorded price of `mint` with its change against the latest
+/// price recorded at least 24 hours earlier.
+async fn last_known(db: &sqlx::PgPool, mint: &str) -> Option<TokenPrice> {
+    let (price, sources, updated_at, day_ago) = sqlx::query_as::<_, (f64, i32, DateTime<Utc>, Option<f64>)>(
+        "SELECT p.price, p.sources, p.updated_at,
+                (SELECT h.price FROM token_price_history h
+                 WHERE h.mint = p.mint AND h.recorded_at <= NOW() - INTERVAL '24 hours'
+                 ORDER BY h.recorded_at DESC LIMIT 1)
+         FROM token_prices p WHERE p.mint = $1"
+    )
+    .bind(mint)
+    .fetch_optional(db)
+    .await
+    .ok()??;
+
+    Some(TokenPrice {
+        mint: mint.to_string(),
+        price,
+        price_change_24h: day_ago.filter(|p| *p > 0.0).map(|p| (price - p) / p * 100.0),
+        sources,
+        updated_at,
+        stale: (Utc::now() - updated_at).num_seconds() > STALE_AFTER_SECS,
+    })
+}
+
+/// Current prices of `mints`. Mints no source can price right now fall back
+/// to their last known price, flagged as stale once it ages; mints that have
+/// never been priced are left out.
+pub async fn prices(state: &AppState, mints: &[String]) -> HashMap<String, TokenPrice> {
+    let mut result = HashMap::new();
+    let mut missing = Vec::new();
+    for mint in mints {
+        match state.cache.get::<TokenPrice>(&format!("price:{}", mint)).await {
+            Some(price) => {
+                result.insert(mint.clone(), price);
+            }
+            None => missing.push(mint.clone()),
+        }
+    }
+    if missing.is_empty() {
+        return result;
+    }
+
+    let mut quotes = state.prices.quotes(&missing).await;
+    let quorum = state.prices.quorum();
+    for mint in missing {
+        if let Some((price, sources)) = quotes.remove(&mint).and_then(|quotes| aggregate(quotes, quorum)) {
+            if let Err(e) = record(&state.db, &mint, price, sources).await {
+                tracing::warn!("failed to record price of {}: {}", mint, e);
+            }
+        }
+        if let Some(price) = last_known(&state.db, &mint).await {
+            state.cache.put(&format!("price:{}", mint), &price, cache::PRICE).await;
+            result.insert(mint, price);
+        }
+    }
+    result
+}
+
+pub async fn price(state: &AppState, mint: &str) -> Option<TokenPrice> {
+    prices(state, &[mint.to_string()]).await.remove(mint)
+}
+
+/// A price given as a JSON number or a numeric string, as the price APIs
+/// disagree on which.
+fn number(value: &Value) -> Option<f64> {
+    value.as_f64().or_else(|| value.as_str()?.parse().ok())
+}
+
+/// Jupiter's price API (`GET <url>?ids=<mint>,<mint>`).
+pub struct JupiterSource {
+    http: reqwest::Client,
+    url: String,
+}
+
+impl JupiterSource {
+    pub fn new(http: reqwest::Client, url: String) -> Self {
+        Self { http, url }
+    }
+}
+
+#[async_trait]
+impl PriceSource for JupiterSource {
+    fn name(&self) -> &str {
+        "jupiter"
+    }
+
+    async fn prices(&self, mints: &[String]) -> HashMap<String, f64> {
+        let mut prices = HashMap::new();
+        for batch in mints.chunks(JUPITER_BATCH) {
+            let response = self.http
+                .get(&self.url)
+                .query(&[("ids", batch.join(","))])
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+            let body: Value = match response {
+                Ok(response) => response.json().await.unwrap_or_default(),
+                Err(e) => {
+                    tracing::warn!("jupiter price request failed: {}", e);
+                    continue;
+                }
+            };
+
+            prices.extend(
+                batch
+                    .iter()
+                    .filter_map(|mint| Some((mint.clone(), number(&body["data"][mint]["price"])?))),
+            );
+        }
+        prices
+    }
+}
+
+/// DexScreener's token endpoint (`GET <url>/tokens/<mint>,<mint>`). Each
+/// mint is priced from its most liquid pair.
+pub struct DexScreenerSource {
+    http: reqwest::Client,
+    url: String,
+}
+
+impl DexScreenerSource {
+    pub fn new(http: reqwest::Client, url: String) -> Self {
+        Self { http, url }
+    }
+}
+
+#[async_trait]
+impl PriceSource for DexScreenerSource {
+    fn name(&self) -> &str {
+        "dexscreener"
+    }
+
+    async fn prices(&self, mints: &[String]) -> HashMap<String, f64> {
+        // (price, liquidity) of the deepest pair seen so far per mint.
+        let mut best: HashMap<String, (f64, f64)> = HashMap::new();
+        for batch in mints.chunks(DEXSCREENER_BATCH) {
+            let response = self.http
+                .get(format!("{}/tokens/{}", self.url, batch.join(",")))
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+            let body: Value = match response {
+                Ok(response) => response.json().await.unwrap_or_default(),
+                Err(e) => {
+                    tracing::warn!("dexscreener price request failed: {}", e);
+                    continue;
+                }
+            };
+
+            for pair in body["pairs"].as_array().into_iter().flatten() {
+                let Some(mint) = pair["baseToken"]["address"].as_str() else { continue };
+                let Some(price) = number(&pair["priceUsd"]) else { continue };
+                let liquidity = number(&pair["liquidity"]["usd"]).unwrap_or(0.0);
+                if !batch.iter().any(|m| m == mint) {
+                    continue;
+                }
+                if best.get(mint).map_or(true, |(_, deepest)| liquidity > *deepest) {
+                    best.insert(mint.to_string(), (price, liquidity));
+                }
+            }
+        }
+        best.into_iter().map(|(mint, (price, _))| (mint, price)).collect()
+    }
+}
+
+/// A pool whose quote side is a USD stablecoin, priced from the balances of
+/// its two vaults.
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+}
+
+/// On-chain prices from constant-product pool reserves.
+pub struct PoolSource {
+    solana_client: Arc<RpcClient>,
+    pools: Vec<PoolConfig>,
+}
+
+impl PoolSource {
+    pub fn new(solana_client: Arc<RpcClient>, pools: Vec<PoolConfig>) -> Self {
+        Self { solana_client, pools }
+    }
+
+    /// Parses `mint:base_vault:quote_vault` entries separated by commas.
+    /// Malformed entries are skipped.
+    pub fn parse_pools(spec: &str) -> Vec<PoolConfig> {
+        spec.split(',')
+            .filter_map(|entry| {
+                let keys: Vec<Pubkey> = entry
+                    .split(':')
+                    .map(|key| Pubkey::from_str(key.trim()))
+                    .collect::<Result<_, _>>()
+                    .ok()?;
+                match keys.as_slice() {
+                    [mint, base_vault, quote_vault] => Some(PoolConfig {
+                        mint: *mint,
+                        base_vault: *base_vault,
+                        quote_vault: *quote_vault,
+                    }),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Quote amount per base unit, both scaled by their mint's decimals.
+    fn pool_price(&self, pool: &PoolConfig) -> Option<f64> {
+        let vaults = self.solana_client.get_multiple_accounts(&[pool.base_vault, pool.quote_vault]).ok()?;
+        let [Some(base), Some(quote)] = vaults.as_slice() else { return None };
+        let base = solana::decode_token_account(&base.owner, &base.data)?;
+        let quote = solana::decode_token_account(&quote.owner, &quote.data)?;
+        if base.mint != pool.mint || base.amount == 0 {
+            return None;
+        }
+
+        let mints = self.solana_client.get_multiple_accounts(&[base.mint, quote.mint]).ok()?;
+        let [Some(base_mint), Some(quote_mint)] = mints.as_slice() else { return None };
+        let base_decimals = solana::decode_mint(&base_mint.owner, &base_mint.data)?.decimals;
+        let quote_decimals = solana::decode_mint(&quote_mint.owner, &quote_mint.data)?.decimals;
+
+        let base_amount = base.amount as f64 / 10f64.powi(base_decimals as i32);
+        let quote_amount = quote.amount as f64 / 10f64.powi(quote_decimals as i32);
+        Some(quote_amount / base_amount)
+    }
+}
+
+#[async_trait]
+impl PriceSource for PoolSource {
+    fn name(&self) -> &str {
+        "pools"
+    }
+
+    async fn prices(&self, mints: &[String]) -> HashMap<String, f64> {
+        self.pools
+            .iter()
+            .filter(|pool| mints.contains(&pool.mint.to_string()))
+            .filter_map(|pool| Some((pool.mint.to_string(), self.pool_price(pool)?)))
+            .collect()
+    }
+}
+
+/// Fixed prices set in code, for tests and for running without network
+/// access to the price APIs.
+pub struct StubSource {
+    name: String,
+    prices: Mutex<HashMap<String, f64>>,
+}
+
+impl StubSource {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), prices: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn set(&self, mint: &str, price: f64) {
+        self.prices.lock().unwrap().insert(mint.to_string(), price);
+    }
+
+    pub fn remove(&self, mint: &str) {
+        self.prices.lock().unwrap().remove(mint);
+    }
+}
+
+#[async_trait]
+impl PriceSource for StubSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn prices(&self, mints: &[String]) -> HashMap<String, f64> {
+        let prices = self.prices.lock().unwrap();
+        mints
+            .iter()
+            .filter_map(|mint| Some((mint.clone(), *prices.get(mint)?)))
+            .collect()
+    }
+}
@@ -0,0 +1,117 @@
+// Name search over tokens, address labels, programs and SNS domains, backed
+// by the trigram-indexed `search_entries` table.
+use crate::models::SearchEntry;
+
+/// Below this trigram similarity a fuzzy match is noise.
+const MIN_SIMILARITY: f32 = 0.3;
+
+/// Escapes LIKE wildcards so user input only ever matches literally.
+fn like_prefix(q: &str) -> String {
+    let escaped = q
+        .to_lowercase()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("{}%", escaped)
+}
+
+/// Adds or renames an entry. Popularity is only raised, never lowered, so a
+/// source that doesn't know it (e.g. a label import) passes 0.
+pub async fn upsert<'e>(
+    db: impl sqlx::PgExecutor<'e>,
+    kind: &str,
+    address: &str,
+    name: &str,
+    symbol: Option<&str>,
+    popularity: f64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO search_entries (kind, address, name, symbol, popularity)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (kind, address) DO UPDATE
+         SET name = EXCLUDED.name,
+             symbol = COALESCE(EXCLUDED.symbol, search_entries.symbol),
+             popularity = GREATEST(search_entries.popularity, EXCLUDED.popularity),
+             updated_at = NOW()"
+    )
+    .bind(kind)
+    .bind(address)
+    .bind(name)
+    .bind(symbol)
+    .bind(popularity)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Indexes every named listed token, with a popularity that grows with the
+/// log of its holder count and of its USD liquidity, so a token with a
+/// million holders and a billion dollars of liquidity scores about 75, in
+/// line with the seeded entries. Popularity is recomputed on every pass, so
+/// a token that loses its holders or liquidity falls back down, though
+/// never below a seeded entry's popularity.
+pub async fn rank_tokens(db: &sqlx::PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO search_entries (kind, address, name, symbol, popularity)
+         SELECT 'token', mint, name, symbol,
+                5 * (LOG(1 + COALESCE(holders, 0)) + LOG(1 + GREATEST(liquidity, 0)))
+         FROM tokens
+         WHERE name IS NOT NULL AND name <> ''
+         ON CONFLICT (kind, address) DO UPDATE
+         SET popularity = GREATEST(search_entries.seeded_popularity, EXCLUDED.popularity),
+             updated_at = NOW()"
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn remove<'e>(db: impl sqlx::PgExecutor<'e>, kind: &str, address: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM search_entries WHERE kind = $1 AND address = $2")
+        .bind(kind)
+        .bind(address)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// Entries matching `q` exactly, by prefix or fuzzily, in that order; ties
+/// are broken by popularity and then similarity.
+pub async fn search(db: &sqlx::PgPool, q: &str, limit: i64) -> Result<Vec<SearchEntry>, sqlx::Error> {
+    sqlx::query_as::<_, SearchEntry>(
+        "SELECT kind, address, name, symbol, popularity FROM search_entries
+         WHERE lower(name) LIKE $2 OR lower(symbol) LIKE $2
+            OR similarity(lower(name), lower($1)) >= $3
+            OR similarity(lower(symbol), lower($1)) >= $3
+         ORDER BY
+            CASE
+                WHEN lower(symbol) = lower($1) OR lower(name) = lower($1) THEN 0
+                WHEN lower(symbol) LIKE $2 OR lower(name) LIKE $2 THEN 1
+                ELSE 2
+            END,
+            popularity DESC,
+            GREATEST(similarity(lower(name), lower($1)), similarity(lower(symbol), lower($1))) DESC
+         LIMIT $4"
+    )
+    .bind(q)
+    .bind(like_prefix(q))
+    .bind(MIN_SIMILARITY)
+    .bind(limit)
+    .fetch_all(db)
+    .await
+}
+
+/// Autocomplete: entries whose name or symbol starts with `prefix`, most
+/// popular first.
+pub async fn suggest(db: &sqlx::PgPool, prefix: &str, limit: i64) -> Result<Vec<SearchEntry>, sqlx::Error> {
+    sqlx::query_as::<_, SearchEntry>(
+        "SELECT kind, address, name, symbol, popularity FROM search_entries
+         WHERE lower(name) LIKE $1 OR lower(symbol) LIKE $1
+         ORDER BY popularity DESC, length(name)
+         LIMIT $2"
+    )
+    .bind(like_prefix(prefix))
+    .bind(limit)
+    .fetch_all(db)
+    .await
+}
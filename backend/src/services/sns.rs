@@ -0,0 +1,273 @@
+// Solana Name Service: resolves `.sol` domains to their owner and finds the
+// primary domain of a wallet.
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{account::Account, hash::hashv, pubkey, pubkey::Pubkey};
+use std::{str::FromStr, time::Duration};
+use crate::{AppState, services::{cache, search_index}};
+
+pub const NAME_PROGRAM_ID: Pubkey = pubkey!("namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX");
+/// Parent name account of every `.sol` domain.
+pub const SOL_TLD: Pubkey = pubkey!("58PwtjSDuFHuUkYjH9BYnnQKHfwo9reZhC2zMJv9JPkx");
+pub const REVERSE_LOOKUP_CLASS: Pubkey = pubkey!("33m47vH6Eav6jr6ZezYnWjpnt9Ry1y3eu8JQsdHJoMF8");
+/// Holds the domain each wallet picked as its favourite.
+pub const NAME_OFFERS_ID: Pubkey = pubkey!("85iDfUvr3HJyLM2LhYSzNdSsnpZy2Xy7Vs4Ajb1DmzG");
+
+const HASH_PREFIX: &str = "SPL Name Service";
+/// Parent, owner and class keys precede a name account's data.
+const HEADER_LEN: usize = 96;
+/// Wallets scanned per pass of the background scanner.
+const SCAN_BATCH: i64 = 50;
+
+pub fn is_domain(q: &str) -> bool {
+    q.len() > 4 && q.to_lowercase().ends_with(".sol")
+}
+
+fn name_account(name: &str, class: &Pubkey, parent: &Pubkey) -> Pubkey {
+    let hashed = hashv(&[HASH_PREFIX.as_bytes(), name.as_bytes()]);
+    Pubkey::find_program_address(
+        &[hashed.as_ref(), class.as_ref(), parent.as_ref()],
+        &NAME_PROGRAM_ID,
+    ).0
+}
+
+/// Name account of `domain`: "toly.sol", "toly" or a subdomain such as
+/// "blog.toly.sol".
+pub fn domain_key(domain: &str) -> Option<Pubkey> {
+    let domain = domain.to_lowercase();
+    let name = domain.strip_suffix(".sol").unwrap_or(&domain);
+    match name.split('.').collect::<Vec<_>>().as_slice() {
+        [name] if !name.is_empty() => Some(name_account(name, &Pubkey::default(), &SOL_TLD)),
+        [sub, name] if !sub.is_empty() && !name.is_empty() => {
+            let parent = name_account(name, &Pubkey::default(), &SOL_TLD);
+            Some(name_account(&format!("\0{}", sub), &Pubkey::default(), &parent))
+        }
+        _ => None,
+    }
+}
+
+/// Reverse-lookup account storing the name of the domain at `domain_key`.
+pub fn reverse_key(domain_key: &Pubkey) -> Pubkey {
+    name_account(&domain_key.to_string(), &REVERSE_LOOKUP_CLASS, &Pubkey::default())
+}
+
+pub fn favourite_key(owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"favourite_domain", owner.as_ref()], &NAME_OFFERS_ID).0
+}
+
+fn account(state: &AppState, key: &Pubkey) -> Option<Account> {
+    state.solana_client
+        .get_account_with_commitment(key, state.solana_client.commitment())
+        .ok()?
+        .value
+}
+
+fn owner_of(data: &[u8]) -> Option<Pubkey> {
+    Pubkey::try_from(data.get(32..64)?).ok()
+}
+
+/// The name in a reverse-lookup account: a borsh string after the header.
+fn reverse_name(data: &[u8]) -> Option<String> {
+    let len = u32::from_le_bytes(data.get(HEADER_LEN..HEADER_LEN + 4)?.try_into().ok()?) as usize;
+    let bytes = data.get(HEADER_LEN + 4..HEADER_LEN + 4 + len)?;
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+/// Owner of `domain`, or None if it isn't registered.
+pub async fn resolve(state: &AppState, domain: &str) -> Option<Pubkey> {
+    let key = domain_key(domain)?;
+    let owner: String = state.cache
+        .get_or_fetch(&format!("sns:{}", domain.to_lowercase()), cache::DOMAIN, || {
+            account(state, &key)
+                .filter(|account| account.owner == NAME_PROGRAM_ID)
+                .and_then(|account| owner_of(&account.data))
+                .map(|owner| owner.to_string())
+        })
+        .await?;
+    Pubkey::from_str(&owner).ok()
+}
+
+/// Top-level `.sol` domains owned by `owner`, or None if the scan failed.
+/// Only the keys are needed, so the account data is sliced away. This walks
+/// the whole name program, so it only runs in the background scanner.
+fn owned_domains(state: &AppState, owner: &Pubkey) -> Option<Vec<Pubkey>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, SOL_TLD.as_ref())),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(32, owner.as_ref())),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig { offset: 0, length: 0 }),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    state.solana_client
+        .get_program_accounts_with_config(&NAME_PROGRAM_ID, config)
+        .map(|accounts| accounts.into_iter().map(|(key, _)| key).collect())
+        .ok()
+}
+
+fn owns(state: &AppState, owner: &Pubkey, domain: &Pubkey) -> bool {
+    account(state, domain)
+        .filter(|account| account.owner == NAME_PROGRAM_ID)
+        .and_then(|account| owner_of(&account.data))
+        == Some(*owner)
+}
+
+/// The domain the wallet picked as its favourite, if it still owns it.
+fn favourite_domain(state: &AppState, owner: &Pubkey) -> Option<Pubkey> {
+    account(state, &favourite_key(owner))
+        .and_then(|account| Pubkey::try_from(account.data.get(1..33)?).ok())
+        .filter(|domain| owns(state, owner, domain))
+}
+
+/// The `.sol` name of the domain at `domain`. Subdomain names are stored
+/// with a leading NUL; only top-level domains are shown as a wallet's name.
+fn domain_name(state: &AppState, domain: &Pubkey) -> Option<String> {
+    reverse_name(&account(state, &reverse_key(domain))?.data)
+        .filter(|name| !name.is_empty() && !name.starts_with('\0'))
+        .map(|name| format!("{}.sol", name))
+}
+
+/// The wallet's favourite domain if it set one and still owns it, otherwise
+/// the shortest `.sol` domain it owns. None if the name program scan failed.
+fn lookup_primary(state: &AppState, owner: &Pubkey) -> Option<Option<String>> {
+    let domains = match favourite_domain(state, owner) {
+        Some(domain) => vec![domain],
+        None => owned_domains(state, owner)?,
+    };
+
+    let mut names: Vec<String> = domains.iter().filter_map(|domain| domain_name(state, domain)).collect();
+    names.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+    Some(names.into_iter().next())
+}
+
+/// The domain an earlier scan indexed for `owner`, if it still owns it.
+async fn indexed_domain(state: &AppState, owner: &Pubkey) -> Option<String> {
+    let name: String = sqlx::query_scalar("SELECT name FROM search_entries WHERE kind = 'domain' AND address = $1")
+        .bind(owner.to_string())
+        .fetch_optional(&state.db)
+        .await
+        .ok()??;
+    let key = domain_key(&name)?;
+    owns(state, owner, &key).then_some(name)
+}
+
+async fn index(state: &AppState, owner: &Pubkey, domain: &str) {
+    if let Err(e) = search_index::upsert(&state.db, "domain", &owner.to_string(), domain, None, 0.0).await {
+        tracing::warn!("failed to index domain {}: {}", domain, e);
+    }
+}
+
+/// Queues `owner` for the background scan of the name program, or asks for
+/// a rescan if it was scanned before.
+async fn request_scan(state: &AppState, owner: &Pubkey) {
+    let queued = sqlx::query(
+        "INSERT INTO sns_scans (owner) VALUES ($1)
+         ON CONFLICT (owner) DO UPDATE SET requested_at = NOW()"
+    )
+    .bind(owner.to_string())
+    .execute(&state.db)
+    .await;
+    if let Err(e) = queued {
+        tracing::warn!("failed to queue domain scan of {}: {}", owner, e);
+    }
+}
+
+fn primary_key(owner: &Pubkey) -> String {
+    format!("sns-primary:{}", owner)
+}
+
+/// Reverse lookup of `owner`'s primary domain: its favourite domain, else
+/// the one the background scan indexed for it. Wallets with neither are
+/// queued for a scan and cached as having none until it runs, so address
+/// pages never walk the name program themselves.
+pub async fn primary_domain(state: &AppState, owner: &Pubkey) -> Option<String> {
+    let key = primary_key(owner);
+    if let Some(domain) = state.cache.get::<Option<String>>(&key).await {
+        return domain;
+    }
+
+    let domain = match favourite_domain(state, owner).and_then(|domain| domain_name(state, &domain)) {
+        Some(domain) => {
+            index(state, owner, &domain).await;
+            Some(domain)
+        }
+        None => indexed_domain(state, owner).await,
+    };
+    if domain.is_none() {
+        request_scan(state, owner).await;
+    }
+    state.cache.put(&key, &domain, cache::DOMAIN).await;
+    domain
+}
+
+/// Scans the name program for the domains `owner` owns, indexes its primary
+/// one (or drops a stale entry if it has none) and refreshes the cached
+/// lookup. A failed scan leaves the wallet queued.
+pub async fn scan(state: &AppState, owner: &Pubkey) -> Result<Option<String>, sqlx::Error> {
+    let Some(domain) = lookup_primary(state, owner) else {
+        tracing::warn!("failed to scan the domains of {}", owner);
+        return Ok(None);
+    };
+
+    match &domain {
+        Some(domain) => search_index::upsert(&state.db, "domain", &owner.to_string(), domain, None, 0.0).await?,
+        None => search_index::remove(&state.db, "domain", &owner.to_string()).await?,
+    }
+    sqlx::query(
+        "INSERT INTO sns_scans (owner, scanned_at) VALUES ($1, NOW())
+         ON CONFLICT (owner) DO UPDATE SET scanned_at = NOW()"
+    )
+    .bind(owner.to_string())
+    .execute(&state.db)
+    .await?;
+    state.cache.put(&primary_key(owner), &domain, cache::DOMAIN).await;
+    Ok(domain)
+}
+
+/// Starts the background task that scans the domains of queued wallets every
+/// `SNS_SCAN_INTERVAL_SECS` (default 60). A wallet viewed again without a
+/// domain is rescanned once `SNS_RESCAN_INTERVAL_SECS` (default 86400) have
+/// passed since its last scan.
+pub fn spawn_scanner(state: AppState) {
+    let every = std::env::var("SNS_SCAN_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60);
+    let rescan_every: i64 = std::env::var("SNS_RESCAN_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(86400);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(every));
+        loop {
+            ticker.tick().await;
+            let owners: Vec<String> = sqlx::query_scalar(
+                "SELECT owner FROM sns_scans
+                 WHERE scanned_at IS NULL
+                    OR (requested_at > scanned_at AND scanned_at < NOW() - make_interval(secs => $1))
+                 ORDER BY requested_at
+                 LIMIT $2"
+            )
+            .bind(rescan_every as f64)
+            .bind(SCAN_BATCH)
+            .fetch_all(&state.db)
+            .await
+            .unwrap_or_default();
+
+            for owner in owners.iter().filter_map(|owner| Pubkey::from_str(owner).ok()) {
+                if let Err(e) = scan(&state, &owner).await {
+                    tracing::warn!("failed to index the domains of {}: {}", owner, e);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,203 @@
+// Shared helpers for talking to the Solana RPC node and picking apart its
+// encoded responses.
+use solana_client::rpc_config::{RpcBlockConfig, RpcTransactionConfig};
+use solana_sdk::{pubkey, pubkey::Pubkey};
+use solana_transaction_status::{
+    EncodedTransaction, TransactionDetails, UiInstruction, UiMessage, UiParsedInstruction,
+    UiTransactionEncoding,
+};
+
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VeNSNFo9ucLgEtudsUjDwMBHyuRWsK");
+
+/// Size of an SPL mint without Token-2022 extensions. Extended mints pad to
+/// the token-account size and then carry an account-type byte.
+const MINT_LEN: usize = 82;
+const ACCOUNT_TYPE_OFFSET: usize = 165;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+/// Size of an SPL token account without Token-2022 extensions.
+pub const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// Fields of an SPL token mint account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mint {
+    pub mint_authority: Option<Pubkey>,
+    pub supply: u64,
+    pub decimals: u8,
+    pub freeze_authority: Option<Pubkey>,
+}
+
+/// Decodes `data` as a mint if `owner` is one of the token programs and the
+/// layout is an initialized mint. Token accounts and multisigs return None.
+pub fn decode_mint(owner: &Pubkey, data: &[u8]) -> Option<Mint> {
+    if *owner != TOKEN_PROGRAM_ID && *owner != TOKEN_2022_PROGRAM_ID {
+        return None;
+    }
+    let is_mint = data.len() == MINT_LEN
+        || (*owner == TOKEN_2022_PROGRAM_ID
+            && data.len() > ACCOUNT_TYPE_OFFSET
+            && data[ACCOUNT_TYPE_OFFSET] == ACCOUNT_TYPE_MINT);
+    if !is_mint || data[45] != 1 {
+        return None;
+    }
+
+    Some(Mint {
+        mint_authority: coption_pubkey(&data[0..36]),
+        supply: u64::from_le_bytes(data[36..44].try_into().ok()?),
+        decimals: data[44],
+        freeze_authority: coption_pubkey(&data[46..82]),
+    })
+}
+
+/// Token-2022 extensions of a mint or token account as `(type, value)`
+/// pairs, read from the type-length-value area after the account-type byte.
+/// Empty for classic accounts.
+pub fn extensions(data: &[u8]) -> Vec<(u16, &[u8])> {
+    let mut extensions = Vec::new();
+    let mut offset = ACCOUNT_TYPE_OFFSET + 1;
+    while let Some(header) = data.get(offset..offset + 4) {
+        let kind = u16::from_le_bytes([header[0], header[1]]);
+        let len = u16::from_le_bytes([header[2], header[3]]) as usize;
+        // Zero padding after the last extension reads as type 0.
+        if kind == 0 {
+            break;
+        }
+        let Some(value) = data.get(offset + 4..offset + 4 + len) else { break };
+        extensions.push((kind, value));
+        offset += 4 + len;
+    }
+    extensions
+}
+
+/// Fields of an SPL token account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenAccount {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+/// Decodes `data` as an initialized token account of either token program.
+/// Extended Token-2022 accounts carry an account-type byte after the base
+/// layout.
+pub fn decode_token_account(owner: &Pubkey, data: &[u8]) -> Option<TokenAccount> {
+    if *owner != TOKEN_PROGRAM_ID && *owner != TOKEN_2022_PROGRAM_ID {
+        return None;
+    }
+    let is_account = data.len() == TOKEN_ACCOUNT_LEN
+        || (*owner == TOKEN_2022_PROGRAM_ID
+            && data.len() > ACCOUNT_TYPE_OFFSET
+            && data[ACCOUNT_TYPE_OFFSET] == ACCOUNT_TYPE_ACCOUNT);
+    // State 0 is uninitialized; 1 and 2 are initialized and frozen.
+    if !is_account || data[108] == 0 {
+        return None;
+    }
+
+    Some(TokenAccount {
+        mint: Pubkey::try_from(&data[0..32]).ok()?,
+        owner: Pubkey::try_from(&data[32..64]).ok()?,
+        amount: u64::from_le_bytes(data[64..72].try_into().ok()?),
+    })
+}
+
+/// A `COption<Pubkey>`: a u32 tag followed by the key.
+fn coption_pubkey(data: &[u8]) -> Option<Pubkey> {
+    match data[0..4] {
+        [1, 0, 0, 0] => Pubkey::try_from(&data[4..36]).ok(),
+        _ => None,
+    }
+}
+
+/// Block request config that accepts versioned transactions. Without
+/// `max_supported_transaction_version` the node rejects any block containing
+/// a v0 transaction, which is nearly every mainnet block.
+pub fn block_config(details: TransactionDetails, rewards: bool) -> RpcBlockConfig {
+    RpcBlockConfig {
+        encoding: Some(UiTransactionEncoding::Json),
+        transaction_details: Some(details),
+        rewards: Some(rewards),
+        commitment: None,
+        max_supported_transaction_version: Some(0),
+    }
+}
+
+/// Transaction request config that accepts versioned transactions, for the
+/// same reason as `block_config`.
+pub fn transaction_config() -> RpcTransactionConfig {
+    RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Json),
+        commitment: None,
+        max_supported_transaction_version: Some(0),
+    }
+}
+
+/// Every account key referenced by `tx`, in message order. Works for raw and
+/// parsed JSON messages as well as `TransactionDetails::Accounts` responses.
+pub fn account_keys(tx: &EncodedTransaction) -> Vec<String> {
+    match tx {
+        EncodedTransaction::Json(ui) => match &ui.message {
+            UiMessage::Raw(message) => message.account_keys.clone(),
+            UiMessage::Parsed(message) => message.account_keys
+                .iter()
+                .map(|a| a.pubkey.clone())
+                .collect(),
+        },
+        EncodedTransaction::Accounts(list) => list.account_keys
+            .iter()
+            .map(|a| a.pubkey.clone())
+            .collect(),
+        _ => tx.decode()
+            .map(|t| t.message.static_account_keys().iter().map(|k| k.to_string()).collect())
+            .unwrap_or_default(),
+    }
+}
+
+
+/// The first signature of `tx`, which identifies the transaction.
+pub fn signature(tx: &EncodedTransaction) -> Option<String> {
+    match tx {
+        EncodedTransaction::Json(ui) => ui.signatures.first().cloned(),
+        EncodedTransaction::Accounts(list) => list.signatures.first().cloned(),
+        _ => tx.decode().and_then(|t| t.signatures.first().map(|s| s.to_string())),
+    }
+}
+
+/// Program ids of the top-level instructions of `tx`.
+pub fn program_ids(tx: &EncodedTransaction) -> Vec<String> {
+    match tx {
+        EncodedTransaction::Json(ui) => match &ui.message {
+            UiMessage::Raw(message) => message.instructions
+                .iter()
+                .filter_map(|ix| message.account_keys.get(ix.program_id_index as usize).cloned())
+                .collect(),
+            UiMessage::Parsed(message) => message.instructions
+                .iter()
+                .filter_map(|ix| match ix {
+                    UiInstruction::Compiled(ix) => message.account_keys
+                        .get(ix.program_id_index as usize)
+                        .map(|a| a.pubkey.clone()),
+                    UiInstruction::Parsed(UiParsedInstruction::Parsed(ix)) => Some(ix.program_id.clone()),
+                    UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(ix)) => Some(ix.program_id.clone()),
+                })
+                .collect(),
+        },
+        EncodedTransaction::Accounts(_) => vec![],
+        _ => tx.decode()
+            .map(|t| {
+                let keys = t.message.static_account_keys();
+                t.message.instructions()
+                    .iter()
+                    .filter_map(|ix| keys.get(ix.program_id_index as usize).map(|k| k.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// Whether `tx` is a validator vote, i.e. only invokes the vote program.
+pub fn is_vote(tx: &EncodedTransaction) -> bool {
+    let vote_program = solana_sdk::vote::program::id().to_string();
+    let programs = program_ids(tx);
+    !programs.is_empty() && programs.iter().all(|p| *p == vote_program)
+}
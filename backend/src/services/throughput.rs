@@ -0,0 +1,71 @@
+use solana_client::rpc_response::RpcPerfSample;
+
+use crate::models::{ThroughputSample, ThroughputWindow};
+
+/// Performance samples are taken by the node roughly once a minute; this is
+/// the most the RPC method will return (12 hours).
+pub const MAX_SAMPLES: usize = 720;
+
+/// Window sizes for the rolling averages in `NetworkStats`, in samples.
+pub const WINDOWS: [(&str, usize); 3] = [("1m", 1), ("5m", 5), ("1h", 60)];
+
+/// Averages total and non-vote TPS over `samples` (newest first). Non-vote TPS
+/// is `None` when any sample in the window comes from a node too old to report it.
+pub fn average(samples: &[RpcPerfSample]) -> (f64, Option<f64>) {
+    let seconds: u64 = samples.iter().map(|s| s.sample_period_secs as u64).sum();
+    if seconds == 0 {
+        return (0.0, None);
+    }
+
+    let transactions: u64 = samples.iter().map(|s| s.num_transactions).sum();
+    let non_vote: Option<u64> = samples.iter().map(|s| s.num_non_vote_transactions).sum();
+
+    (
+        transactions as f64 / seconds as f64,
+        non_vote.map(|n| n as f64 / seconds as f64),
+    )
+}
+
+/// Average seconds per slot over `samples`, if any slots were recorded.
+pub fn slot_duration(samples: &[RpcPerfSample]) -> Option<f64> {
+    let slots: u64 = samples.iter().map(|s| s.num_slots).sum();
+    let seconds: u64 = samples.iter().map(|s| s.sample_period_secs as u64).sum();
+    (slots > 0).then(|| seconds as f64 / slots as f64)
+}
+
+pub fn windows(samples: &[RpcPerfSample]) -> Vec<ThroughputWindow> {
+    WINDOWS
+        .iter()
+        .map(|(window, size)| {
+            let (tps, non_vote_tps) = average(&samples[..samples.len().min(*size)]);
+            ThroughputWindow {
+                window: window.to_string(),
+                tps,
+                non_vote_tps,
+            }
+        })
+        .collect()
+}
+
+/// Buckets `samples` (newest first) into points of `bucket` samples each and
+/// returns them oldest first, ready to chart. Timestamps are reconstructed by
+/// walking back from `now` by each sample's period, since the RPC response
+/// carries only slots.
+pub fn series(samples: &[RpcPerfSample], bucket: usize, now: i64) -> Vec<ThroughputSample> {
+    let mut points = Vec::new();
+    let mut end = now;
+
+    for chunk in samples.chunks(bucket.max(1)) {
+        let (tps, non_vote_tps) = average(chunk);
+        points.push(ThroughputSample {
+            slot: chunk[0].slot as i64,
+            timestamp: end,
+            tps,
+            non_vote_tps,
+        });
+        end -= chunk.iter().map(|s| s.sample_period_secs as i64).sum::<i64>();
+    }
+
+    points.reverse();
+    points
+}
@@ -0,0 +1,191 @@
+// Token-2022 extensions: decodes the type-length-value entries of mints and
+// token accounts into named fields, using the names and field names of the
+// RPC's jsonParsed encoding, and flags the ones that put holders at risk.
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use crate::{
+    models::TokenExtension,
+    services::{solana, token_metadata},
+};
+
+/// Token account states, indexed by their byte value.
+const ACCOUNT_STATES: [&str; 3] = ["uninitialized", "initialized", "frozen"];
+
+/// Fixed-offset reads over an extension value.
+struct Fields<'a>(&'a [u8]);
+
+impl Fields<'_> {
+    fn bytes<const N: usize>(&self, offset: usize) -> Option<[u8; N]> {
+        self.0.get(offset..offset + N)?.try_into().ok()
+    }
+
+    /// An `OptionalNonZeroPubkey`: all zeroes means none.
+    fn pubkey(&self, offset: usize) -> Option<Value> {
+        let key = Pubkey::new_from_array(self.bytes::<32>(offset)?);
+        Some(if key == Pubkey::default() { Value::Null } else { json!(key.to_string()) })
+    }
+
+    fn bool(&self, offset: usize) -> Option<bool> {
+        Some(self.bytes::<1>(offset)?[0] != 0)
+    }
+
+    fn u16(&self, offset: usize) -> Option<u16> {
+        Some(u16::from_le_bytes(self.bytes(offset)?))
+    }
+
+    fn i16(&self, offset: usize) -> Option<i16> {
+        Some(i16::from_le_bytes(self.bytes(offset)?))
+    }
+
+    fn u64(&self, offset: usize) -> Option<u64> {
+        Some(u64::from_le_bytes(self.bytes(offset)?))
+    }
+
+    fn i64(&self, offset: usize) -> Option<i64> {
+        Some(i64::from_le_bytes(self.bytes(offset)?))
+    }
+
+    fn f64(&self, offset: usize) -> Option<f64> {
+        Some(f64::from_le_bytes(self.bytes(offset)?))
+    }
+
+    fn transfer_fee(&self, offset: usize) -> Option<Value> {
+        Some(json!({
+            "epoch": self.u64(offset)?,
+            "maximumFee": self.u64(offset + 8)?,
+            "transferFeeBasisPoints": self.u16(offset + 16)?,
+        }))
+    }
+}
+
+/// Extension names by type, as the RPC's jsonParsed encoding names them.
+const NAMES: [&str; 28] = [
+    "uninitialized",
+    "transferFeeConfig",
+    "transferFeeAmount",
+    "mintCloseAuthority",
+    "confidentialTransferMint",
+    "confidentialTransferAccount",
+    "defaultAccountState",
+    "immutableOwner",
+    "memoTransfer",
+    "nonTransferable",
+    "interestBearingConfig",
+    "cpiGuard",
+    "permanentDelegate",
+    "nonTransferableAccount",
+    "transferHook",
+    "transferHookAccount",
+    "confidentialTransferFeeConfig",
+    "confidentialTransferFeeAmount",
+    "metadataPointer",
+    "tokenMetadata",
+    "groupPointer",
+    "tokenGroup",
+    "groupMemberPointer",
+    "tokenGroupMember",
+    "confidentialMintBurn",
+    "scaledUiAmountConfig",
+    "pausableConfig",
+    "pausableAccount",
+];
+
+/// Decoded fields of extension `kind`. None for extensions without fields
+/// and for values too short for their layout. Encrypted fields are left out.
+fn decode_state(kind: u16, value: &[u8]) -> Option<Value> {
+    let f = Fields(value);
+    Some(match kind {
+        1 => json!({
+            "transferFeeConfigAuthority": f.pubkey(0)?,
+            "withdrawWithheldAuthority": f.pubkey(32)?,
+            "withheldAmount": f.u64(64)?,
+            "olderTransferFee": f.transfer_fee(72)?,
+            "newerTransferFee": f.transfer_fee(90)?,
+        }),
+        2 => json!({ "withheldAmount": f.u64(0)? }),
+        3 => json!({ "closeAuthority": f.pubkey(0)? }),
+        4 => json!({
+            "authority": f.pubkey(0)?,
+            "autoApproveNewAccounts": f.bool(32)?,
+            "auditorElgamalPubkey": f.pubkey(33)?,
+        }),
+        5 => json!({ "approved": f.bool(0)? }),
+        6 => json!({ "accountState": ACCOUNT_STATES.get(f.bytes::<1>(0)?[0] as usize)? }),
+        8 => json!({ "requireIncomingTransferMemos": f.bool(0)? }),
+        10 => json!({
+            "rateAuthority": f.pubkey(0)?,
+            "initializationTimestamp": f.i64(32)?,
+            "preUpdateAverageRate": f.i16(40)?,
+            "lastUpdateTimestamp": f.i64(42)?,
+            "currentRate": f.i16(50)?,
+        }),
+        11 => json!({ "lockCpi": f.bool(0)? }),
+        12 => json!({ "delegate": f.pubkey(0)? }),
+        14 => json!({ "authority": f.pubkey(0)?, "programId": f.pubkey(32)? }),
+        15 => json!({ "transferring": f.bool(0)? }),
+        16 => json!({ "authority": f.pubkey(0)?, "harvestToMintEnabled": f.bool(64)? }),
+        18 => json!({ "authority": f.pubkey(0)?, "metadataAddress": f.pubkey(32)? }),
+        19 => {
+            let metadata = token_metadata::parse_token_2022(value)?;
+            json!({
+                "updateAuthority": metadata.update_authority.map(|k| k.to_string()),
+                "name": metadata.name,
+                "symbol": metadata.symbol,
+                "uri": metadata.uri,
+            })
+        }
+        20 => json!({ "authority": f.pubkey(0)?, "groupAddress": f.pubkey(32)? }),
+        21 => json!({
+            "updateAuthority": f.pubkey(0)?,
+            "mint": f.pubkey(32)?,
+            "size": f.u64(64)?,
+            "maxSize": f.u64(72)?,
+        }),
+        22 => json!({ "authority": f.pubkey(0)?, "memberAddress": f.pubkey(32)? }),
+        23 => json!({ "mint": f.pubkey(0)?, "group": f.pubkey(32)?, "memberNumber": f.u64(64)? }),
+        25 => json!({
+            "authority": f.pubkey(0)?,
+            "multiplier": f.f64(32)?,
+            "newMultiplierEffectiveTimestamp": f.i64(40)?,
+            "newMultiplier": f.f64(48)?,
+        }),
+        26 => json!({ "authority": f.pubkey(0)?, "paused": f.bool(32)? }),
+        kind if NAMES.get(kind as usize).is_some() => return None,
+        kind => json!({ "type": kind }),
+    })
+}
+
+/// Whether an extension lets someone other than the holder move, freeze,
+/// tax or lock up tokens.
+fn is_risky(extension: &str, state: &Value) -> bool {
+    match extension {
+        "permanentDelegate" => !state["delegate"].is_null(),
+        "nonTransferable" | "nonTransferableAccount" => true,
+        "transferHook" => !state["programId"].is_null(),
+        "defaultAccountState" => state["accountState"] == "frozen",
+        "pausableConfig" => !state["authority"].is_null() || state["paused"] == true,
+        // A fee authority can raise the fee at any time.
+        "transferFeeConfig" => {
+            !state["transferFeeConfigAuthority"].is_null()
+                || state["newerTransferFee"]["transferFeeBasisPoints"].as_u64().unwrap_or(0) > 0
+        }
+        _ => false,
+    }
+}
+
+/// Every extension of a Token-2022 mint or token account, in account order.
+/// Empty for classic SPL accounts.
+pub fn decode(data: &[u8]) -> Vec<TokenExtension> {
+    solana::extensions(data)
+        .into_iter()
+        .map(|(kind, value)| {
+            let extension = NAMES.get(kind as usize).copied().unwrap_or("unknown");
+            let state = decode_state(kind, value).unwrap_or(Value::Null);
+            TokenExtension {
+                risky: is_risky(extension, &state),
+                extension: extension.to_string(),
+                state,
+            }
+        })
+        .collect()
+}
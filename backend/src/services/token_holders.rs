@@ -0,0 +1,249 @@
+// Token holders: the largest accounts of a mint from RPC, and a per-mint
+// snapshot of every token account that backs the holder counts.
+use chrono::{DateTime, Utc};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::pubkey::Pubkey;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    time::Duration,
+};
+use crate::{
+    AppState,
+    models::{TokenHolder, TokenHolders},
+    services::{cache, solana, token_metadata},
+};
+
+/// Mints with at least this many holders keep only their count: their
+/// accounts aren't stored, and the periodic indexer rescans them less often.
+pub const MAX_INDEXED_HOLDERS: i64 = 100_000;
+
+/// Every token account of `mint`, read with getProgramAccounts. None if the
+/// node refuses the scan, which large mints can trigger.
+fn scan(state: &AppState, mint: &Pubkey, program: &Pubkey) -> Option<Vec<(Pubkey, solana::TokenAccount)>> {
+    let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, mint.as_ref()))];
+    // Classic token accounts are fixed-size; Token-2022 ones grow with their
+    // extensions.
+    if *program == solana::TOKEN_PROGRAM_ID {
+        filters.push(RpcFilterType::DataSize(solana::TOKEN_ACCOUNT_LEN as u64));
+    }
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    let accounts = state.solana_client
+        .get_program_accounts_with_config(program, config)
+        .map_err(|e| tracing::warn!("failed to scan token accounts of {}: {}", mint, e))
+        .ok()?;
+    Some(
+        accounts
+            .into_iter()
+            .filter_map(|(key, account)| Some((key, solana::decode_token_account(&account.owner, &account.data)?)))
+            .filter(|(_, account)| account.mint == *mint)
+            .collect(),
+    )
+}
+
+/// Replaces the indexed token accounts of `mint` and returns its holder
+/// count. Ok(None) if `mint` isn't a mint or its accounts can't be scanned;
+/// the previous snapshot is kept in that case. Mints with
+/// `MAX_INDEXED_HOLDERS` or more holders only have their count recorded.
+pub async fn index_mint(state: &AppState, mint: &Pubkey) -> Result<Option<i64>, sqlx::Error> {
+    let Some(program) = state.solana_client.get_account(mint).ok().map(|account| account.owner) else {
+        return Ok(None);
+    };
+    let Some(accounts) = scan(state, mint, &program) else {
+        return Ok(None);
+    };
+
+    let holders = accounts
+        .iter()
+        .filter(|(_, account)| account.amount > 0)
+        .map(|(_, account)| account.owner)
+        .collect::<HashSet<_>>()
+        .len() as i64;
+    if holders >= MAX_INDEXED_HOLDERS {
+        let mut tx = state.db.begin().await?;
+        sqlx::query("DELETE FROM token_accounts WHERE mint = $1")
+            .bind(mint.to_string())
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "INSERT INTO token_holder_counts (mint, holders) VALUES ($1, $2)
+             ON CONFLICT (mint) DO UPDATE SET holders = EXCLUDED.holders, indexed_at = NOW()"
+        )
+        .bind(mint.to_string())
+        .bind(holders)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        return Ok(Some(holders));
+    }
+
+    let addresses: Vec<String> = accounts.iter().map(|(key, _)| key.to_string()).collect();
+    let owners: Vec<String> = accounts.iter().map(|(_, account)| account.owner.to_string()).collect();
+    let amounts: Vec<String> = accounts.iter().map(|(_, account)| account.amount.to_string()).collect();
+
+    let mut tx = state.db.begin().await?;
+    sqlx::query("DELETE FROM token_accounts WHERE mint = $1")
+        .bind(mint.to_string())
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "INSERT INTO token_accounts (address, mint, owner, amount)
+         SELECT address, $2, owner, amount::numeric
+         FROM UNNEST($1::text[], $3::text[], $4::text[]) AS t(address, owner, amount)
+         ON CONFLICT (address) DO UPDATE
+         SET mint = EXCLUDED.mint, owner = EXCLUDED.owner, amount = EXCLUDED.amount, updated_at = NOW()"
+    )
+    .bind(&addresses)
+    .bind(mint.to_string())
+    .bind(&owners)
+    .bind(&amounts)
+    .execute(&mut *tx)
+    .await?;
+    let (holders,): (i64,) = sqlx::query_as(
+        "INSERT INTO token_holder_counts (mint, holders)
+         SELECT $1, COUNT(DISTINCT owner) FROM token_accounts WHERE mint = $1 AND amount > 0
+         ON CONFLICT (mint) DO UPDATE SET holders = EXCLUDED.holders, indexed_at = NOW()
+         RETURNING holders"
+    )
+    .bind(mint.to_string())
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(Some(holders))
+}
+
+/// Holder count of `mint` and when it was taken. None if the mint has never
+/// been indexed.
+pub async fn holder_count(db: &sqlx::PgPool, mint: &str) -> Option<(i64, DateTime<Utc>)> {
+    sqlx::query_as::<_, (i64, DateTime<Utc>)>(
+        "SELECT holders, indexed_at FROM token_holder_counts WHERE mint = $1"
+    )
+    .bind(mint)
+    .fetch_optional(db)
+    .await
+    .ok()
+    .flatten()
+}
+
+/// Owners of the given token accounts: from the index where possible, the
+/// rest read from RPC in one call.
+async fn owners(state: &AppState, addresses: &[String]) -> HashMap<String, String> {
+    let mut owners: HashMap<String, String> = sqlx::query_as::<_, (String, String)>(
+        "SELECT address, owner FROM token_accounts WHERE address = ANY($1)"
+    )
+    .bind(addresses)
+    .fetch_all(&state.db)
+    .await
+    .unwrap_or_default()
+    .into_iter()
+    .collect();
+
+    let missing: Vec<Pubkey> = addresses
+        .iter()
+        .filter(|address| !owners.contains_key(*address))
+        .filter_map(|address| Pubkey::from_str(address).ok())
+        .collect();
+    if missing.is_empty() {
+        return owners;
+    }
+    let accounts = state.solana_client.get_multiple_accounts(&missing).unwrap_or_default();
+    for (key, account) in missing.iter().zip(accounts) {
+        if let Some(account) = account.and_then(|a| solana::decode_token_account(&a.owner, &a.data)) {
+            owners.insert(key.to_string(), account.owner.to_string());
+        }
+    }
+    owners
+}
+
+/// The largest holders of `mint` with their share of supply, plus the
+/// indexed holder count. None if `mint` isn't a mint.
+pub async fn top_holders(state: &AppState, mint: &Pubkey) -> Option<TokenHolders> {
+    let key = format!("token-holders:{}", mint);
+    if let Some(cached) = state.cache.get::<TokenHolders>(&key).await {
+        return Some(cached);
+    }
+
+    let token = token_metadata::fetch(state, mint).await?;
+    let supply: u64 = token.supply.parse().unwrap_or(0);
+    let largest = state.solana_client.get_token_largest_accounts(mint).unwrap_or_default();
+    let addresses: Vec<String> = largest.iter().map(|account| account.address.clone()).collect();
+    let owners = owners(state, &addresses).await;
+
+    let holders = largest
+        .into_iter()
+        .map(|account| {
+            let amount: u64 = account.amount.amount.parse().unwrap_or(0);
+            TokenHolder {
+                owner: owners.get(&account.address).cloned(),
+                ui_amount: amount as f64 / 10f64.powi(token.decimals as i32),
+                share: if supply > 0 { amount as f64 / supply as f64 } else { 0.0 },
+                amount: account.amount.amount,
+                address: account.address,
+            }
+        })
+        .collect();
+    let count = holder_count(&state.db, &token.mint).await;
+
+    let result = TokenHolders {
+        mint: token.mint,
+        holders,
+        holder_count: count.map(|(holders, _)| holders),
+        indexed_at: count.map(|(_, indexed_at)| indexed_at),
+    };
+    state.cache.put(&key, &result, cache::TOKEN).await;
+    Some(result)
+}
+
+/// Starts the background task that re-indexes the holders of every listed
+/// token every `TOKEN_HOLDERS_INTERVAL_SECS` (default 3600). Mints already
+/// known to have `MAX_INDEXED_HOLDERS` or more holders are too heavy to scan
+/// that often, so their count is only refreshed every
+/// `TOKEN_HOLDERS_LARGE_INTERVAL_SECS` (default 86400).
+pub fn spawn_indexer(state: AppState) {
+    let every = std::env::var("TOKEN_HOLDERS_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(3600);
+    let large_every: i64 = std::env::var("TOKEN_HOLDERS_LARGE_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(86400);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(every));
+        loop {
+            ticker.tick().await;
+            let mints: Vec<String> = sqlx::query_scalar(
+                "SELECT t.mint FROM tokens t
+                 LEFT JOIN token_holder_counts c ON c.mint = t.mint
+                 WHERE c.holders IS NULL OR c.holders < $1
+                    OR c.indexed_at < NOW() - make_interval(secs => $2)
+                 ORDER BY t.market_cap DESC NULLS LAST"
+            )
+            .bind(MAX_INDEXED_HOLDERS)
+            .bind(large_every as f64)
+            .fetch_all(&state.db)
+            .await
+            .unwrap_or_default();
+
+            for mint in mints.iter().filter_map(|mint| Pubkey::from_str(mint).ok()) {
+                if let Err(e) = index_mint(&state, &mint).await {
+                    tracing::warn!("failed to index holders of {}: {}", mint, e);
+                }
+            }
+        }
+    });
+}
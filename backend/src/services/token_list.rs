@@ -0,0 +1,231 @@
+// The token listing: one row per known mint in the `tokens` table, kept
+// current by a background job and queried with sorting and filters.
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::{str::FromStr, time::Duration};
+use crate::{
+    AppState,
+    models::{Token, TokenDetails},
+    services::{prices, search_index, solana},
+};
+
+/// Mints per getMultipleAccounts call, the RPC maximum.
+const ACCOUNTS_BATCH: usize = 100;
+
+/// Sortable columns by `sort` value. Anything else sorts by volume.
+const SORTS: [&str; 5] = ["volume_24h", "market_cap", "price", "price_change_24h", "holders"];
+
+#[derive(Debug, Default)]
+pub struct TokenFilter {
+    pub verified: Option<bool>,
+    /// Token program id.
+    pub program: Option<String>,
+    pub min_liquidity: Option<f64>,
+}
+
+/// An entry of a verified token list in the common token-list format.
+#[derive(Debug, Deserialize)]
+pub struct VerifiedToken {
+    #[serde(alias = "mint")]
+    pub address: String,
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+    pub decimals: Option<i16>,
+    #[serde(rename = "logoURI", alias = "logo_uri")]
+    pub logo_uri: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    Invalid(String),
+    Db(sqlx::Error),
+}
+
+impl From<sqlx::Error> for ImportError {
+    fn from(e: sqlx::Error) -> Self {
+        ImportError::Db(e)
+    }
+}
+
+/// Parses a verified token list: either a bare array of entries or a
+/// token-list document with a `tokens` array.
+pub fn parse_verified_list(text: &str) -> Result<Vec<VerifiedToken>, String> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum List {
+        Tokens(Vec<VerifiedToken>),
+        Document { tokens: Vec<VerifiedToken> },
+    }
+
+    let tokens = match serde_json::from_str::<List>(text).map_err(|e| e.to_string())? {
+        List::Tokens(tokens) | List::Document { tokens } => tokens,
+    };
+    for token in &tokens {
+        Pubkey::from_str(&token.address).map_err(|_| format!("invalid mint {}", token.address))?;
+    }
+    Ok(tokens)
+}
+
+/// Marks every listed token verified, adding the ones not yet known. Returns
+/// how many were imported.
+pub async fn import_verified(db: &sqlx::PgPool, tokens: &[VerifiedToken]) -> Result<usize, ImportError> {
+    let mut tx = db.begin().await?;
+    for token in tokens {
+        sqlx::query(
+            "INSERT INTO tokens (mint, symbol, name, decimals, logo_uri, verified)
+             VALUES ($1, $2, $3, COALESCE($4, 0), $5, true)
+             ON CONFLICT (mint) DO UPDATE
+             SET symbol = COALESCE(tokens.symbol, EXCLUDED.symbol),
+                 name = COALESCE(tokens.name, EXCLUDED.name),
+                 logo_uri = COALESCE(tokens.logo_uri, EXCLUDED.logo_uri),
+                 verified = true,
+                 updated_at = NOW()"
+        )
+        .bind(&token.address)
+        .bind(&token.symbol)
+        .bind(&token.name)
+        .bind(token.decimals)
+        .bind(&token.logo_uri)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(tokens.len())
+}
+
+/// Adds a newly resolved token or refreshes its metadata. Listing fields
+/// such as price and verification are left alone.
+pub async fn upsert(db: &sqlx::PgPool, token: &TokenDetails) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO tokens (mint, symbol, name, program, decimals, supply, logo_uri)
+         VALUES ($1, $2, $3, $4, $5, $6::numeric, $7)
+         ON CONFLICT (mint) DO UPDATE
+         SET symbol = COALESCE(EXCLUDED.symbol, tokens.symbol),
+             name = COALESCE(EXCLUDED.name, tokens.name),
+             program = EXCLUDED.program,
+             decimals = EXCLUDED.decimals,
+             supply = EXCLUDED.supply,
+             logo_uri = COALESCE(EXCLUDED.logo_uri, tokens.logo_uri),
+             updated_at = NOW()"
+    )
+    .bind(&token.mint)
+    .bind(&token.symbol)
+    .bind(&token.name)
+    .bind(&token.program)
+    .bind(token.decimals as i16)
+    .bind(&token.supply)
+    .bind(&token.logo_uri)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// One page of the listing and the number of tokens matching `filter`.
+/// Tokens missing the sort value come last.
+pub async fn list(
+    db: &sqlx::PgPool,
+    filter: &TokenFilter,
+    sort: &str,
+    ascending: bool,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<Token>, i64), sqlx::Error> {
+    let column = SORTS.iter().find(|s| **s == sort).copied().unwrap_or("volume_24h");
+    let direction = if ascending { "ASC" } else { "DESC" };
+    let filters = "($1::boolean IS NULL OR verified = $1)
+         AND ($2::text IS NULL OR program = $2)
+         AND ($3::float8 IS NULL OR liquidity >= $3)";
+
+    let tokens = sqlx::query_as::<_, Token>(&format!(
+        "SELECT mint, COALESCE(symbol, '') AS symbol, COALESCE(name, '') AS name, program, decimals,
+                logo_uri, verified, price, price_change_24h, volume_24h, liquidity, market_cap, holders,
+                risk_flags
+         FROM tokens
+         WHERE {}
+         ORDER BY {} {} NULLS LAST, mint
+         LIMIT $4 OFFSET $5",
+        filters, column, direction
+    ))
+    .bind(filter.verified)
+    .bind(&filter.program)
+    .bind(filter.min_liquidity)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(db)
+    .await?;
+
+    let (total,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM tokens WHERE {}", filters))
+        .bind(filter.verified)
+        .bind(&filter.program)
+        .bind(filter.min_liquidity)
+        .fetch_one(db)
+        .await?;
+
+    Ok((tokens, total))
+}
+
+/// Re-reads the supply of every listed mint, reprices it, picks up holder
+/// counts from the holder index and ranks the tokens for search by them.
+pub async fn refresh(state: &AppState) -> Result<(), sqlx::Error> {
+    let mints: Vec<String> = sqlx::query_scalar("SELECT mint FROM tokens")
+        .fetch_all(&state.db)
+        .await?;
+
+    let keys: Vec<Pubkey> = mints.iter().filter_map(|mint| Pubkey::from_str(mint).ok()).collect();
+    for batch in keys.chunks(ACCOUNTS_BATCH) {
+        let accounts = state.solana_client.get_multiple_accounts(batch).unwrap_or_default();
+        for (mint, account) in batch.iter().zip(accounts) {
+            let Some(fields) = account.and_then(|a| solana::decode_mint(&a.owner, &a.data)) else { continue };
+            sqlx::query("UPDATE tokens SET supply = $2::numeric, decimals = $3 WHERE mint = $1")
+                .bind(mint.to_string())
+                .bind(fields.supply.to_string())
+                .bind(fields.decimals as i16)
+                .execute(&state.db)
+                .await?;
+        }
+    }
+
+    for (mint, price) in prices::prices(state, &mints).await {
+        sqlx::query(
+            "UPDATE tokens
+             SET price = $2, price_change_24h = $3,
+                 market_cap = $2 * supply::float8 / power(10, decimals::float8),
+                 updated_at = NOW()
+             WHERE mint = $1"
+        )
+        .bind(mint)
+        .bind(price.price)
+        .bind(price.price_change_24h)
+        .execute(&state.db)
+        .await?;
+    }
+
+    sqlx::query(
+        "UPDATE tokens t SET holders = c.holders
+         FROM token_holder_counts c
+         WHERE c.mint = t.mint AND t.holders IS DISTINCT FROM c.holders"
+    )
+    .execute(&state.db)
+    .await?;
+
+    search_index::rank_tokens(&state.db).await
+}
+
+/// Starts the background task that refreshes the listing every
+/// `TOKEN_LIST_INTERVAL_SECS` (default 300).
+pub fn spawn_refresher(state: AppState) {
+    let every = std::env::var("TOKEN_LIST_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(300);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(every));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = refresh(&state).await {
+                tracing::warn!("failed to refresh token list: {}", e);
+            }
+        }
+    });
+}
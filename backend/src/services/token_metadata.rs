@@ -0,0 +1,334 @@
+// Token metadata: mint fields plus name/symbol/uri from either the Token-2022
+// metadata extension or the Metaplex metadata account, and image/description
+// from the off-chain JSON the uri points at.
+use serde::{Deserialize, Serialize};
+use solana_sdk::{account::Account, pubkey, pubkey::Pubkey};
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+use crate::{
+    AppState,
+    models::TokenDetails,
+    services::{cache, search_index, solana, token_extensions, token_list},
+};
+
+pub const METADATA_PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Token-2022 extension type of inline `TokenMetadata`.
+pub const TOKEN_METADATA_EXTENSION: u16 = 19;
+
+/// Metaplex account key of a `MetadataV1` account.
+const METAPLEX_METADATA_V1: u8 = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnChainMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub update_authority: Option<Pubkey>,
+    /// Whether the update authority can still change the metadata.
+    pub mutable: bool,
+    pub source: &'static str,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct OffChainMetadata {
+    pub image: Option<String>,
+    pub description: Option<String>,
+    /// NFT traits, as the JSON has them.
+    pub attributes: Option<serde_json::Value>,
+}
+
+/// Borsh reader over account data.
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.offset..self.offset + len)?;
+        self.offset += len;
+        Some(bytes)
+    }
+
+    pub fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|b| b[0])
+    }
+
+    pub fn bool(&mut self) -> Option<bool> {
+        Some(self.u8()? != 0)
+    }
+
+    pub fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes(self.bytes(2)?.try_into().ok()?))
+    }
+
+    pub fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+
+    pub fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.bytes(8)?.try_into().ok()?))
+    }
+
+    pub fn pubkey(&mut self) -> Option<Pubkey> {
+        Pubkey::try_from(self.bytes(32)?).ok()
+    }
+
+    /// A u32-length-prefixed string. Metaplex pads fixed-size fields with
+    /// NULs, which are dropped.
+    pub fn string(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        let raw = String::from_utf8_lossy(self.bytes(len)?).into_owned();
+        Some(raw.trim_end_matches('\0').trim().to_string())
+    }
+}
+
+pub fn metadata_key(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"metadata", METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &METADATA_PROGRAM_ID,
+    ).0
+}
+
+/// Reads past the seller fee, creators and primary-sale flag that follow the
+/// uri of a Metaplex account to its `is_mutable` flag.
+fn metaplex_is_mutable(reader: &mut Reader) -> Option<bool> {
+    reader.bytes(2)?;
+    if reader.u8()? == 1 {
+        let creators = reader.u32()? as usize;
+        // Each creator is a pubkey, a verified flag and a share.
+        reader.bytes(creators * 34)?;
+    }
+    reader.u8()?;
+    Some(reader.u8()? != 0)
+}
+
+/// Parses a Metaplex `MetadataV1` account. Accounts cut off before the
+/// `is_mutable` flag are taken to be mutable.
+pub fn parse_metaplex(data: &[u8]) -> Option<OnChainMetadata> {
+    let mut reader = Reader::new(data);
+    if reader.u8()? != METAPLEX_METADATA_V1 {
+        return None;
+    }
+    let update_authority = reader.pubkey()?;
+    let _mint = reader.pubkey()?;
+    let (name, symbol, uri) = (reader.string()?, reader.string()?, reader.string()?);
+
+    Some(OnChainMetadata {
+        name,
+        symbol,
+        uri,
+        update_authority: Some(update_authority),
+        mutable: metaplex_is_mutable(&mut reader).unwrap_or(true),
+        source: "metaplex",
+    })
+}
+
+/// Parses the value of a Token-2022 `TokenMetadata` extension. An all-zero
+/// update authority means the metadata is immutable.
+pub fn parse_token_2022(value: &[u8]) -> Option<OnChainMetadata> {
+    let mut reader = Reader::new(value);
+    let update_authority = reader.pubkey()?;
+    let _mint = reader.pubkey()?;
+
+    Some(OnChainMetadata {
+        name: reader.string()?,
+        symbol: reader.string()?,
+        uri: reader.string()?,
+        update_authority: (update_authority != Pubkey::default()).then_some(update_authority),
+        mutable: update_authority != Pubkey::default(),
+        source: "token-2022",
+    })
+}
+
+fn account(state: &AppState, key: &Pubkey) -> Option<Account> {
+    state.solana_client
+        .get_account_with_commitment(key, state.solana_client.commitment())
+        .ok()?
+        .value
+}
+
+/// Mint fields and on-chain metadata. Inline Token-2022 metadata wins; mints
+/// without it fall back to the Metaplex metadata account.
+fn on_chain(state: &AppState, mint: &Pubkey) -> Option<TokenDetails> {
+    let mint_account = account(state, mint)?;
+    let fields = solana::decode_mint(&mint_account.owner, &mint_account.data)?;
+
+    let metadata = solana::extensions(&mint_account.data)
+        .into_iter()
+        .find(|(kind, _)| *kind == TOKEN_METADATA_EXTENSION)
+        .and_then(|(_, value)| parse_token_2022(value))
+        .or_else(|| {
+            account(state, &metadata_key(mint))
+                .filter(|account| account.owner == METADATA_PROGRAM_ID)
+                .and_then(|account| parse_metaplex(&account.data))
+        });
+
+    let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+    Some(TokenDetails {
+        mint: mint.to_string(),
+        program: mint_account.owner.to_string(),
+        supply: fields.supply.to_string(),
+        decimals: fields.decimals,
+        mint_authority: fields.mint_authority.map(|k| k.to_string()),
+        freeze_authority: fields.freeze_authority.map(|k| k.to_string()),
+        name: metadata.as_ref().and_then(|m| non_empty(&m.name)),
+        symbol: metadata.as_ref().and_then(|m| non_empty(&m.symbol)),
+        uri: metadata.as_ref().and_then(|m| non_empty(&m.uri)),
+        logo_uri: None,
+        description: None,
+        update_authority: metadata.as_ref().and_then(|m| m.update_authority).map(|k| k.to_string()),
+        metadata_source: metadata.as_ref().map(|m| m.source.to_string()),
+        metadata_mutable: metadata.as_ref().map(|m| m.mutable),
+        extensions: token_extensions::decode(&mint_account.data),
+    })
+}
+
+/// Largest off-chain JSON document read; anything bigger is treated as
+/// missing.
+const MAX_JSON_BYTES: usize = 1 << 20;
+
+/// Whether `ip` is routable on the public internet. Token URIs are chosen
+/// by whoever minted the token, so they must not reach private, loopback or
+/// link-local addresses (cloud metadata endpoints among them).
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || a == 0
+                // Carrier-grade NAT, 100.64.0.0/10.
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    // Unique local, fc00::/7, and link-local, fe80::/10.
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Fetches the JSON at an http(s) `uri`. The host is resolved once and
+/// every address vetted, and the request is pinned to that address with
+/// redirects off, so a rebinding or redirecting host can't slip past the
+/// check. Private hosts are allowed when `METADATA_ALLOW_PRIVATE_HOSTS` is
+/// set, for local development.
+async fn fetch_json(uri: &str) -> Result<OffChainMetadata, String> {
+    let url = reqwest::Url::parse(uri).map_err(|e| e.to_string())?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("unsupported scheme".to_string());
+    }
+    let host = url.host_str().ok_or("missing host")?;
+    let port = url.port_or_known_default().ok_or("missing port")?;
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host.trim_matches(['[', ']']), port))
+        .await
+        .map_err(|e| e.to_string())?
+        .collect();
+    let allow_private = std::env::var("METADATA_ALLOW_PRIVATE_HOSTS").map_or(false, |v| v == "true");
+    if !allow_private && addresses.iter().any(|address| !is_public(address.ip())) {
+        return Err("host resolves to a non-public address".to_string());
+    }
+    let address = *addresses.first().ok_or("host did not resolve")?;
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve(host, address)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut response = client.get(url).send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("status {}", response.status()));
+    }
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if !content_type.contains("json") {
+        return Err(format!("content type {:?}", content_type));
+    }
+    if response.content_length().map_or(false, |len| len > MAX_JSON_BYTES as u64) {
+        return Err("body too large".to_string());
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        if body.len() + chunk.len() > MAX_JSON_BYTES {
+            return Err("body too large".to_string());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    serde_json::from_slice(&body).map_err(|e| e.to_string())
+}
+
+/// The off-chain JSON at `uri`. Documents are cached for a day; failures
+/// only briefly, so a host that was down is retried soon.
+pub(crate) async fn off_chain(state: &AppState, uri: &str) -> OffChainMetadata {
+    let key = format!("token-json:{}", uri);
+    if let Some(cached) = state.cache.get::<OffChainMetadata>(&key).await {
+        return cached;
+    }
+
+    let (metadata, ttl) = match fetch_json(uri).await {
+        Ok(metadata) => (metadata, cache::TOKEN_JSON),
+        Err(e) => {
+            if !uri.is_empty() {
+                tracing::warn!("failed to fetch token metadata from {}: {}", uri, e);
+            }
+            (OffChainMetadata::default(), cache::TOKEN_JSON_FAILURE)
+        }
+    };
+    state.cache.put(&key, &metadata, ttl).await;
+    metadata
+}
+
+/// Full token details for `mint`, or None if it isn't a mint account. Newly
+/// read tokens are added to the token list, and named ones to the search
+/// index.
+pub async fn fetch(state: &AppState, mint: &Pubkey) -> Option<TokenDetails> {
+    let key = format!("token:{}", mint);
+    let (mut token, fresh) = match state.cache.get::<TokenDetails>(&key).await {
+        Some(token) => (token, false),
+        None => {
+            let token = on_chain(state, mint)?;
+            state.cache.put(&key, &token, cache::TOKEN).await;
+            (token, true)
+        }
+    };
+
+    if let Some(uri) = token.uri.clone() {
+        let metadata = off_chain(state, &uri).await;
+        token.logo_uri = metadata.image;
+        token.description = metadata.description;
+    }
+
+    if fresh {
+        if let Err(e) = token_list::upsert(&state.db, &token).await {
+            tracing::warn!("failed to list token {}: {}", token.mint, e);
+        }
+        if let Some(name) = &token.name {
+            if let Err(e) = search_index::upsert(&state.db, "token", &token.mint, name, token.symbol.as_deref(), 0.0).await {
+                tracing::warn!("failed to index token {}: {}", token.mint, e);
+            }
+        }
+    }
+    Some(token)
+}
@@ -0,0 +1,299 @@
+// Token risk reports: checks a mint for the authorities, holder
+// distribution, liquidity, age and naming patterns common to scam tokens,
+// and records the flags raised on its listing row.
+use chrono::{DateTime, Duration, Utc};
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use unicode_normalization::UnicodeNormalization;
+use crate::{
+    AppState,
+    models::{RiskFlag, TokenRisk},
+    services::{cache, token_holders, token_metadata},
+};
+
+/// Largest accounts counted towards holder concentration.
+const TOP_HOLDERS: usize = 10;
+/// Share of supply the top holders may own before it is flagged.
+const MAX_TOP_HOLDERS_SHARE: f64 = 0.5;
+/// USD liquidity below which a token is flagged.
+const MIN_LIQUIDITY: f64 = 10_000.0;
+/// Tokens whose first transaction is more recent than this are flagged.
+const NEW_TOKEN_DAYS: i64 = 7;
+/// Signatures read per page when looking for a mint's first transaction.
+const AGE_SIGNATURES: usize = 1000;
+/// Pages of signatures read before giving up on finding the first one.
+const AGE_PAGES: usize = 10;
+
+const HIGH: &str = "high";
+const MEDIUM: &str = "medium";
+const LOW: &str = "low";
+
+fn flag(flag: &str, severity: &str, detail: String) -> RiskFlag {
+    RiskFlag { flag: flag.to_string(), severity: severity.to_string(), detail }
+}
+
+/// Contribution of a flag to the risk score.
+fn weight(severity: &str) -> u32 {
+    match severity {
+        HIGH => 40,
+        MEDIUM => 20,
+        _ => 5,
+    }
+}
+
+/// The oldest transaction in up to `AGE_PAGES` pages of `mint`'s history,
+/// and whether that reached its very first one. Paging stops early once it
+/// passes `NEW_TOKEN_DAYS`, as the token can no longer be new. None if the
+/// history can't be read.
+fn first_activity(state: &AppState, mint: &Pubkey) -> Option<(DateTime<Utc>, bool)> {
+    let cutoff = (Utc::now() - Duration::days(NEW_TOKEN_DAYS)).timestamp();
+    let mut before = None;
+    let mut oldest = None;
+    for _ in 0..AGE_PAGES {
+        let config = GetConfirmedSignaturesForAddress2Config {
+            before,
+            limit: Some(AGE_SIGNATURES),
+            ..GetConfirmedSignaturesForAddress2Config::default()
+        };
+        let signatures = state.solana_client.get_signatures_for_address_with_config(mint, config).ok()?;
+        if let Some(last) = signatures.last() {
+            oldest = last.block_time.or(oldest);
+        }
+        if signatures.len() < AGE_SIGNATURES {
+            return Some((DateTime::<Utc>::from_timestamp(oldest?, 0)?, true));
+        }
+        if oldest.is_some_and(|oldest| oldest < cutoff) {
+            break;
+        }
+        before = Some(signatures.last()?.signature.parse().ok()?);
+    }
+    Some((DateTime::<Utc>::from_timestamp(oldest?, 0)?, false))
+}
+
+/// The stored age of a listed token, when it is settled: its first
+/// transaction was found, or it was already too old to be new.
+async fn stored_age(db: &sqlx::PgPool, mint: &str) -> Option<(DateTime<Utc>, bool)> {
+    sqlx::query_as::<_, (DateTime<Utc>, bool)>(
+        "SELECT first_activity, first_activity_reached FROM tokens
+         WHERE mint = $1 AND first_activity IS NOT NULL
+           AND (first_activity_reached OR first_activity < NOW() - make_interval(days => $2))"
+    )
+    .bind(mint)
+    .bind(NEW_TOKEN_DAYS as i32)
+    .fetch_optional(db)
+    .await
+    .ok()
+    .flatten()
+}
+
+/// The token's age, from its listing row when settled and otherwise from
+/// its history, which is then stored.
+async fn age(state: &AppState, mint: &Pubkey) -> Option<(DateTime<Utc>, bool)> {
+    let key = mint.to_string();
+    if let Some(stored) = stored_age(&state.db, &key).await {
+        return Some(stored);
+    }
+    let (first, reached) = first_activity(state, mint)?;
+    if let Err(e) = sqlx::query("UPDATE tokens SET first_activity = $2, first_activity_reached = $3 WHERE mint = $1")
+        .bind(&key)
+        .bind(first)
+        .bind(reached)
+        .execute(&state.db)
+        .await
+    {
+        tracing::warn!("failed to store first activity of {}: {}", key, e);
+    }
+    Some((first, reached))
+}
+
+/// Folds `text` for look-alike comparison: NFKC, without invisible
+/// characters, lowercased, with Cyrillic and Greek homoglyphs and the
+/// digits and letters commonly swapped for each other mapped to one Latin
+/// letter.
+fn fold(text: &str) -> String {
+    text.trim()
+        .nfkc()
+        .filter(|c| !matches!(c, '\u{200b}'..='\u{200f}' | '\u{2060}' | '\u{feff}' | '\u{00ad}'))
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'а' | 'α' => 'a',
+            'в' | 'β' => 'b',
+            'с' | 'ϲ' => 'c',
+            'ԁ' => 'd',
+            'е' | 'ё' | 'ε' => 'e',
+            'н' | 'η' => 'h',
+            'і' | 'ї' | 'ι' | 'l' | '1' | '|' => 'i',
+            'ј' => 'j',
+            'к' | 'κ' => 'k',
+            'м' | 'μ' => 'm',
+            'ν' => 'n',
+            'о' | 'ο' | '0' => 'o',
+            'р' | 'ρ' => 'p',
+            'ѕ' | '5' => 's',
+            'т' | 'τ' => 't',
+            'у' | 'υ' => 'y',
+            'х' | 'χ' => 'x',
+            'ζ' => 'z',
+            c => c,
+        })
+        .filter(|c| !c.is_whitespace())
+        .collect()
+}
+
+/// A verified token other than `mint` whose symbol or name looks the same
+/// as this one's, with which of the two matched.
+async fn verified_namesake(
+    db: &sqlx::PgPool,
+    mint: &str,
+    symbol: Option<&str>,
+    name: Option<&str>,
+) -> Option<(String, &'static str)> {
+    let symbol = symbol.map(fold).filter(|s| !s.is_empty());
+    let name = name.map(fold).filter(|s| !s.is_empty());
+    if symbol.is_none() && name.is_none() {
+        return None;
+    }
+    let verified = sqlx::query_as::<_, (String, Option<String>, Option<String>)>(
+        "SELECT mint, symbol, name FROM tokens
+         WHERE verified AND mint <> $1
+         ORDER BY market_cap DESC NULLS LAST"
+    )
+    .bind(mint)
+    .fetch_all(db)
+    .await
+    .ok()?;
+
+    verified.into_iter().find_map(|(original, their_symbol, their_name)| {
+        if symbol.is_some() && their_symbol.as_deref().map(fold) == symbol {
+            Some((original, "symbol"))
+        } else if name.is_some() && their_name.as_deref().map(fold) == name {
+            Some((original, "name"))
+        } else {
+            None
+        }
+    })
+}
+
+/// Checks `mint` and stores the flags on its listing row. None if `mint`
+/// isn't a token mint.
+pub async fn report(state: &AppState, mint: &Pubkey) -> Option<TokenRisk> {
+    let key = format!("token-risk:{}", mint);
+    if let Some(cached) = state.cache.get::<TokenRisk>(&key).await {
+        return Some(cached);
+    }
+
+    let token = token_metadata::fetch(state, mint).await?;
+    let (verified, liquidity) = sqlx::query_as::<_, (bool, f64)>(
+        "SELECT verified, liquidity FROM tokens WHERE mint = $1"
+    )
+    .bind(&token.mint)
+    .fetch_optional(&state.db)
+    .await
+    .ok()
+    .flatten()
+    .unwrap_or((false, 0.0));
+
+    let mut flags = Vec::new();
+    if let Some(authority) = &token.mint_authority {
+        flags.push(flag("mint_authority", HIGH, format!("{} can mint more supply", authority)));
+    }
+    if let Some(authority) = &token.freeze_authority {
+        flags.push(flag("freeze_authority", HIGH, format!("{} can freeze holders' accounts", authority)));
+    }
+    if token.metadata_mutable == Some(true) {
+        flags.push(flag("mutable_metadata", LOW, "name, symbol and uri can still be changed".to_string()));
+    }
+    let risky: Vec<&str> = token.extensions
+        .iter()
+        .filter(|extension| extension.risky)
+        .map(|extension| extension.extension.as_str())
+        .collect();
+    if !risky.is_empty() {
+        flags.push(flag("risky_extensions", HIGH, format!("Token-2022 extensions: {}", risky.join(", "))));
+    }
+
+    let top_holders_share = token_holders::top_holders(state, mint)
+        .await
+        .map(|holders| holders.holders.iter().take(TOP_HOLDERS).map(|holder| holder.share).sum::<f64>());
+    if let Some(share) = top_holders_share.filter(|share| *share > MAX_TOP_HOLDERS_SHARE) {
+        flags.push(flag(
+            "holder_concentration",
+            MEDIUM,
+            format!("top {} accounts hold {:.0}% of supply", TOP_HOLDERS, share * 100.0),
+        ));
+    }
+    if liquidity < MIN_LIQUIDITY {
+        flags.push(flag("low_liquidity", MEDIUM, format!("${:.0} of pool liquidity", liquidity)));
+    }
+
+    let first = age(state, mint).await;
+    if let Some((first, true)) = first {
+        if first > Utc::now() - Duration::days(NEW_TOKEN_DAYS) {
+            flags.push(flag("new_token", MEDIUM, format!("first transaction at {}", first.to_rfc3339())));
+        }
+    }
+
+    if !verified {
+        let (symbol, name) = (token.symbol.as_deref(), token.name.as_deref());
+        if let Some((original, field)) = verified_namesake(&state.db, &token.mint, symbol, name).await {
+            let value = if field == "symbol" { symbol } else { name };
+            flags.push(flag(
+                "impersonates_verified",
+                HIGH,
+                format!("uses the {} {} of verified token {}", field, value.unwrap_or_default(), original),
+            ));
+        }
+    }
+
+    let score = flags.iter().map(|f| weight(&f.severity)).sum::<u32>().min(100) as u8;
+    let names: Vec<String> = flags.iter().map(|f| f.flag.clone()).collect();
+    if let Err(e) = sqlx::query(
+        "UPDATE tokens SET risk_flags = $2, risk_score = $3, risk_checked_at = NOW() WHERE mint = $1"
+    )
+    .bind(&token.mint)
+    .bind(&names)
+    .bind(score as i16)
+    .execute(&state.db)
+    .await
+    {
+        tracing::warn!("failed to store risk flags of {}: {}", token.mint, e);
+    }
+
+    let result = TokenRisk {
+        mint: token.mint,
+        verified,
+        score,
+        flags,
+        top_holders_share,
+        liquidity,
+        first_activity: first.map(|(first, _)| first),
+        checked_at: Utc::now(),
+    };
+    state.cache.put(&key, &result, cache::TOKEN).await;
+    Some(result)
+}
+
+/// Starts the background task that re-checks every listed token every
+/// `TOKEN_RISK_INTERVAL_SECS` (default 3600).
+pub fn spawn_scorer(state: AppState) {
+    let every = std::env::var("TOKEN_RISK_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(3600);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(every));
+        loop {
+            ticker.tick().await;
+            let mints: Vec<String> = sqlx::query_scalar("SELECT mint FROM tokens ORDER BY volume_24h DESC")
+                .fetch_all(&state.db)
+                .await
+                .unwrap_or_default();
+
+            for mint in mints.iter().filter_map(|mint| Pubkey::from_str(mint).ok()) {
+                report(&state, &mint).await;
+            }
+        }
+    });
+}
@@ -0,0 +1,292 @@
+// DEX trade index: records every swap through an indexed market in the
+// blocks the block follower hands it, read from the pool's vault balance changes in
+// transactions whose logs show the DEX program running, and rolls the last
+// 24 hours of trades up into each market's volume and price change.
+use chrono::{DateTime, Utc};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedTransactionWithStatusMeta, UiConfirmedBlock,
+};
+use std::{collections::HashMap, time::Duration};
+use crate::{
+    AppState,
+    models::Trade,
+    services::{pools, solana},
+};
+
+/// An indexed market, as needed to spot swaps through it.
+#[derive(Debug, Clone)]
+pub struct Venue {
+    pub address: String,
+    pub dex: String,
+    pub base_mint: String,
+    pub quote_mint: String,
+    pub base_vault: String,
+    pub quote_vault: String,
+}
+
+/// A swap read from a transaction, before it is stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewTrade {
+    pub market: String,
+    pub side: &'static str,
+    pub trader: String,
+    pub base_amount: f64,
+    pub quote_amount: f64,
+    pub price: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct TradeFilter {
+    pub trader: Option<String>,
+    /// "buy" or "sell".
+    pub side: Option<String>,
+}
+
+/// UI balance change of each token account in `tx`, by address. Accounts
+/// opened or closed by the transaction count from or to zero.
+fn balance_changes(tx: &EncodedTransactionWithStatusMeta) -> HashMap<String, f64> {
+    let keys = solana::account_keys(&tx.transaction);
+    let Some(meta) = tx.meta.as_ref() else { return HashMap::new() };
+
+    let mut raw: HashMap<String, (i128, u8)> = HashMap::new();
+    for (balances, sign) in [(&meta.pre_token_balances, -1), (&meta.post_token_balances, 1)] {
+        let OptionSerializer::Some(balances) = balances else { continue };
+        for balance in balances {
+            let Some(address) = keys.get(balance.account_index as usize) else { continue };
+            let Ok(amount) = balance.ui_token_amount.amount.parse::<u64>() else { continue };
+            let entry = raw.entry(address.clone()).or_insert((0, balance.ui_token_amount.decimals));
+            entry.0 += sign * amount as i128;
+        }
+    }
+    raw.into_iter()
+        .map(|(address, (change, decimals))| (address, change as f64 / 10f64.powi(decimals as i32)))
+        .collect()
+}
+
+/// Swaps made by `tx` through any of `venues` (keyed by pool address). A pool
+/// counts as traded when its DEX program was invoked and its two vaults moved
+/// in opposite directions; several swaps through one pool net out. Failed
+/// transactions yield nothing.
+pub fn extract(tx: &EncodedTransactionWithStatusMeta, venues: &HashMap<String, Venue>) -> Vec<NewTrade> {
+    let Some(meta) = tx.meta.as_ref().filter(|meta| meta.err.is_none()) else { return vec![] };
+    let OptionSerializer::Some(logs) = &meta.log_messages else { return vec![] };
+    let keys = solana::account_keys(&tx.transaction);
+    let Some(trader) = keys.first() else { return vec![] };
+    let changes = balance_changes(tx);
+
+    keys.iter()
+        .filter_map(|key| venues.get(key))
+        .filter_map(|venue| {
+            let dex = pools::DEXES.iter().find(|dex| dex.name == venue.dex)?;
+            let invoke = format!("Program {} invoke", dex.program);
+            if !logs.iter().any(|log| log.starts_with(&invoke)) {
+                return None;
+            }
+            let base = changes.get(&venue.base_vault).copied().unwrap_or(0.0);
+            let quote = changes.get(&venue.quote_vault).copied().unwrap_or(0.0);
+            if base == 0.0 || quote == 0.0 || base.signum() == quote.signum() {
+                return None;
+            }
+
+            Some(NewTrade {
+                market: venue.address.clone(),
+                // The pool paying out base means the trader bought it.
+                side: if base < 0.0 { "buy" } else { "sell" },
+                trader: trader.clone(),
+                base_amount: base.abs(),
+                quote_amount: quote.abs(),
+                price: quote.abs() / base.abs(),
+            })
+        })
+        .collect()
+}
+
+/// Records the swaps in `block`, produced at `slot`, and returns how many
+/// there were. Re-indexing a block is a no-op.
+pub async fn index(db: &sqlx::PgPool, slot: u64, block: &UiConfirmedBlock) -> Result<usize, sqlx::Error> {
+    let block_time = block.block_time.and_then(|t| DateTime::<Utc>::from_timestamp(t, 0));
+    let transactions = block.transactions.as_deref().unwrap_or_default();
+
+    let mut keys: Vec<String> = transactions.iter().flat_map(|tx| solana::account_keys(&tx.transaction)).collect();
+    keys.sort();
+    keys.dedup();
+    let venues: HashMap<String, Venue> = sqlx::query_as::<_, (String, String, String, String, String, String)>(
+        "SELECT address, dex, base_mint, quote_mint, base_vault, quote_vault
+         FROM markets
+         WHERE address = ANY($1) AND base_vault IS NOT NULL AND quote_vault IS NOT NULL"
+    )
+    .bind(&keys)
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .map(|(address, dex, base_mint, quote_mint, base_vault, quote_vault)| {
+        (address.clone(), Venue { address, dex, base_mint, quote_mint, base_vault, quote_vault })
+    })
+    .collect();
+    if venues.is_empty() {
+        return Ok(0);
+    }
+
+    let mints: Vec<String> = venues.values().flat_map(|v| [v.base_mint.clone(), v.quote_mint.clone()]).collect();
+    let usd: HashMap<String, f64> = sqlx::query_as::<_, (String, f64)>(
+        "SELECT mint, price FROM tokens WHERE mint = ANY($1) AND price IS NOT NULL"
+    )
+    .bind(&mints)
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .collect();
+
+    let mut recorded = 0;
+    for tx in transactions {
+        let trades = extract(tx, &venues);
+        if trades.is_empty() {
+            continue;
+        }
+        let Some(signature) = solana::signature(&tx.transaction) else { continue };
+        for trade in &trades {
+            let venue = &venues[&trade.market];
+            let volume_usd = usd
+                .get(&venue.quote_mint)
+                .map(|price| trade.quote_amount * price)
+                .or_else(|| usd.get(&venue.base_mint).map(|price| trade.base_amount * price));
+
+            sqlx::query(
+                "INSERT INTO trades
+                    (signature, market, slot, block_time, side, trader, base_amount, quote_amount, price, volume_usd)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                 ON CONFLICT (signature, market) DO NOTHING"
+            )
+            .bind(&signature)
+            .bind(&trade.market)
+            .bind(slot as i64)
+            .bind(block_time)
+            .bind(trade.side)
+            .bind(&trade.trader)
+            .bind(trade.base_amount)
+            .bind(trade.quote_amount)
+            .bind(trade.price)
+            .bind(volume_usd)
+            .execute(db)
+            .await?;
+            recorded += 1;
+        }
+    }
+    Ok(recorded)
+}
+
+/// Sets each market's 24h USD volume and its price change against the first
+/// trade of the last 24 hours, then each traded token's 24h volume as the sum
+/// over the markets it is on. Markets without trades in that window get no
+/// volume and no price change.
+pub async fn update_stats(db: &sqlx::PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "WITH day AS (
+             SELECT market,
+                    SUM(volume_usd) AS volume,
+                    (ARRAY_AGG(price ORDER BY slot, signature))[1] AS open
+             FROM trades
+             WHERE block_time >= NOW() - INTERVAL '24 hours'
+             GROUP BY market
+         )
+         UPDATE markets m
+         SET volume_24h = COALESCE(day.volume, 0),
+             price_change_24h = CASE WHEN day.open > 0 THEN (m.price - day.open) / day.open * 100 END
+         FROM markets all_markets
+         LEFT JOIN day ON day.market = all_markets.address
+         WHERE m.address = all_markets.address"
+    )
+    .execute(db)
+    .await?;
+
+    sqlx::query(
+        "UPDATE tokens t
+         SET volume_24h = sides.volume, updated_at = NOW()
+         FROM (
+             SELECT mint, SUM(volume_24h) AS volume
+             FROM (
+                 SELECT base_mint AS mint, volume_24h FROM markets
+                 UNION ALL
+                 SELECT quote_mint AS mint, volume_24h FROM markets
+             ) by_side
+             GROUP BY mint
+         ) sides
+         WHERE t.mint = sides.mint AND t.volume_24h IS DISTINCT FROM sides.volume"
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Starts the background task that refreshes market stats every
+/// `MARKET_STATS_INTERVAL_SECS` (default 60).
+pub fn spawn_stats(state: AppState) {
+    let every = std::env::var("MARKET_STATS_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(every));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = update_stats(&state.db).await {
+                tracing::warn!("failed to update market stats: {}", e);
+            }
+        }
+    });
+}
+
+/// Trades with side, amounts and price seen from `base_mint`, whichever
+/// side of its market that is.
+const ORIENTED_TRADES: &str =
+    "SELECT t.signature, t.market, t.slot, t.block_time,
+            CASE WHEN m.base_mint = $2 THEN t.side WHEN t.side = 'buy' THEN 'sell' ELSE 'buy' END AS side,
+            t.trader,
+            CASE WHEN m.base_mint = $2 THEN t.base_amount ELSE t.quote_amount END AS base_amount,
+            CASE WHEN m.base_mint = $2 THEN t.quote_amount ELSE t.base_amount END AS quote_amount,
+            CASE WHEN m.base_mint = $2 THEN t.price ELSE 1 / t.price END AS price,
+            t.volume_usd
+     FROM trades t
+     JOIN markets m ON m.address = t.market
+     WHERE t.market = ANY($1)";
+
+/// One page of the trades through any of `markets`, newest first, priced in
+/// the other side of `base_mint`, and the number matching `filter`.
+pub async fn list(
+    db: &sqlx::PgPool,
+    markets: &[String],
+    base_mint: &str,
+    filter: &TradeFilter,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<Trade>, i64), sqlx::Error> {
+    let filters = "($3::text IS NULL OR trader = $3)
+         AND ($4::text IS NULL OR side = $4)";
+
+    let trades = sqlx::query_as::<_, Trade>(&format!(
+        "SELECT * FROM ({}) t
+         WHERE {}
+         ORDER BY slot DESC, signature, market
+         LIMIT $5 OFFSET $6",
+        ORIENTED_TRADES, filters
+    ))
+    .bind(markets)
+    .bind(base_mint)
+    .bind(&filter.trader)
+    .bind(&filter.side)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(db)
+    .await?;
+
+    let (total,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM ({}) t WHERE {}", ORIENTED_TRADES, filters))
+        .bind(markets)
+        .bind(base_mint)
+        .bind(&filter.trader)
+        .bind(&filter.side)
+        .fetch_one(db)
+        .await?;
+
+    Ok((trades, total))
+}
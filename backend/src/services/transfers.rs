@@ -0,0 +1,246 @@
+// Token transfer index: records every SPL Token / Token-2022 transfer, mint
+// and burn in the blocks the block follower hands it, including ones made by
+// inner instructions.
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedTransaction, EncodedTransactionWithStatusMeta,
+    UiConfirmedBlock, UiInstruction, UiMessage, UiParsedInstruction,
+};
+use std::collections::HashMap;
+use crate::{models::TokenTransfer, services::solana};
+
+/// A transfer read from a transaction, before it is stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewTransfer {
+    pub instruction_index: i16,
+    pub inner_index: i16,
+    pub kind: &'static str,
+    pub mint: String,
+    pub program: String,
+    pub from_account: Option<String>,
+    pub from_owner: Option<String>,
+    pub to_account: Option<String>,
+    pub to_owner: Option<String>,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+#[derive(Debug, Default)]
+pub struct TransferFilter {
+    pub from_owner: Option<String>,
+    pub to_owner: Option<String>,
+    pub min_amount: Option<f64>,
+    pub max_amount: Option<f64>,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// Mint, owner and decimals of each token account in `tx`, by address, from
+/// its pre- and post-transaction token balances.
+fn token_accounts(tx: &EncodedTransactionWithStatusMeta) -> HashMap<String, (String, Option<String>, u8)> {
+    let keys = solana::account_keys(&tx.transaction);
+    let Some(meta) = tx.meta.as_ref() else { return HashMap::new() };
+
+    let mut accounts = HashMap::new();
+    for balances in [&meta.pre_token_balances, &meta.post_token_balances] {
+        let OptionSerializer::Some(balances) = balances else { continue };
+        for balance in balances {
+            let Some(address) = keys.get(balance.account_index as usize) else { continue };
+            let owner = Option::<String>::from(balance.owner.clone());
+            accounts.insert(address.clone(), (balance.mint.clone(), owner, balance.ui_token_amount.decimals));
+        }
+    }
+    accounts
+}
+
+/// Parsed token instructions of `tx` as `(instruction index, inner index,
+/// program id, parsed)`, with top-level instructions at inner index -1.
+fn token_instructions(tx: &EncodedTransactionWithStatusMeta) -> Vec<(i16, i16, String, Value)> {
+    let token_programs = [solana::TOKEN_PROGRAM_ID.to_string(), solana::TOKEN_2022_PROGRAM_ID.to_string()];
+    let parsed = |ix: &UiInstruction| match ix {
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(ix)) if token_programs.contains(&ix.program_id) => {
+            Some((ix.program_id.clone(), ix.parsed.clone()))
+        }
+        _ => None,
+    };
+
+    let mut found = Vec::new();
+    if let EncodedTransaction::Json(ui) = &tx.transaction {
+        if let UiMessage::Parsed(message) = &ui.message {
+            for (i, ix) in message.instructions.iter().enumerate() {
+                if let Some((program, ix)) = parsed(ix) {
+                    found.push((i as i16, -1, program, ix));
+                }
+            }
+        }
+    }
+    if let Some(OptionSerializer::Some(inner)) = tx.meta.as_ref().map(|m| &m.inner_instructions) {
+        for group in inner {
+            for (j, ix) in group.instructions.iter().enumerate() {
+                if let Some((program, ix)) = parsed(ix) {
+                    found.push((group.index as i16, j as i16, program, ix));
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Transfers, mints and burns made by `tx`. Failed transactions move no
+/// tokens and yield nothing.
+pub fn extract(tx: &EncodedTransactionWithStatusMeta) -> Vec<NewTransfer> {
+    if tx.meta.as_ref().map_or(true, |meta| meta.err.is_some()) {
+        return vec![];
+    }
+    let accounts = token_accounts(tx);
+    let str_field = |info: &Value, field: &str| info[field].as_str().map(str::to_string);
+
+    token_instructions(tx)
+        .into_iter()
+        .filter_map(|(instruction_index, inner_index, program, parsed)| {
+            let info = &parsed["info"];
+            let (kind, from_account, to_account) = match parsed["type"].as_str()? {
+                "transfer" | "transferChecked" => ("transfer", str_field(info, "source"), str_field(info, "destination")),
+                "mintTo" | "mintToChecked" => ("mint", None, str_field(info, "account")),
+                "burn" | "burnChecked" => ("burn", str_field(info, "account"), None),
+                _ => return None,
+            };
+            // Checked variants carry the amount as a token amount.
+            let amount = info["amount"]
+                .as_str()
+                .or_else(|| info["tokenAmount"]["amount"].as_str())?
+                .parse()
+                .ok()?;
+
+            let known = |account: &Option<String>| account.as_ref().and_then(|a| accounts.get(a));
+            let balance = known(&from_account).or_else(|| known(&to_account));
+            let mint = str_field(info, "mint").or_else(|| balance.map(|(mint, _, _)| mint.clone()))?;
+            let decimals = info["tokenAmount"]["decimals"]
+                .as_u64()
+                .map(|d| d as u8)
+                .or_else(|| balance.map(|(_, _, decimals)| *decimals))
+                .unwrap_or(0);
+
+            Some(NewTransfer {
+                instruction_index,
+                inner_index,
+                kind,
+                from_owner: known(&from_account).and_then(|(_, owner, _)| owner.clone()),
+                to_owner: known(&to_account).and_then(|(_, owner, _)| owner.clone()),
+                mint,
+                program,
+                from_account,
+                to_account,
+                amount,
+                decimals,
+            })
+        })
+        .collect()
+}
+
+async fn store(
+    db: &sqlx::PgPool,
+    signature: &str,
+    slot: u64,
+    block_time: Option<DateTime<Utc>>,
+    transfer: &NewTransfer,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO token_transfers
+            (signature, instruction_index, inner_index, slot, block_time, kind, mint, program,
+             from_account, from_owner, to_account, to_owner, amount, decimals, ui_amount)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13::numeric, $14, $15)
+         ON CONFLICT (signature, instruction_index, inner_index) DO NOTHING"
+    )
+    .bind(signature)
+    .bind(transfer.instruction_index)
+    .bind(transfer.inner_index)
+    .bind(slot as i64)
+    .bind(block_time)
+    .bind(transfer.kind)
+    .bind(&transfer.mint)
+    .bind(&transfer.program)
+    .bind(&transfer.from_account)
+    .bind(&transfer.from_owner)
+    .bind(&transfer.to_account)
+    .bind(&transfer.to_owner)
+    .bind(transfer.amount.to_string())
+    .bind(transfer.decimals as i16)
+    .bind(transfer.amount as f64 / 10f64.powi(transfer.decimals as i32))
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Records the token movements in `block`, produced at `slot`, and returns
+/// how many there were. Re-indexing a block is a no-op.
+pub async fn index(db: &sqlx::PgPool, slot: u64, block: &UiConfirmedBlock) -> Result<usize, sqlx::Error> {
+    let block_time = block.block_time.and_then(|t| DateTime::<Utc>::from_timestamp(t, 0));
+
+    let mut recorded = 0;
+    for tx in block.transactions.iter().flatten() {
+        let transfers = extract(tx);
+        if transfers.is_empty() {
+            continue;
+        }
+        let Some(signature) = solana::signature(&tx.transaction) else { continue };
+        for transfer in &transfers {
+            store(db, &signature, slot, block_time, transfer).await?;
+            recorded += 1;
+        }
+    }
+    Ok(recorded)
+}
+
+/// One page of `mint`'s transfers, newest first, and the number matching
+/// `filter`. Amount bounds are in UI units.
+pub async fn list(
+    db: &sqlx::PgPool,
+    mint: &str,
+    filter: &TransferFilter,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<TokenTransfer>, i64), sqlx::Error> {
+    let filters = "mint = $1
+         AND ($2::text IS NULL OR from_owner = $2)
+         AND ($3::text IS NULL OR to_owner = $3)
+         AND ($4::float8 IS NULL OR ui_amount >= $4)
+         AND ($5::float8 IS NULL OR ui_amount <= $5)
+         AND ($6::timestamptz IS NULL OR block_time >= $6)
+         AND ($7::timestamptz IS NULL OR block_time <= $7)";
+
+    let transfers = sqlx::query_as::<_, TokenTransfer>(&format!(
+        "SELECT signature, instruction_index, inner_index, slot, block_time, kind, mint,
+                from_account, from_owner, to_account, to_owner, amount::text AS amount, decimals, ui_amount
+         FROM token_transfers
+         WHERE {}
+         ORDER BY slot DESC, signature, instruction_index, inner_index
+         LIMIT $8 OFFSET $9",
+        filters
+    ))
+    .bind(mint)
+    .bind(&filter.from_owner)
+    .bind(&filter.to_owner)
+    .bind(filter.min_amount)
+    .bind(filter.max_amount)
+    .bind(filter.start)
+    .bind(filter.end)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(db)
+    .await?;
+
+    let (total,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM token_transfers WHERE {}", filters))
+        .bind(mint)
+        .bind(&filter.from_owner)
+        .bind(&filter.to_owner)
+        .bind(filter.min_amount)
+        .bind(filter.max_amount)
+        .bind(filter.start)
+        .bind(filter.end)
+        .fetch_one(db)
+        .await?;
+
+    Ok((transfers, total))
+}
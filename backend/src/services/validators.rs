@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use crate::{AppState, models::Validator};
+
+/// Builds the validator directory from the current vote accounts, enriched
+/// with gossip versions and this epoch's block production.
+pub fn directory(state: &AppState) -> Option<Vec<Validator>> {
+    let vote_accounts = state.solana_client.get_vote_accounts().ok()?;
+
+    let versions: HashMap<String, Option<String>> = state.solana_client
+        .get_cluster_nodes()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|node| (node.pubkey, node.version))
+        .collect();
+    let production = state.solana_client
+        .get_block_production()
+        .map(|response| response.value.by_identity)
+        .unwrap_or_default();
+
+    let total_stake: u64 = vote_accounts.current.iter()
+        .chain(vote_accounts.delinquent.iter())
+        .map(|account| account.activated_stake)
+        .sum();
+
+    let current = vote_accounts.current.into_iter().map(|account| (account, false));
+    let delinquent = vote_accounts.delinquent.into_iter().map(|account| (account, true));
+
+    Some(current.chain(delinquent).map(|(account, delinquent)| {
+        let (leader_slots, blocks_produced) = production
+            .get(&account.node_pubkey)
+            .copied()
+            .unwrap_or((0, 0));
+
+        Validator {
+            version: versions.get(&account.node_pubkey).cloned().flatten(),
+            vote_pubkey: account.vote_pubkey,
+            identity: account.node_pubkey,
+            commission: account.commission,
+            activated_stake: account.activated_stake as f64 / 1_000_000_000.0,
+            stake_share: if total_stake > 0 {
+                account.activated_stake as f64 / total_stake as f64 * 100.0
+            } else {
+                0.0
+            },
+            last_vote: account.last_vote as i64,
+            root_slot: account.root_slot as i64,
+            leader_slots: leader_slots as i64,
+            blocks_produced: blocks_produced as i64,
+            skip_rate: (leader_slots > 0)
+                .then(|| leader_slots.saturating_sub(blocks_produced) as f64 / leader_slots as f64 * 100.0),
+            delinquent,
+        }
+    }).collect())
+}
@@ -0,0 +1,90 @@
+mod support;
+
+use serde_json::json;
+use support::TestApp;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn auth_routes_respond() {
+    let app = TestApp::spawn().await;
+
+    let registered = app
+        .post("/api/auth/register", json!({ "email": "a@example.com", "password": "hunter22" }))
+        .await;
+    assert!(registered["token"].is_string());
+
+    let logged_in = app
+        .post("/api/auth/login", json!({ "email": "a@example.com", "password": "hunter22" }))
+        .await;
+    assert!(logged_in["user"]["id"].is_string());
+
+    let logged_out = app.post("/api/auth/logout", json!({})).await;
+    assert!(logged_out["message"].is_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn user_profile_routes_respond() {
+    let app = TestApp::spawn().await;
+
+    assert!(app.get("/api/user/profile").await["email"].is_string());
+    assert!(app.put("/api/user/profile", json!({ "username": "x" })).await["id"].is_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_key_routes_respond() {
+    let app = TestApp::spawn().await;
+
+    assert!(app.get("/api/user/api-keys").await.is_array());
+    let key = app
+        .post("/api/user/api-keys", json!({ "name": "ci", "plan": "free" }))
+        .await;
+    assert!(key["key"].as_str().unwrap().starts_with("sk_live_"));
+    assert!(app.delete(&format!("/api/user/api-keys/{}", key["id"].as_str().unwrap())).await["message"].is_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn watchlist_routes_respond() {
+    let app = TestApp::spawn().await;
+
+    assert!(app.get("/api/user/watchlist").await.is_array());
+    assert!(app
+        .post("/api/user/watchlist", json!({ "type": "address", "address": support::WALLET }))
+        .await["message"]
+        .is_string());
+    assert!(app.delete("/api/user/watchlist/1").await["message"].is_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn admin_user_routes_respond() {
+    let app = TestApp::spawn().await;
+
+    let users = app.get("/api/admin/users?limit=5").await;
+    assert!(users["users"].is_array());
+    assert_eq!(users["limit"], 5);
+    assert!(app.put("/api/admin/users/1", json!({ "role": "admin" })).await["message"].is_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn admin_ad_routes_round_trip_through_postgres() {
+    let app = TestApp::spawn().await;
+
+    let ad = app
+        .post("/api/admin/ads", json!({
+            "slot": "sidebar",
+            "image_url": "https://example.com/ad.png",
+            "link_url": "https://example.com",
+        }))
+        .await;
+    let id = ad["id"].as_str().unwrap().to_string();
+
+    let ads = app.get("/api/admin/ads").await;
+    assert!(ads.as_array().unwrap().iter().any(|a| a["id"] == id.as_str()));
+
+    assert!(app.put(&format!("/api/admin/ads/{}", id), json!({ "active": false })).await["message"].is_string());
+    assert!(app.delete(&format!("/api/admin/ads/{}", id)).await["message"].is_string());
+
+    sqlx::query("DELETE FROM ads WHERE id = $1::uuid")
+        .bind(&id)
+        .execute(&app.db)
+        .await
+        .unwrap();
+}
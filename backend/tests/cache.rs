@@ -0,0 +1,106 @@
+mod support;
+
+use std::time::Duration;
+use support::{TestApp, SLOT, WALLET};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn finalized_blocks_are_served_from_cache() {
+    let app = TestApp::spawn().await;
+    let path = format!("/api/blocks/{}", SLOT);
+
+    let first = app.get(&path).await;
+    let second = app.get(&path).await;
+
+    assert_eq!(first, second);
+    assert_eq!(app.rpc.calls("getBlock"), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_misses_are_coalesced_into_one_rpc_call() {
+    let app = TestApp::spawn().await;
+    app.rpc.set_delay(Duration::from_millis(200));
+    let url = format!("{}/api/blocks/{}", app.address, SLOT);
+
+    let requests = (0..5).map(|_| {
+        let client = app.client.clone();
+        let url = url.clone();
+        tokio::spawn(async move { client.get(url).send().await.unwrap().status() })
+    });
+    for request in requests.collect::<Vec<_>>() {
+        assert!(request.await.unwrap().is_success());
+    }
+
+    assert_eq!(app.rpc.calls("getBlock"), 1);
+    let token = app.sign_up_admin().await;
+    let stats = app.get_as(&token, "/api/admin/cache/stats").await;
+    assert_eq!(stats["namespaces"]["block"]["misses"], 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn balances_are_cached_briefly() {
+    let app = TestApp::spawn().await;
+    let path = format!("/api/addresses/{}", WALLET);
+
+    app.get(&path).await;
+    app.get(&path).await;
+    assert_eq!(app.rpc.calls("getBalance"), 1);
+
+    tokio::time::sleep(Duration::from_secs(6)).await;
+    app.get(&path).await;
+    assert_eq!(app.rpc.calls("getBalance"), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stats_report_hits_per_namespace() {
+    let app = TestApp::spawn().await;
+
+    app.get("/api/network/stats").await;
+    app.get("/api/network/stats").await;
+
+    let token = app.sign_up_admin().await;
+    let stats = app.get_as(&token, "/api/admin/cache/stats").await;
+    assert_eq!(stats["namespaces"]["network"]["misses"], 1);
+    assert_eq!(stats["namespaces"]["network"]["hits"], 1);
+    assert_eq!(stats["hit_rate"], 0.5);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn purge_removes_matching_keys() {
+    let app = TestApp::spawn().await;
+    app.get(&format!("/api/blocks/{}", SLOT)).await;
+    app.get(&format!("/api/addresses/{}", WALLET)).await;
+
+    let token = app.sign_up_admin().await;
+    let purged = app.delete_as(&token, "/api/admin/cache?pattern=block:*").await;
+    assert_eq!(purged["deleted"], 1);
+
+    app.get(&format!("/api/blocks/{}", SLOT)).await;
+    app.get(&format!("/api/addresses/{}", WALLET)).await;
+    assert_eq!(app.rpc.calls("getBlock"), 2);
+    assert_eq!(app.rpc.calls("getBalance"), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn network_stats_are_not_cached_when_the_rpc_fails() {
+    let app = TestApp::spawn().await;
+    app.rpc.set("getEpochInfo", serde_json::Value::Null);
+
+    let body = app.get("/api/network/stats").await;
+    app.get("/api/network/stats").await;
+
+    assert_eq!(body["slot"], 0);
+    assert_eq!(app.rpc.calls("getEpochInfo"), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cache_admin_requires_an_admin() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.sign_up().await;
+    let purge = format!("{}/api/admin/cache?pattern=*", app.address);
+    let stats = format!("{}/api/admin/cache/stats", app.address);
+
+    assert_eq!(app.client.delete(&purge).send().await.unwrap().status(), 401);
+    assert_eq!(app.client.delete(&purge).bearer_auth(&token).send().await.unwrap().status(), 403);
+    assert_eq!(app.client.get(&stats).send().await.unwrap().status(), 401);
+    assert_eq!(app.client.get(&stats).bearer_auth(&token).send().await.unwrap().status(), 403);
+}
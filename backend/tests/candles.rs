@@ -0,0 +1,211 @@
+mod support;
+
+use serde_json::Value;
+use solana_explorer_backend::services::{candles, prices};
+use solana_sdk::{signature::Keypair, signer::Signer};
+use support::{random_mint, TestApp};
+
+/// 2024-01-01T00:00:00Z, a minute and hour boundary.
+const START: i64 = 1_704_067_200;
+
+async fn tick(app: &TestApp, mint: &str, at: i64, price: f64, volume: f64) {
+    sqlx::query("INSERT INTO token_price_history (mint, price, volume, recorded_at) VALUES ($1, $2, $3, to_timestamp($4))")
+        .bind(mint)
+        .bind(price)
+        .bind(volume)
+        .bind(at as f64)
+        .execute(&app.db)
+        .await
+        .unwrap();
+}
+
+/// Ticks in the first and third minute after START; the second has none.
+async fn seed(app: &TestApp, mint: &str) {
+    tick(app, mint, START + 10, 10.0, 1.0).await;
+    tick(app, mint, START + 40, 12.0, 2.0).await;
+    tick(app, mint, START + 50, 9.0, 0.0).await;
+    tick(app, mint, START + 150, 11.0, 5.0).await;
+}
+
+fn ohlcv(candle: &Value) -> [f64; 5] {
+    ["open", "high", "low", "close", "volume"].map(|field| candle[field].as_f64().unwrap())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn candles_aggregate_ticks_and_fill_gaps() {
+    let app = TestApp::spawn().await;
+    let mint = random_mint();
+    seed(&app, &mint).await;
+
+    let body = app
+        .get(&format!("/api/tokens/{}/candles?interval=1m&from={}&to={}", mint, START, START + 120))
+        .await;
+
+    assert_eq!(body["from"], START);
+    assert_eq!(body["to"], START + 120);
+    let candles = body["candles"].as_array().unwrap();
+    assert_eq!(candles.len(), 3);
+    assert_eq!(candles[0]["bucket"], "2024-01-01T00:00:00Z");
+    assert_eq!(ohlcv(&candles[0]), [10.0, 12.0, 9.0, 9.0, 3.0]);
+    // The empty minute carries the previous close.
+    assert_eq!(ohlcv(&candles[1]), [9.0, 9.0, 9.0, 9.0, 0.0]);
+    assert_eq!(ohlcv(&candles[2]), [11.0, 11.0, 11.0, 11.0, 5.0]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn leading_gaps_use_the_last_earlier_tick() {
+    let app = TestApp::spawn().await;
+    let mint = random_mint();
+    seed(&app, &mint).await;
+
+    let before = app
+        .get(&format!("/api/tokens/{}/candles?interval=1m&from={}&to={}", mint, START - 120, START))
+        .await;
+    // Nothing to carry forward before the first tick.
+    assert_eq!(before["candles"].as_array().unwrap().len(), 1);
+
+    let body = app
+        .get(&format!("/api/tokens/{}/candles?interval=1m&from={}&to={}", mint, START + 60, START + 60))
+        .await;
+    assert_eq!(ohlcv(&body["candles"][0]), [9.0, 9.0, 9.0, 9.0, 0.0]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn wider_intervals_merge_ticks() {
+    let app = TestApp::spawn().await;
+    let mint = random_mint();
+    seed(&app, &mint).await;
+
+    let body = app
+        .get(&format!("/api/tokens/{}/candles?interval=5m&from={}&to={}", mint, START, START))
+        .await;
+
+    assert_eq!(body["candles"].as_array().unwrap().len(), 1);
+    assert_eq!(ohlcv(&body["candles"][0]), [10.0, 12.0, 9.0, 11.0, 8.0]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn closed_candle_ranges_are_cached() {
+    let app = TestApp::spawn().await;
+    let mint = random_mint();
+    seed(&app, &mint).await;
+    let path = format!("/api/tokens/{}/candles?interval=1h&from={}&to={}", mint, START, START);
+
+    let first = app.get(&path).await;
+    tick(&app, &mint, START + 200, 50.0, 1.0).await;
+    let second = app.get(&path).await;
+
+    assert_eq!(first, second);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_intervals_and_mints_return_null() {
+    let app = TestApp::spawn().await;
+
+    assert!(app.get(&format!("/api/tokens/{}/candles?interval=2m", random_mint())).await.is_null());
+    assert!(app.get("/api/tokens/not-a-mint/candles").await.is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ranges_at_the_edge_of_time_return_null() {
+    let app = TestApp::spawn().await;
+    let mint = random_mint();
+
+    for to in [8210266876799i64, -8334601228800] {
+        let path = format!("/api/tokens/{}/candles?interval=1d&to={}", mint, to);
+        assert!(app.get(&path).await.is_null());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ticker_records_prices_of_the_most_active_tokens() {
+    let app = TestApp::spawn().await;
+    let mint = random_mint();
+    sqlx::query("DELETE FROM tokens WHERE volume_24h >= 1e18").execute(&app.db).await.unwrap();
+    sqlx::query("INSERT INTO tokens (mint, symbol, volume_24h) VALUES ($1, 'TICK', 1e18)")
+        .bind(&mint)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    app.prices.set(&mint, 3.0);
+
+    assert_eq!(candles::record_ticks(&app.state, 1).await.unwrap(), 1);
+
+    let body = app.get(&format!("/api/tokens/{}/candles?interval=1m", mint)).await;
+    let candles = body["candles"].as_array().unwrap();
+    assert_eq!(ohlcv(candles.last().unwrap())[3], 3.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ticks_carry_the_trade_volume_since_the_previous_tick() {
+    let app = TestApp::spawn().await;
+    let (mint, market) = (random_mint(), random_mint());
+    sqlx::query(
+        "INSERT INTO markets (address, dex, base_mint, quote_mint, base_reserve, quote_reserve, price)
+         VALUES ($1, 'raydium', $2, $3, 1, 1, 1)"
+    )
+    .bind(&market)
+    .bind(&mint)
+    .bind(random_mint())
+    .execute(&app.db)
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO token_price_history (mint, price, recorded_at) VALUES ($1, 2.0, NOW() - INTERVAL '10 minutes')")
+        .bind(&mint)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    // The first trade predates the previous tick.
+    for (minutes, volume) in [(20, 100.0), (5, 4.0), (1, 6.0)] {
+        sqlx::query(
+            "INSERT INTO trades (signature, market, slot, block_time, side, trader, base_amount, quote_amount, price, volume_usd)
+             VALUES ($1, $2, 1, NOW() - make_interval(mins => $3), 'buy', $4, 1, 1, 1, $5)"
+        )
+        .bind(Keypair::new().pubkey().to_string())
+        .bind(&market)
+        .bind(minutes)
+        .bind(random_mint())
+        .bind(volume)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    }
+    app.prices.set(&mint, 3.0);
+
+    prices::price(&app.state, &mint).await.unwrap();
+
+    let volume: f64 = sqlx::query_scalar(
+        "SELECT volume FROM token_price_history WHERE mint = $1 ORDER BY recorded_at DESC LIMIT 1"
+    )
+    .bind(&mint)
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(volume, 10.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn old_ticks_are_thinned_to_hourly_ohlc() {
+    let app = TestApp::spawn().await;
+    let mint = random_mint();
+    // An hour a week and a half ago, past the raw tick retention.
+    let hour = (chrono::Utc::now().timestamp() - 7 * 86400 - 12 * 3600) / 3600 * 3600;
+    for (offset, price) in [(60, 5.0), (600, 9.0), (1200, 2.0), (1800, 4.0), (2400, 6.0)] {
+        tick(&app, &mint, hour + offset, price, 1.0).await;
+    }
+
+    candles::compact_history(&app.db).await.unwrap();
+    // Compacting again changes nothing.
+    candles::compact_history(&app.db).await.unwrap();
+
+    let ticks: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM token_price_history WHERE mint = $1")
+        .bind(&mint)
+        .fetch_one(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(ticks, 4);
+    let body = app
+        .get(&format!("/api/tokens/{}/candles?interval=1h&from={}&to={}", mint, hour, hour))
+        .await;
+    assert_eq!(ohlcv(&body["candles"][0]), [5.0, 9.0, 2.0, 6.0, 5.0]);
+}
@@ -0,0 +1,83 @@
+mod support;
+
+use support::{TestApp, LEADER, SLOT};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn epochs_are_listed_newest_first() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/epochs?limit=2").await;
+
+    assert_eq!(body["total"], 579);
+    let epochs = body["epochs"].as_array().unwrap();
+    assert_eq!(epochs[0]["epoch"], 578);
+    assert_eq!(epochs[0]["status"], "current");
+    assert_eq!(epochs[1]["epoch"], 577);
+    assert_eq!(epochs[1]["status"], "completed");
+    assert_eq!(epochs[1]["last_slot"], 249695999);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn huge_epoch_pages_are_empty_rather_than_overflowing() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/epochs?page=2147483647&limit=2147483647").await;
+
+    assert!(body["epochs"].as_array().unwrap().is_empty());
+    assert_eq!(body["limit"], 100);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn current_epoch_has_inflation_schedule_and_estimated_end() {
+    let app = TestApp::spawn().await;
+    let now = chrono::Utc::now().timestamp();
+
+    let body = app.get("/api/epochs/578").await;
+
+    assert_eq!(body["first_slot"], 249696000);
+    assert_eq!(body["last_slot"], 250127999);
+    assert_eq!(body["inflation_rate"], 0.0503);
+    assert!(body["rewards"].is_null());
+    let remaining = body["end_time"].as_i64().unwrap() - now;
+    assert!((51_199..=51_201).contains(&remaining));
+
+    let schedule = body["leader_schedule"].as_array().unwrap();
+    assert_eq!(schedule.len(), 3);
+    assert_eq!(schedule[0]["identity"], LEADER);
+    assert_eq!(schedule[0]["leader_slots"], 8);
+    assert_eq!(schedule[0]["share"], 50.0);
+    assert_eq!(schedule[0]["last_slot"], 249696011);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn completed_epoch_rewards_come_from_the_next_epochs_first_block() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/epochs/577").await;
+
+    assert_eq!(body["status"], "completed");
+    assert!(body["inflation_rate"].is_null());
+    assert_eq!(body["rewards"]["slot"], 249696001);
+    assert_eq!(body["rewards"]["staking"], 2.5);
+    assert_eq!(body["rewards"]["voting"], 1.5);
+    assert_eq!(body["rewards"]["total"], 4.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn epochs_beyond_the_next_are_unknown() {
+    let app = TestApp::spawn().await;
+
+    assert!(app.get("/api/epochs/580").await.is_null());
+    assert_eq!(app.get("/api/epochs/579").await["status"], "upcoming");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn upcoming_leaders_start_at_the_current_slot() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/leaders/upcoming?limit=1").await;
+
+    let leaders = body.as_array().unwrap();
+    assert_eq!(leaders[0]["slot"], SLOT);
+    assert_eq!(leaders[0]["leader"], LEADER);
+}
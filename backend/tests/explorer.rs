@@ -0,0 +1,382 @@
+mod support;
+
+use solana_explorer_backend::services::block_follower;
+use support::{TestApp, BLOCKHASH, LEADER, MINT, PROGRAM, SIGNATURE, SLOT, WALLET};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn health_check_reports_ok() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/health").await;
+
+    assert_eq!(body["status"], "ok");
+    assert_eq!(body["service"], "solana-explorer-backend");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_blocks_walks_back_from_the_current_slot() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/blocks?limit=3").await;
+
+    let blocks = body["blocks"].as_array().unwrap();
+    assert_eq!(blocks.len(), 3);
+    assert_eq!(blocks[0]["slot"], SLOT);
+    assert_eq!(blocks[2]["slot"], SLOT - 2);
+    assert_eq!(blocks[0]["blockhash"], BLOCKHASH);
+    assert_eq!(blocks[0]["transactions_count"], 3);
+    assert_eq!(body["page"], 1);
+    assert_eq!(body["limit"], 3);
+    // One fetch per produced slot; SLOT - 1 was skipped.
+    assert_eq!(app.rpc.calls("getBlock"), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_blocks_attributes_leaders_and_reports_skipped_slots() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/blocks?limit=3").await;
+
+    let blocks = body["blocks"].as_array().unwrap();
+    assert_eq!(blocks[0]["leader"], LEADER);
+    assert_eq!(blocks[0]["skipped"], false);
+    assert_eq!(blocks[1]["slot"], SLOT - 1);
+    assert_eq!(blocks[1]["skipped"], true);
+    assert!(blocks[1]["block_number"].is_null());
+    assert_eq!(blocks[1]["leader"], "osWb66v4SoXxnK4XEnbvbMX8MFYRnyrZU9LfitCrJgp");
+    assert!(blocks[1]["blockhash"].is_null());
+    assert_eq!(blocks[2]["leader"], "2PSrzLN6KiT87mrzN22fzkNED8QqDvisBeGy9BtTyfct");
+    // The skipped slot is known from getBlocks and never fetched.
+    assert_eq!(app.rpc.calls("getBlock"), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_blocks_clamps_the_limit() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/blocks?limit=0").await;
+
+    assert_eq!(body["blocks"].as_array().unwrap().len(), 1);
+    assert_eq!(body["limit"], 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_blocks_marks_nothing_skipped_when_produced_slots_are_unknown() {
+    let app = TestApp::spawn().await;
+    app.rpc.set("getBlocks", serde_json::Value::Null);
+
+    let body = app.get("/api/blocks?limit=3").await;
+
+    assert!(body["blocks"].as_array().unwrap().is_empty());
+    assert_eq!(app.rpc.calls("getBlock"), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_block_returns_the_requested_slot() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get(&format!("/api/blocks/{}", SLOT)).await;
+
+    assert_eq!(body["slot"], SLOT);
+    assert_eq!(body["timestamp"], 1708000000);
+    assert_eq!(body["parent_slot"], SLOT - 1);
+    assert_eq!(body["leader"], LEADER);
+    assert_eq!(body["block_number"], 229000000);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_block_resolves_followed_blockhashes() {
+    let app = TestApp::spawn().await;
+    block_follower::index_block(&app.state, SLOT).await.unwrap();
+
+    let body = app.get(&format!("/api/blocks/{}", BLOCKHASH)).await;
+
+    assert_eq!(body["slot"], SLOT);
+    assert_eq!(body["blockhash"], BLOCKHASH);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_blockhash_is_null() {
+    let app = TestApp::spawn().await;
+
+    let body = app
+        .get("/api/blocks/EG5HVD9aBAa1vEvQBZuGy5R6y8ikveZg4oMUf7a2jgge")
+        .await;
+
+    assert!(body.is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_block_by_height_counts_back_over_skipped_slots() {
+    let app = TestApp::spawn().await;
+
+    assert_eq!(app.get("/api/blocks/height/229000000").await["slot"], SLOT);
+    // SLOT - 1 was skipped, so two blocks back is SLOT - 3.
+    assert_eq!(app.get("/api/blocks/height/228999998").await["slot"], SLOT - 3);
+    assert!(app.get("/api/blocks/height/229000001").await.is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_block_at_clamps_to_available_history() {
+    let app = TestApp::spawn().await;
+
+    let latest = app.get("/api/blocks/at?timestamp=1708000000").await;
+    assert_eq!(latest["slot"], SLOT);
+
+    let oldest = app.get("/api/blocks/at?timestamp=1700000000").await;
+    assert_eq!(oldest["slot"], 249000000);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_block_summarises_fees_compute_and_outcomes() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get(&format!("/api/blocks/{}", SLOT)).await;
+
+    assert_eq!(body["total_fees"], 15000);
+    assert_eq!(body["compute_units_consumed"], 2550);
+    assert_eq!(body["successful_transactions"], 2);
+    assert_eq!(body["failed_transactions"], 1);
+    assert_eq!(body["vote_transactions"], 1);
+    assert_eq!(body["rewards"]["fee"], 0.000005);
+    assert_eq!(body["rewards"]["staking"], 0.0);
+    assert_eq!(body["transactions_total"], 3);
+
+    let transactions = body["transactions"].as_array().unwrap();
+    assert_eq!(transactions[0]["signature"], SIGNATURE);
+    assert_eq!(transactions[0]["signer"], WALLET);
+    assert_eq!(transactions[0]["compute_units_consumed"], 150);
+    assert_eq!(transactions[1]["vote"], true);
+    assert_eq!(transactions[2]["status"], "failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_block_pages_transactions_and_filters_votes() {
+    let app = TestApp::spawn().await;
+
+    let page = app.get(&format!("/api/blocks/{}?page=2&limit=1", SLOT)).await;
+    let transactions = page["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0]["vote"], true);
+
+    let without_votes = app.get(&format!("/api/blocks/{}?votes=false", SLOT)).await;
+    assert_eq!(without_votes["transactions_total"], 2);
+    assert!(without_votes["transactions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|tx| tx["vote"] == false));
+    // Summary counts always cover the whole block.
+    assert_eq!(without_votes["vote_transactions"], 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn block_production_is_reported_per_leader() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/blocks/production").await;
+
+    assert_eq!(body["total_leader_slots"], 148);
+    assert_eq!(body["total_blocks_produced"], 135);
+    assert_eq!(body["total_skipped_slots"], 13);
+    let leaders = body["leaders"].as_array().unwrap();
+    assert_eq!(leaders[0]["identity"], LEADER);
+    assert_eq!(leaders[0]["skipped_slots"], 5);
+    assert_eq!(leaders[0]["skip_rate"], 5.0);
+
+    let one = app.get(&format!("/api/blocks/production?identity={}", LEADER)).await;
+    assert_eq!(one["leaders"].as_array().unwrap().len(), 1);
+    assert_eq!(one["total_leader_slots"], 100);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_block_returns_null_when_the_node_has_no_block() {
+    let app = TestApp::spawn().await;
+    app.rpc.set("getBlock.1", serde_json::Value::Null);
+
+    let body = app.get("/api/blocks/1").await;
+
+    assert!(body.is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_transactions_is_paginated() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/transactions?page=2&limit=10").await;
+
+    assert!(body["transactions"].is_array());
+    assert_eq!(body["page"], 2);
+    assert_eq!(body["limit"], 10);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_transaction_decodes_fee_and_status() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get(&format!("/api/transactions/{}", SIGNATURE)).await;
+
+    assert_eq!(body["signature"], SIGNATURE);
+    assert_eq!(body["slot"], SLOT);
+    assert_eq!(body["block_number"], 229000000);
+    assert_eq!(body["fee"], 5000);
+    assert_eq!(body["status"], "success");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_transaction_rejects_malformed_signatures() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/transactions/not-a-signature").await;
+
+    assert!(body.is_null());
+    assert_eq!(app.rpc.calls("getTransaction"), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_address_reports_balance_in_sol() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get(&format!("/api/addresses/{}", WALLET)).await;
+
+    assert_eq!(body["address"], WALLET);
+    assert_eq!(body["balance"], 12.5);
+    assert_eq!(body["type"], "wallet");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_address_transactions_is_paginated() {
+    let app = TestApp::spawn().await;
+
+    let body = app
+        .get(&format!("/api/addresses/{}/transactions?limit=5", WALLET))
+        .await;
+
+    assert!(body["transactions"].is_array());
+    assert_eq!(body["limit"], 5);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn network_stats_reflect_epoch_info() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/network/stats").await;
+
+    assert_eq!(body["slot"], SLOT);
+    assert_eq!(body["block_height"], 229000000);
+    assert_eq!(body["epoch"], 578);
+    let progress = body["epoch_progress"].as_f64().unwrap();
+    assert!((progress - 70.370).abs() < 0.01);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn network_stats_compute_tps_from_performance_samples() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/network/stats").await;
+
+    assert_eq!(body["tps"], 4000.0);
+    assert_eq!(body["non_vote_tps"], 1000.0);
+    assert_eq!(body["total_transactions"], 268000000000u64);
+
+    let windows = body["throughput"].as_array().unwrap();
+    assert_eq!(windows[0]["window"], "1m");
+    assert_eq!(windows[1]["window"], "5m");
+    assert_eq!(windows[1]["tps"], 3200.0);
+    assert_eq!(windows[1]["non_vote_tps"], 600.0);
+    assert_eq!(windows[2]["window"], "1h");
+    assert_eq!(windows[2]["tps"], 3000.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn network_tps_series_is_bucketed_oldest_first() {
+    let app = TestApp::spawn().await;
+
+    let minutely = app.get("/api/network/tps").await;
+    assert_eq!(minutely["interval"], "1m");
+    assert_eq!(minutely["samples"].as_array().unwrap().len(), 6);
+
+    let body = app.get("/api/network/tps?interval=5m").await;
+    let samples = body["samples"].as_array().unwrap();
+    assert_eq!(samples.len(), 2);
+    assert_eq!(samples[0]["tps"], 2000.0);
+    assert_eq!(samples[1]["tps"], 3200.0);
+    assert_eq!(samples[1]["slot"], SLOT);
+    let gap = samples[1]["timestamp"].as_i64().unwrap() - samples[0]["timestamp"].as_i64().unwrap();
+    assert_eq!(gap, 300);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_classifies_the_query() {
+    let app = TestApp::spawn().await;
+
+    assert_eq!(app.get(&format!("/api/search?q={}", SLOT)).await["type"], "block");
+    assert_eq!(app.get(&format!("/api/search?q={}", SIGNATURE)).await["type"], "transaction");
+    assert_eq!(app.get(&format!("/api/search?q={}", WALLET)).await["type"], "address");
+    assert_eq!(app.get("/api/search?q=hello").await["type"], "unknown");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_returns_the_resolved_entity() {
+    let app = TestApp::spawn().await;
+
+    let tx = app.get(&format!("/api/search?q={}", SIGNATURE)).await;
+    assert_eq!(tx["result"]["signature"], SIGNATURE);
+    assert_eq!(tx["result"]["slot"], SLOT);
+
+    let wallet = app.get(&format!("/api/search?q={}", WALLET)).await;
+    assert_eq!(wallet["result"]["balance"], 12.5);
+    assert!(app.get("/api/search?q=hello").await["result"].is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_recognises_mints_and_programs() {
+    let app = TestApp::spawn().await;
+
+    let token = app.get(&format!("/api/search?q={}", MINT)).await;
+    assert_eq!(token["type"], "token");
+    assert_eq!(token["result"]["decimals"], 6);
+    assert_eq!(token["result"]["supply"], "1000000000000");
+    assert!(token["result"]["mint_authority"].is_null());
+
+    let program = app.get(&format!("/api/search?q={}", PROGRAM)).await;
+    assert_eq!(program["type"], "program");
+    assert_eq!(program["result"]["loader"], "BPFLoaderUpgradeab1e11111111111111111111111");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_resolves_followed_blockhashes() {
+    let app = TestApp::spawn().await;
+
+    block_follower::index_block(&app.state, SLOT).await.unwrap();
+    let body = app.get(&format!("/api/search?q={}", BLOCKHASH)).await;
+
+    assert_eq!(body["type"], "block");
+    assert_eq!(body["result"]["slot"], SLOT);
+    assert_eq!(body["candidates"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_lists_every_candidate_for_ambiguous_numbers() {
+    let app = TestApp::spawn().await;
+
+    // 228999998 is both a slot and, two blocks below the tip, a block height.
+    let body = app.get("/api/search?q=228999998").await;
+    let candidates = body["candidates"].as_array().unwrap();
+
+    assert_eq!(candidates.len(), 2);
+    assert_eq!(candidates[0]["result"]["slot"], 228999998);
+    assert_eq!(candidates[1]["type"], "block");
+    assert_eq!(candidates[1]["result"]["slot"], SLOT - 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn resolved_block_heights_are_cached() {
+    let app = TestApp::spawn().await;
+
+    app.get("/api/search?q=228999998").await;
+    let resolved = app.rpc.calls("getEpochInfo");
+    app.get("/api/search?q=228999998").await;
+
+    assert_eq!(app.rpc.calls("getEpochInfo"), resolved);
+}
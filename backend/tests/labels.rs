@@ -0,0 +1,160 @@
+mod support;
+
+use serde_json::json;
+use support::{TestApp, LEADER, RECIPIENT, SIGNATURE, SLOT, WALLET};
+
+// Each test labels its own address: the labels table is shared by tests
+// running in parallel.
+
+#[tokio::test(flavor = "multi_thread")]
+async fn curated_labels_appear_on_blocks() {
+    let app = TestApp::spawn().await;
+    let admin = app.sign_up_admin().await;
+
+    let label = app
+        .post_as(&admin, "/api/admin/labels", json!({ "address": LEADER, "label": "Leader Validator", "category": "validator" }))
+        .await;
+    assert_eq!(label["category"], "validator");
+
+    let block = app.get(&format!("/api/blocks/{}", SLOT)).await;
+    assert_eq!(block["labels"][LEADER]["name"], "Leader Validator");
+    assert_eq!(block["labels"][LEADER]["private"], false);
+
+    let list = app.get("/api/blocks?limit=3").await;
+    assert_eq!(list["labels"][LEADER]["category"], "validator");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn imported_labels_appear_on_transactions_and_in_search() {
+    let app = TestApp::spawn().await;
+    let admin = app.sign_up_admin().await;
+    let csv = format!("address,label,category\n{},\"Hot Wallet, Exchange 7\",exchange\n", RECIPIENT);
+
+    let response = app.client
+        .post(format!("{}/api/admin/labels/import", app.address))
+        .bearer_auth(&admin)
+        .body(csv)
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let tx = app.get(&format!("/api/transactions/{}", SIGNATURE)).await;
+    assert_eq!(tx["signer"], WALLET);
+    assert_eq!(tx["labels"][RECIPIENT]["name"], "Hot Wallet, Exchange 7");
+    assert_eq!(tx["labels"][RECIPIENT]["category"], "exchange");
+
+    let search = app.get("/api/search?q=hot%20wallet%2C%20exchange%207").await;
+    assert_eq!(search["type"], "label");
+    assert_eq!(search["result"]["address"], RECIPIENT);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn imports_with_an_invalid_row_write_nothing() {
+    let app = TestApp::spawn().await;
+    let admin = app.sign_up_admin().await;
+    let address = "5EDiqwwNZ5MU1byqjqJkM8UJdorXqxA8qtjNF7vCKCXm";
+    let body = json!([
+        { "address": address, "label": "Valid" },
+        { "address": "not-a-pubkey", "label": "Invalid" },
+    ]);
+
+    let response = app.client
+        .post(format!("{}/api/admin/labels/import", app.address))
+        .bearer_auth(&admin)
+        .body(body.to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let details = app.get(&format!("/api/addresses/{}", address)).await;
+    assert!(details["labels"].as_object().unwrap().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn private_labels_are_only_visible_to_their_owner() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.sign_up().await;
+
+    app.post_as(&token, "/api/user/labels", json!({ "address": WALLET, "label": "My cold wallet" }))
+        .await;
+
+    let mine = app.get_as(&token, &format!("/api/addresses/{}", WALLET)).await;
+    assert_eq!(mine["labels"][WALLET]["name"], "My cold wallet");
+    assert_eq!(mine["labels"][WALLET]["private"], true);
+    assert_eq!(app.get_as(&token, "/api/user/labels").await.as_array().unwrap().len(), 1);
+
+    let anonymous = app.get(&format!("/api/addresses/{}", WALLET)).await;
+    assert!(anonymous["labels"].get(WALLET).is_none());
+
+    app.delete_as(&token, &format!("/api/user/labels/{}", WALLET)).await;
+    let mine = app.get_as(&token, &format!("/api/addresses/{}", WALLET)).await;
+    assert!(mine["labels"].get(WALLET).is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn private_labels_override_curated_ones() {
+    let app = TestApp::spawn().await;
+    let address = "9Q97LER2kPEqNVVJHrdhUwzpisZ6iNJsVN3qfBbbR8c5";
+    let (_, token) = app.sign_up().await;
+    let admin = app.sign_up_admin().await;
+
+    app.post_as(&admin, "/api/admin/labels", json!({ "address": address, "label": "Curated" })).await;
+    app.post_as(&token, "/api/user/labels", json!({ "address": address, "label": "Mine" })).await;
+
+    let path = format!("/api/addresses/{}", address);
+    assert_eq!(app.get_as(&token, &path).await["labels"][address]["name"], "Mine");
+    assert_eq!(app.get(&path).await["labels"][address]["name"], "Curated");
+
+    app.delete_as(&admin, &format!("/api/admin/labels/{}", address)).await;
+    assert!(app.get(&path).await["labels"].get(address).is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn user_labels_require_a_token() {
+    let app = TestApp::spawn().await;
+
+    let response = app.client
+        .get(format!("{}/api/user/labels", app.address))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 401);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn curated_labels_require_an_admin() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.sign_up().await;
+    let body = json!({ "address": WALLET, "label": "Not curated" });
+    let url = format!("{}/api/admin/labels", app.address);
+
+    let anonymous = app.client.post(&url).json(&body).send().await.unwrap();
+    assert_eq!(anonymous.status(), 401);
+
+    let user = app.client.post(&url).bearer_auth(&token).json(&body).send().await.unwrap();
+    assert_eq!(user.status(), 403);
+
+    let import = app.client
+        .post(format!("{}/api/admin/labels/import", app.address))
+        .bearer_auth(&token)
+        .body(format!("{},Not curated\n", WALLET))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(import.status(), 403);
+    assert!(app.get(&format!("/api/addresses/{}", WALLET)).await["labels"].get(WALLET).is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn curated_label_list_reports_the_clamped_page_and_limit() {
+    let app = TestApp::spawn().await;
+    let admin = app.sign_up_admin().await;
+
+    let body = app.get_as(&admin, "/api/admin/labels?page=0&limit=100000").await;
+
+    assert_eq!(body["page"], 1);
+    assert_eq!(body["limit"], 500);
+}
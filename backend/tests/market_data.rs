@@ -0,0 +1,146 @@
+mod support;
+
+use serde_json::Value;
+use solana_explorer_backend::services::token_list;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use support::TestApp;
+
+const WSOL: &str = "So11111111111111111111111111111111111111112";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_tokens_is_paginated() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/tokens?page=1&limit=10").await;
+
+    assert!(body["tokens"].is_array());
+    assert_eq!(body["page"], 1);
+    assert_eq!(body["limit"], 10);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_token_responds_for_a_mint() {
+    let app = TestApp::spawn().await;
+
+    let response = app.client
+        .get(format!("{}/api/tokens/{}", app.address, WSOL))
+        .send()
+        .await
+        .unwrap();
+
+    assert!(response.status().is_success());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_markets_is_paginated() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/markets?limit=5").await;
+
+    assert!(body["markets"].is_array());
+    assert_eq!(body["limit"], 5);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_market_returns_null_for_unknown_pair() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/markets/UNKNOWN-PAIR").await;
+
+    assert!(body.is_null());
+}
+
+/// Lists tokens with a liquidity floor only the rows inserted by
+/// `seed_listing` reach, so other tokens in the shared database don't
+/// interfere.
+const LISTING_FLOOR: f64 = 1e15;
+
+async fn seed_listing(app: &TestApp) {
+    sqlx::query("DELETE FROM tokens WHERE liquidity >= $1")
+        .bind(LISTING_FLOOR)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    let rows = [
+        ("LISTA", "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", true, Some(3.0), 10.0, Some(5_i64)),
+        ("LISTB", "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", false, Some(1.0), 30.0, Some(50)),
+        ("LISTC", "TokenzQdBNbLqP5VeNSNFo9ucLgEtudsUjDwMBHyuRWsK", true, None, 20.0, None),
+    ];
+    for (i, (symbol, program, verified, price, volume, holders)) in rows.into_iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO tokens (mint, symbol, name, program, verified, price, volume_24h, liquidity, holders)
+             VALUES ($1, $2, $2, $3, $4, $5, $6, $7, $8)"
+        )
+        .bind(Keypair::new().pubkey().to_string())
+        .bind(symbol)
+        .bind(program)
+        .bind(verified)
+        .bind(price)
+        .bind(volume)
+        .bind(LISTING_FLOOR + i as f64)
+        .bind(holders)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    }
+}
+
+fn symbols(body: &Value) -> Vec<&str> {
+    body["tokens"].as_array().unwrap().iter().map(|t| t["symbol"].as_str().unwrap()).collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_tokens_sorts_filters_and_counts() {
+    let app = TestApp::spawn().await;
+    seed_listing(&app).await;
+    let base = format!("/api/tokens?min_liquidity={}", LISTING_FLOOR);
+
+    let by_volume = app.get(&base).await;
+    assert_eq!(symbols(&by_volume), ["LISTB", "LISTC", "LISTA"]);
+    assert_eq!(by_volume["total"], 3);
+
+    // Tokens without a price sort last either way.
+    assert_eq!(symbols(&app.get(&format!("{}&sort=price", base)).await), ["LISTA", "LISTB", "LISTC"]);
+    assert_eq!(symbols(&app.get(&format!("{}&sort=price&order=asc", base)).await), ["LISTB", "LISTA", "LISTC"]);
+    assert_eq!(symbols(&app.get(&format!("{}&sort=holders", base)).await), ["LISTB", "LISTA", "LISTC"]);
+
+    let verified = app.get(&format!("{}&verified=true", base)).await;
+    assert_eq!(symbols(&verified), ["LISTC", "LISTA"]);
+    assert_eq!(verified["total"], 2);
+
+    let token_2022 = app.get(&format!("{}&program=token-2022", base)).await;
+    assert_eq!(symbols(&token_2022), ["LISTC"]);
+
+    let page = app.get(&format!("{}&limit=2&page=2", base)).await;
+    assert_eq!(symbols(&page), ["LISTA"]);
+    assert_eq!(page["total"], 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn token_list_refresh_computes_market_cap_and_holders() {
+    let app = TestApp::spawn().await;
+    let mint = Keypair::new().pubkey().to_string();
+    sqlx::query("INSERT INTO tokens (mint, symbol, decimals, supply) VALUES ($1, 'CAP', 6, 1000000000)")
+        .bind(&mint)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO token_holder_counts (mint, holders) VALUES ($1, 7)")
+        .bind(&mint)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    app.prices.set(&mint, 2.0);
+
+    token_list::refresh(&app.state).await.unwrap();
+
+    let (price, market_cap, holders): (Option<f64>, Option<f64>, Option<i64>) =
+        sqlx::query_as("SELECT price, market_cap, holders FROM tokens WHERE mint = $1")
+            .bind(&mint)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(price, Some(2.0));
+    assert_eq!(market_cap, Some(2000.0));
+    assert_eq!(holders, Some(7));
+}
@@ -0,0 +1,428 @@
+mod support;
+
+use serde_json::{json, Value};
+use solana_explorer_backend::services::{block_follower, markets, pools, solana::TOKEN_PROGRAM_ID, trades};
+use solana_sdk::{pubkey, pubkey::Pubkey};
+use support::{account_info, keyed_account, set_block, token_account_data, token_balance, TestApp, SLOT};
+
+const WSOL: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+
+fn mint_data(decimals: u8) -> Vec<u8> {
+    let mut data = vec![0u8; 82];
+    data[44] = decimals;
+    data[45] = 1;
+    data
+}
+
+fn write(data: &mut [u8], offset: usize, bytes: &[u8]) {
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+/// Serves `accounts`, in the order the indexer asks for them, from
+/// getMultipleAccounts.
+fn set_accounts(app: &TestApp, accounts: &[Vec<u8>]) {
+    app.rpc.set(
+        "getMultipleAccounts",
+        json!({
+            "context": { "slot": SLOT },
+            "value": accounts.iter().map(|data| account_info(&TOKEN_PROGRAM_ID, data)["value"].clone()).collect::<Vec<Value>>(),
+        }),
+    );
+}
+
+async fn insert_token(app: &TestApp, mint: &Pubkey, symbol: &str, price: Option<f64>) {
+    sqlx::query("INSERT INTO tokens (mint, symbol, price) VALUES ($1, $2, $3)")
+        .bind(mint.to_string())
+        .bind(symbol)
+        .bind(price)
+        .execute(&app.db)
+        .await
+        .unwrap();
+}
+
+/// Runs the trade index alone over the block at `slot`.
+async fn index_trades(app: &TestApp, slot: u64) -> usize {
+    let block = block_follower::fetch(&app.state, slot).unwrap();
+    trades::index(&app.state.db, slot, &block).await.unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn whirlpools_are_priced_from_sqrt_price_and_valued_in_usd() {
+    let app = TestApp::spawn().await;
+    let (pool, base, quote) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let (base_vault, quote_vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+    insert_token(&app, &base, "WHIRL", None).await;
+    insert_token(&app, &quote, "QUO", Some(1.0)).await;
+
+    // sqrt price 2.0 in Q64.64: 4 QUO per WHIRL.
+    let mut data = vec![0u8; 653];
+    write(&mut data, 65, &(1u128 << 65).to_le_bytes());
+    write(&mut data, 101, base.as_ref());
+    write(&mut data, 133, base_vault.as_ref());
+    write(&mut data, 181, quote.as_ref());
+    write(&mut data, 213, quote_vault.as_ref());
+    app.rpc.set(
+        &format!("getProgramAccounts.{}", pools::ORCA_WHIRLPOOL),
+        json!([keyed_account(&pool, &pools::ORCA_WHIRLPOOL, &data)]),
+    );
+    let owner = Pubkey::new_unique();
+    set_accounts(&app, &[
+        token_account_data(&base, &owner, 1_000_000_000),
+        token_account_data(&quote, &owner, 4_000_000_000),
+        mint_data(6),
+        mint_data(6),
+    ]);
+
+    assert_eq!(markets::discover(&app.state, &base).await.unwrap(), 1);
+
+    let body = app.get(&format!("/api/markets?mint={}", base)).await;
+    assert_eq!(body["total"], 1);
+    let market = &body["markets"][0];
+    assert_eq!(market["address"], pool.to_string());
+    assert_eq!(market["pair"], "WHIRL/QUO");
+    assert_eq!(market["dex"], "orca");
+    assert_eq!(market["price"], 4.0);
+    assert_eq!(market["base_reserve"], 1000.0);
+    // 1000 WHIRL at the pool price of $4 plus 4000 QUO at $1.
+    assert_eq!(market["liquidity"], 8000.0);
+
+    assert_eq!(app.get(&format!("/api/markets/{}", pool)).await["pair"], "WHIRL/QUO");
+    let token = app.get(&format!("/api/markets?mint={}&dex=raydium", quote)).await;
+    assert_eq!(token["total"], 0);
+    let (liquidity,): (f64,) = sqlx::query_as("SELECT liquidity FROM tokens WHERE mint = $1")
+        .bind(quote.to_string())
+        .fetch_one(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(liquidity, 8000.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn raydium_pools_are_priced_from_reserves_against_the_stronger_quote() {
+    let app = TestApp::spawn().await;
+    let (pool, token) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (sol_vault, token_vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    // WSOL on the base side with 2 SOL of it owed to the protocol.
+    let mut data = vec![0u8; 752];
+    write(&mut data, 192, &2_000_000_000u64.to_le_bytes());
+    write(&mut data, 336, sol_vault.as_ref());
+    write(&mut data, 368, token_vault.as_ref());
+    write(&mut data, 400, WSOL.as_ref());
+    write(&mut data, 432, token.as_ref());
+    app.rpc.set(
+        &format!("getProgramAccounts.{}", pools::RAYDIUM_AMM_V4),
+        json!([keyed_account(&pool, &pools::RAYDIUM_AMM_V4, &data)]),
+    );
+    let owner = Pubkey::new_unique();
+    set_accounts(&app, &[
+        token_account_data(&WSOL, &owner, 10_000_000_000),
+        token_account_data(&token, &owner, 1_000_000_000),
+        mint_data(9),
+        mint_data(6),
+    ]);
+
+    markets::discover(&app.state, &token).await.unwrap();
+
+    let market = &app.get(&format!("/api/markets/{}", pool)).await["markets"][0];
+    assert_eq!(market["dex"], "raydium");
+    assert_eq!(market["base_mint"], token.to_string());
+    assert_eq!(market["quote_mint"], WSOL.to_string());
+    assert_eq!(market["base_reserve"], 1000.0);
+    assert_eq!(market["quote_reserve"], 8.0);
+    assert_eq!(market["price"], 0.008);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn raydium_cp_swap_pools_take_their_fee_from_the_amm_config() {
+    let app = TestApp::spawn().await;
+    let (pool, config, base, quote) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let (base_vault, quote_vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    let mut data = vec![0u8; 637];
+    write(&mut data, 8, config.as_ref());
+    write(&mut data, 72, base_vault.as_ref());
+    write(&mut data, 104, quote_vault.as_ref());
+    write(&mut data, 168, base.as_ref());
+    write(&mut data, 200, quote.as_ref());
+    app.rpc.set(
+        &format!("getProgramAccounts.{}", pools::RAYDIUM_CPMM),
+        json!([keyed_account(&pool, &pools::RAYDIUM_CPMM, &data)]),
+    );
+    // A 1% tier, in millionths.
+    let mut config_data = vec![0u8; 236];
+    write(&mut config_data, 12, &10_000u64.to_le_bytes());
+    let owner = Pubkey::new_unique();
+    set_accounts(&app, &[
+        token_account_data(&base, &owner, 1_000_000_000),
+        token_account_data(&quote, &owner, 2_000_000_000),
+        mint_data(6),
+        mint_data(6),
+        config_data,
+    ]);
+
+    markets::discover(&app.state, &base).await.unwrap();
+
+    let market = &app.get(&format!("/api/markets/{}", pool)).await["markets"][0];
+    assert_eq!(market["dex"], "raydium-cpmm");
+    assert_eq!(market["price"], 2.0);
+    assert_eq!(market["fee_rate"], 0.01);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn markets_report_the_clamped_page_and_limit() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get(&format!("/api/markets?mint={}&page=0&limit=1000", Pubkey::new_unique())).await;
+
+    assert_eq!(body["page"], 1);
+    assert_eq!(body["limit"], 100);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn market_trades_report_the_clamped_page_and_limit() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get(&format!("/api/markets/{}/trades?page=0&limit=1000", Pubkey::new_unique())).await;
+
+    assert_eq!(body["page"], 1);
+    assert_eq!(body["limit"], 100);
+}
+
+const TRADE_SLOT: u64 = 250_000_200;
+
+/// A transaction by `trader` that moves the two vaults of `pool` from `pre`
+/// to `post` raw balances, logging `program` as invoked if given.
+fn swap_tx(trader: &Pubkey, pool: &Pubkey, vaults: [(&Pubkey, &Pubkey); 2], pre: [u64; 2], post: [u64; 2], program: Option<&Pubkey>) -> Value {
+    let keys = [trader, pool, vaults[0].0, vaults[1].0];
+    let balance = |index: u8, mint: &Pubkey, amount: u64| {
+        token_balance(index, &mint.to_string(), &Pubkey::new_unique().to_string(), amount)
+    };
+    let balances = |amounts: [u64; 2]| json!([balance(2, vaults[0].1, amounts[0]), balance(3, vaults[1].1, amounts[1])]);
+    let logs: Vec<String> = program
+        .map(|program| vec![format!("Program {} invoke [1]", program), format!("Program {} success", program)])
+        .unwrap_or_default();
+    json!({
+        "transaction": {
+            "signatures": [solana_sdk::signature::Signature::new_unique().to_string()],
+            "message": {
+                "accountKeys": keys
+                    .iter()
+                    .map(|key| json!({ "pubkey": key.to_string(), "writable": true, "signer": false, "source": "transaction" }))
+                    .collect::<Vec<_>>(),
+                "recentBlockhash": "EG5HVD9aBAa1vEvQBZuGy5R6y8ikveZg4oMUf7a2jgge",
+                "instructions": [],
+            },
+        },
+        "meta": {
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5000,
+            "preBalances": [0, 0, 0, 0],
+            "postBalances": [0, 0, 0, 0],
+            "innerInstructions": [],
+            "logMessages": logs,
+            "preTokenBalances": balances(pre),
+            "postTokenBalances": balances(post),
+            "rewards": [],
+        },
+        "version": 0,
+    })
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn swaps_are_indexed_and_rolled_into_24h_stats() {
+    let app = TestApp::spawn().await;
+    let (pool, base, quote) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let (base_vault, quote_vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (buyer, seller) = (Pubkey::new_unique(), Pubkey::new_unique());
+    insert_token(&app, &quote, "QUO", Some(1.0)).await;
+    sqlx::query(
+        "INSERT INTO markets (address, dex, base_mint, quote_mint, base_vault, quote_vault, base_reserve, quote_reserve, price)
+         VALUES ($1, 'orca', $2, $3, $4, $5, 995, 2009, 2.2)"
+    )
+    .bind(pool.to_string())
+    .bind(base.to_string())
+    .bind(quote.to_string())
+    .bind(base_vault.to_string())
+    .bind(quote_vault.to_string())
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    let vaults = [(&base_vault, &base), (&quote_vault, &quote)];
+    // Buys 10 base for 20 quote, then a plain transfer into the vaults that
+    // never touches the pool program.
+    set_block(&app, TRADE_SLOT, chrono::Utc::now().timestamp() - 3600, vec![
+        swap_tx(&buyer, &pool, vaults, [1_000_000_000, 2_000_000_000], [990_000_000, 2_020_000_000], Some(&pools::ORCA_WHIRLPOOL)),
+        swap_tx(&buyer, &pool, vaults, [990_000_000, 2_020_000_000], [991_000_000, 2_019_000_000], None),
+    ]);
+    // Sells 5 base for 11 quote.
+    set_block(&app, TRADE_SLOT + 1, chrono::Utc::now().timestamp() - 3600, vec![
+        swap_tx(&seller, &pool, vaults, [990_000_000, 2_020_000_000], [995_000_000, 2_009_000_000], Some(&pools::ORCA_WHIRLPOOL)),
+    ]);
+
+    assert_eq!(index_trades(&app, TRADE_SLOT).await, 1);
+    assert_eq!(index_trades(&app, TRADE_SLOT + 1).await, 1);
+    index_trades(&app, TRADE_SLOT).await;
+
+    let body = app.get(&format!("/api/markets/{}/trades", pool)).await;
+    assert_eq!(body["total"], 2);
+    let sell = &body["trades"][0];
+    assert_eq!(sell["side"], "sell");
+    assert_eq!(sell["trader"], seller.to_string());
+    assert_eq!(sell["base_amount"], 5.0);
+    assert_eq!(sell["quote_amount"], 11.0);
+    assert_eq!(sell["price"], 2.2);
+    assert_eq!(sell["volume_usd"], 11.0);
+    let buy = &body["trades"][1];
+    assert_eq!(buy["side"], "buy");
+    assert_eq!(buy["price"], 2.0);
+    assert_eq!(app.get(&format!("/api/markets/{}/trades?side=buy", pool)).await["total"], 1);
+    assert_eq!(app.get(&format!("/api/markets/{}/trades?trader={}", pool, seller)).await["total"], 1);
+
+    trades::update_stats(&app.db).await.unwrap();
+    let market = &app.get(&format!("/api/markets/{}", pool)).await["markets"][0];
+    assert_eq!(market["volume_24h"], 31.0);
+    // From the first trade of the day at 2.0 to the pool price of 2.2.
+    assert!((market["price_change_24h"].as_f64().unwrap() - 10.0).abs() < 1e-9);
+}
+
+/// Inserts an indexed market with `liquidity`, stored as priced.
+async fn insert_market(app: &TestApp, dex: &str, base: &Pubkey, quote: &Pubkey, reserves: (f64, f64), price: f64, liquidity: f64, fee_rate: f64) -> Pubkey {
+    let pool = Pubkey::new_unique();
+    sqlx::query(
+        "INSERT INTO markets (address, dex, base_mint, quote_mint, base_reserve, quote_reserve, price, liquidity, fee_rate)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"
+    )
+    .bind(pool.to_string())
+    .bind(dex)
+    .bind(base.to_string())
+    .bind(quote.to_string())
+    .bind(reserves.0)
+    .bind(reserves.1)
+    .bind(price)
+    .bind(liquidity)
+    .bind(fee_rate)
+    .execute(&app.db)
+    .await
+    .unwrap();
+    pool
+}
+
+/// Liquidity floor only the tokens of `token_volume_rolls_up_from_market_trades`
+/// reach, so the listing it sorts holds nothing else.
+const VOLUME_FLOOR: f64 = 7e15;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn token_volume_rolls_up_from_market_trades() {
+    let app = TestApp::spawn().await;
+    let (quiet, busy, quote) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    sqlx::query("DELETE FROM tokens WHERE liquidity >= $1").bind(VOLUME_FLOOR).execute(&app.db).await.unwrap();
+    for (i, (mint, symbol)) in [(quiet, "QUIET"), (busy, "BUSY")].into_iter().enumerate() {
+        sqlx::query("INSERT INTO tokens (mint, symbol, liquidity) VALUES ($1, $2, $3)")
+            .bind(mint.to_string())
+            .bind(symbol)
+            .bind(VOLUME_FLOOR + i as f64)
+            .execute(&app.db)
+            .await
+            .unwrap();
+    }
+    let quiet_pool = insert_market(&app, "orca", &quiet, &quote, (1.0, 1.0), 1.0, 0.0, 0.0).await;
+    // BUSY trades on two pools, on the quote side of one of them.
+    let busy_pool = insert_market(&app, "orca", &busy, &quote, (1.0, 1.0), 1.0, 0.0, 0.0).await;
+    let flipped_pool = insert_market(&app, "raydium", &quote, &busy, (1.0, 1.0), 1.0, 0.0, 0.0).await;
+    for (pool, volume) in [(quiet_pool, 5.0), (busy_pool, 20.0), (flipped_pool, 15.0)] {
+        sqlx::query(
+            "INSERT INTO trades (signature, market, slot, block_time, side, trader, base_amount, quote_amount, price, volume_usd)
+             VALUES ($1, $2, $3, NOW() - INTERVAL '1 hour', 'buy', $4, 1, 1, 1, $5)"
+        )
+        .bind(solana_sdk::signature::Signature::new_unique().to_string())
+        .bind(pool.to_string())
+        .bind(TRADE_SLOT as i64)
+        .bind(Pubkey::new_unique().to_string())
+        .bind(volume)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    }
+
+    trades::update_stats(&app.db).await.unwrap();
+
+    let body = app.get(&format!("/api/tokens?min_liquidity={}", VOLUME_FLOOR)).await;
+    let tokens = body["tokens"].as_array().unwrap();
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(tokens[0]["symbol"], "BUSY");
+    assert_eq!(tokens[0]["volume_24h"], 35.0);
+    assert_eq!(tokens[1]["symbol"], "QUIET");
+    assert_eq!(tokens[1]["volume_24h"], 5.0);
+}
+
+fn close(value: &Value, expected: f64) -> bool {
+    (value.as_f64().unwrap() - expected).abs() < 1e-9
+}
+
+/// A pair with a shallow Raydium pool at 2.0 and a deeper Orca pool stored
+/// the other way round at 2.5, and the symbols of its two tokens.
+async fn seed_pair(app: &TestApp) -> (Pubkey, Pubkey, Pubkey, Pubkey, String, String) {
+    let (base, quote) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (base_symbol, quote_symbol) = (format!("B{}", &base.to_string()[..8]), format!("Q{}", &quote.to_string()[..8]));
+    insert_token(app, &base, &base_symbol, None).await;
+    insert_token(app, &quote, &quote_symbol, None).await;
+    let raydium = insert_market(app, "raydium", &base, &quote, (1000.0, 2000.0), 2.0, 4000.0, 0.0025).await;
+    let orca = insert_market(app, "orca", &quote, &base, (5000.0, 2000.0), 0.4, 9000.0, 0.0).await;
+    (base, quote, raydium, orca, base_symbol, quote_symbol)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pairs_resolve_from_symbols_mints_or_a_pool() {
+    let app = TestApp::spawn().await;
+    let (base, quote, raydium, orca, base_symbol, quote_symbol) = seed_pair(&app).await;
+
+    let pair = app.get(&format!("/api/markets/{}-{}", base_symbol, quote_symbol)).await;
+    assert_eq!(pair["pair"], format!("{}/{}", base_symbol, quote_symbol));
+    assert_eq!(pair["base_mint"], base.to_string());
+    assert_eq!(pair["best_buy_price"], 2.0);
+    assert_eq!(pair["best_sell_price"], 2.5);
+    assert_eq!(pair["liquidity"], 13000.0);
+    let markets = pair["markets"].as_array().unwrap();
+    assert_eq!(markets.len(), 2);
+    assert_eq!(markets[0]["address"], orca.to_string());
+    assert_eq!(markets[0]["base_mint"], base.to_string());
+    assert_eq!(markets[0]["base_reserve"], 2000.0);
+    assert_eq!(markets[0]["price"], 2.5);
+
+    let reversed = app.get(&format!("/api/markets/{}-{}", quote, base)).await;
+    assert_eq!(reversed["pair"], format!("{}/{}", quote_symbol, base_symbol));
+    assert_eq!(reversed["best_buy_price"], 0.4);
+    assert_eq!(reversed["best_sell_price"], 0.5);
+
+    // A pool address keeps the pool's own orientation.
+    let by_pool = app.get(&format!("/api/markets/{}", raydium)).await;
+    assert_eq!(by_pool["base_mint"], base.to_string());
+    assert_eq!(by_pool["markets"].as_array().unwrap().len(), 2);
+
+    assert!(app.get(&format!("/api/markets/{}-NOSUCHSYMBOL", base_symbol)).await.is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn quotes_rank_pools_by_output() {
+    let app = TestApp::spawn().await;
+    let (base, quote, raydium, orca, _, _) = seed_pair(&app).await;
+
+    let sell_base = app.get(&format!("/api/markets/{}-{}/quote?amount=10", base, quote)).await;
+    assert_eq!(sell_base["input_mint"], base.to_string());
+    let routes = sell_base["routes"].as_array().unwrap();
+    assert_eq!(routes[0]["market"], orca.to_string());
+    assert!(close(&routes[0]["amount_out"], 5000.0 * 10.0 / 2010.0));
+    assert!(close(&sell_base["amount_out"], 5000.0 * 10.0 / 2010.0));
+    assert!(close(&routes[1]["amount_out"], 2000.0 * 9.975 / 1009.975));
+    assert!(close(&routes[1]["fee"], 0.025));
+
+    let sell_quote = app.get(&format!("/api/markets/{}-{}/quote?amount=10&input=quote", base, quote)).await;
+    assert_eq!(sell_quote["output_mint"], base.to_string());
+    assert_eq!(sell_quote["routes"][0]["market"], raydium.to_string());
+    assert!(close(&sell_quote["amount_out"], 1000.0 * 9.975 / 2009.975));
+
+    assert!(app.get(&format!("/api/markets/{}-{}/quote?amount=0", base, quote)).await.is_null());
+}
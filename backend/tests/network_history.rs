@@ -0,0 +1,82 @@
+mod support;
+
+use solana_explorer_backend::services::network_history;
+use support::TestApp;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sample_reads_every_metric_from_rpc() {
+    let app = TestApp::spawn().await;
+
+    let sample = network_history::take_sample(&app.state);
+
+    let value = |metric: &str| sample.iter().find(|(m, _)| *m == metric).map(|(_, v)| *v);
+    assert_eq!(value("tps"), Some(4000.0));
+    assert_eq!(value("non_vote_tps"), Some(1000.0));
+    assert_eq!(value("block_time"), Some(0.4));
+    assert_eq!(value("avg_fee"), Some(5000.0));
+    assert_eq!(value("active_accounts"), Some(6.0));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn recorded_samples_are_served_raw_and_rolled_up() {
+    let app = TestApp::spawn().await;
+    let now = chrono::Utc::now().timestamp();
+
+    assert_eq!(network_history::record_sample(&app.state).await.unwrap(), 5);
+    network_history::rollup(&app.db).await.unwrap();
+
+    let raw = app
+        .get(&format!("/api/network/history?metric=tps&from={}&to={}", now - 60, now + 60))
+        .await;
+    assert_eq!(raw["interval"], "raw");
+    let points = raw["points"].as_array().unwrap();
+    assert!(!points.is_empty());
+    assert!(points.iter().all(|p| p["avg"] == 4000.0));
+
+    let hourly = app
+        .get(&format!("/api/network/history?metric=tps&from={}&to={}&interval=1h", now - 3600, now + 60))
+        .await;
+    assert_eq!(hourly["interval"], "1h");
+    let last = hourly["points"].as_array().unwrap().last().cloned().unwrap();
+    assert_eq!(last["avg"], 4000.0);
+    assert!(last["samples"].as_i64().unwrap() >= 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn interval_defaults_to_the_span() {
+    let app = TestApp::spawn().await;
+    let now = chrono::Utc::now().timestamp();
+
+    let week = app
+        .get(&format!("/api/network/history?metric=block_time&from={}&to={}", now - 7 * 86400, now))
+        .await;
+    assert_eq!(week["interval"], "1h");
+
+    let year = app
+        .get(&format!("/api/network/history?metric=block_time&from={}&to={}", now - 365 * 86400, now))
+        .await;
+    assert_eq!(year["interval"], "1d");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_metrics_have_no_points() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/network/history?metric=gas").await;
+
+    assert_eq!(body["points"].as_array().unwrap().len(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn out_of_range_times_are_rejected() {
+    let app = TestApp::spawn().await;
+
+    for query in ["to=-8334601228800", "from=9223372036854775807"] {
+        let response = app.client
+            .get(format!("{}/api/network/history?metric=tps&{}", app.address, query))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+    }
+}
@@ -0,0 +1,383 @@
+mod support;
+
+use serde_json::{json, Value};
+use solana_explorer_backend::services::{block_follower, bubblegum, solana::TOKEN_PROGRAM_ID, token_metadata};
+use solana_sdk::{bs58, hash::{hashv, Hash}, pubkey::Pubkey, signature::Signature};
+use std::str::FromStr;
+use support::{account_info, borsh_string, set_block, token_account_data, TestApp, RECIPIENT, SLOT, WALLET};
+
+const MINT_SLOT: u64 = 249_990_000;
+
+/// An initialized mint with a supply of one at zero decimals.
+fn nft_mint_data() -> Vec<u8> {
+    let mut data = vec![0u8; 82];
+    data[36..44].copy_from_slice(&1u64.to_le_bytes());
+    data[45] = 1;
+    data
+}
+
+/// A `NonFungible` Metaplex metadata account with one verified creator and
+/// a verified collection.
+fn nft_metadata(mint: &Pubkey, creator: &Pubkey, collection: &Pubkey, uri: &str) -> Vec<u8> {
+    [
+        vec![4u8],
+        Pubkey::from_str(WALLET).unwrap().to_bytes().to_vec(),
+        mint.to_bytes().to_vec(),
+        borsh_string("Explorer Test NFT", 32),
+        borsh_string("XNFT", 10),
+        borsh_string(uri, 200),
+        500u16.to_le_bytes().to_vec(),
+        vec![1, 1, 0, 0, 0],
+        creator.to_bytes().to_vec(),
+        vec![1, 100],
+        // Primary sale, mutable, edition nonce, token standard.
+        vec![1, 1, 1, 255, 1, 0],
+        vec![1, 1],
+        collection.to_bytes().to_vec(),
+        vec![0; 40],
+    ]
+    .concat()
+}
+
+/// A jsonParsed token account of `owner` under the token program.
+fn parsed_token_account(owner: &str, mint: &Pubkey, amount: &str, decimals: u8) -> Value {
+    json!({
+        "pubkey": Pubkey::new_unique().to_string(),
+        "account": {
+            "data": {
+                "program": "spl-token",
+                "parsed": {
+                    "type": "account",
+                    "info": {
+                        "mint": mint.to_string(),
+                        "owner": owner,
+                        "state": "initialized",
+                        "isNative": false,
+                        "tokenAmount": { "amount": amount, "decimals": decimals, "uiAmount": 1.0, "uiAmountString": "1" },
+                    },
+                },
+                "space": 165,
+            },
+            "executable": false,
+            "lamports": 2039280,
+            "owner": TOKEN_PROGRAM_ID.to_string(),
+            "rentEpoch": 0,
+            "space": 165,
+        },
+    })
+}
+
+/// Runs the compressed NFT index alone over the block at `slot`.
+async fn index_bubblegum(app: &TestApp, slot: u64) -> usize {
+    let block = block_follower::fetch(&app.state, slot).unwrap();
+    bubblegum::index(&app.state.db, slot, &block).await.unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn address_nfts_are_grouped_by_collection_and_left_out_of_tokens() {
+    let app = TestApp::spawn().await;
+    let owner = Pubkey::new_unique().to_string();
+    let (nft, token, collection) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    sqlx::query("INSERT INTO nfts (mint, name) VALUES ($1, 'Explorer Test Collection')")
+        .bind(collection.to_string())
+        .execute(&app.db)
+        .await
+        .unwrap();
+    app.rpc.set(
+        &format!("getTokenAccountsByOwner.{}", owner),
+        json!({
+            "context": { "slot": SLOT },
+            "value": [
+                parsed_token_account(&owner, &nft, "1", 0),
+                parsed_token_account(&owner, &token, "5000", 3),
+            ],
+        }),
+    );
+    app.rpc.set(
+        "getMultipleAccounts",
+        json!({
+            "context": { "slot": SLOT },
+            "value": [
+                account_info(&TOKEN_PROGRAM_ID, &nft_mint_data())["value"],
+                account_info(
+                    &token_metadata::METADATA_PROGRAM_ID,
+                    &nft_metadata(&nft, &Pubkey::new_unique(), &collection, ""),
+                )["value"],
+            ],
+        }),
+    );
+
+    let details = app.get(&format!("/api/addresses/{}", owner)).await;
+
+    let tokens = details["tokens"].as_array().unwrap();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0]["mint"], token.to_string());
+    let collections = details["nfts"].as_array().unwrap();
+    assert_eq!(collections.len(), 1);
+    assert_eq!(collections[0]["collection"], collection.to_string());
+    assert_eq!(collections[0]["name"], "Explorer Test Collection");
+    let nfts = collections[0]["nfts"].as_array().unwrap();
+    assert_eq!(nfts.len(), 1);
+    assert_eq!(nfts[0]["mint"], nft.to_string());
+    assert_eq!(nfts[0]["name"], "Explorer Test NFT");
+    assert_eq!(nfts[0]["owner"], owner);
+    assert_eq!(nfts[0]["collection_verified"], true);
+    assert_eq!(nfts[0]["compressed"], false);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn nft_details_carry_attributes_and_ownership_history() {
+    let app = TestApp::spawn().await;
+    let (mint, creator, collection) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let recipient = Pubkey::from_str(RECIPIENT).unwrap();
+    let holder = Pubkey::new_unique();
+    let uri = app.rpc.file_url("metadata/nft.json");
+    app.rpc.set(&format!("getAccountInfo.{}", mint), account_info(&TOKEN_PROGRAM_ID, &nft_mint_data()));
+    app.rpc.set(
+        &format!("getAccountInfo.{}", token_metadata::metadata_key(&mint)),
+        account_info(&token_metadata::METADATA_PROGRAM_ID, &nft_metadata(&mint, &creator, &collection, &uri)),
+    );
+    app.rpc.set(
+        &format!("getTokenLargestAccounts.{}", mint),
+        json!({
+            "context": { "slot": SLOT },
+            "value": [{ "address": holder.to_string(), "amount": "1", "decimals": 0, "uiAmount": 1.0, "uiAmountString": "1" }],
+        }),
+    );
+    app.rpc.set(
+        "getMultipleAccounts",
+        json!({
+            "context": { "slot": SLOT },
+            "value": [account_info(&TOKEN_PROGRAM_ID, &token_account_data(&mint, &recipient, 1))["value"]],
+        }),
+    );
+    sqlx::query(
+        "INSERT INTO token_transfers
+            (signature, instruction_index, inner_index, slot, kind, mint, program, from_owner, to_owner, amount, decimals, ui_amount)
+         VALUES ($1, 0, -1, $2, 'transfer', $3, $4, $5, $6, 1, 0, 1)"
+    )
+    .bind(Signature::new_unique().to_string())
+    .bind(SLOT as i64)
+    .bind(mint.to_string())
+    .bind(TOKEN_PROGRAM_ID.to_string())
+    .bind(WALLET)
+    .bind(RECIPIENT)
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    let nft = app.get(&format!("/api/nfts/{}", mint)).await;
+
+    assert_eq!(nft["mint"], mint.to_string());
+    assert_eq!(nft["name"], "Explorer Test NFT");
+    assert_eq!(nft["image"], "https://example.com/xnft.png");
+    assert_eq!(nft["description"], "An NFT used by the explorer test suite.");
+    assert_eq!(nft["owner"], RECIPIENT);
+    assert_eq!(nft["update_authority"], WALLET);
+    assert_eq!(nft["seller_fee_basis_points"], 500);
+    assert_eq!(nft["creators"], json!([{ "address": creator.to_string(), "verified": true, "share": 100 }]));
+    // The entry without a trait type is skipped.
+    assert_eq!(
+        nft["attributes"],
+        json!([{ "trait_type": "Background", "value": "Blue" }, { "trait_type": "Level", "value": 3 }])
+    );
+    let history = nft["history"].as_array().unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["kind"], "transfer");
+    assert_eq!(history[0]["from"], WALLET);
+    assert_eq!(history[0]["to"], RECIPIENT);
+    assert!(nft["merkle_tree"].is_null());
+
+    let listed = app.get(&format!("/api/collections/{}", collection)).await;
+    assert_eq!(listed["total"], 1);
+    assert_eq!(listed["nfts"][0]["mint"], mint.to_string());
+
+    // A second view is served without re-reading the NFT from chain.
+    let reads = app.rpc.calls("getAccountInfo");
+    app.get(&format!("/api/nfts/{}", mint)).await;
+    assert_eq!(app.rpc.calls("getAccountInfo"), reads);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn fungible_mints_are_not_nfts() {
+    let app = TestApp::spawn().await;
+
+    let nft = app.get(&format!("/api/nfts/{}", support::MINT)).await;
+
+    assert!(nft.is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn collections_report_the_clamped_page_and_limit() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/collections/Nobody?page=0&limit=1000").await;
+
+    assert_eq!(body["page"], 1);
+    assert_eq!(body["limit"], 100);
+}
+
+fn discriminator(name: &str) -> Vec<u8> {
+    hashv(&[format!("global:{}", name).as_bytes()]).to_bytes()[..8].to_vec()
+}
+
+fn instruction(program: &Pubkey, accounts: &[&Pubkey], data: &[u8]) -> Value {
+    json!({
+        "programId": program.to_string(),
+        "accounts": accounts.iter().map(|key| key.to_string()).collect::<Vec<_>>(),
+        "data": bs58::encode(data).into_string(),
+        "stackHeight": null,
+    })
+}
+
+/// A transaction making `ix`, which invoked `inner`.
+fn bubblegum_tx(ix: Value, inner: Vec<Value>) -> Value {
+    json!({
+        "transaction": {
+            "signatures": [Signature::new_unique().to_string()],
+            "message": {
+                "accountKeys": [{ "pubkey": WALLET, "writable": true, "signer": true, "source": "transaction" }],
+                "recentBlockhash": "EG5HVD9aBAa1vEvQBZuGy5R6y8ikveZg4oMUf7a2jgge",
+                "instructions": [ix],
+            },
+        },
+        "meta": {
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5000,
+            "preBalances": [0],
+            "postBalances": [0],
+            "innerInstructions": [{ "index": 0, "instructions": inner }],
+            "logMessages": [],
+            "preTokenBalances": [],
+            "postTokenBalances": [],
+            "rewards": [],
+        },
+        "version": 0,
+    })
+}
+
+/// A depth-3 tree with room for 2 change logs, one leaf appended, `root` as
+/// its latest root and a one-level canopy.
+fn tree_data(authority: &Pubkey, root: [u8; 32]) -> Vec<u8> {
+    let depth = 3u32;
+    let mut data = vec![1u8, 0];
+    data.extend_from_slice(&2u32.to_le_bytes());
+    data.extend_from_slice(&depth.to_le_bytes());
+    data.extend_from_slice(authority.as_ref());
+    data.extend_from_slice(&SLOT.to_le_bytes());
+    data.extend_from_slice(&[0; 6]);
+    // Sequence number, active index, buffer size.
+    for value in [2u64, 1, 2] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    for change_log in 0..2 {
+        data.extend_from_slice(&if change_log == 1 { root } else { [0; 32] });
+        data.extend_from_slice(&vec![0; 32 * depth as usize + 8]);
+    }
+    // Rightmost proof, its leaf and index.
+    data.extend_from_slice(&vec![0; 32 * depth as usize + 32]);
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(&[0; 4]);
+    data.extend_from_slice(&[0; 64]);
+    data
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn compressed_nfts_are_indexed_from_bubblegum() {
+    let app = TestApp::spawn().await;
+    let (tree, authority, collection, creator) =
+        (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let (owner, new_owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let asset = bubblegum::asset_id(&tree, 0);
+    let uri = app.rpc.file_url("metadata/nft.json");
+
+    let args = [
+        borsh_string("Compressed #0", 0),
+        borsh_string("CNFT", 0),
+        borsh_string(&uri, 0),
+        250u16.to_le_bytes().to_vec(),
+        // Primary sale, mutable, no edition nonce, NonFungible.
+        vec![0, 1, 0, 1, 0],
+        vec![1, 0],
+        collection.to_bytes().to_vec(),
+        // No uses, token program version, one creator.
+        vec![0, 0, 1, 0, 0, 0],
+        creator.to_bytes().to_vec(),
+        vec![1, 100],
+    ]
+    .concat();
+    // LeafSchemaEvent V1 with a LeafSchema::V1: id, owner, delegate, nonce,
+    // data hash, creator hash, then the leaf hash.
+    let event = [
+        vec![1u8, 0, 0],
+        asset.to_bytes().to_vec(),
+        owner.to_bytes().to_vec(),
+        owner.to_bytes().to_vec(),
+        0u64.to_le_bytes().to_vec(),
+        vec![0; 96],
+    ]
+    .concat();
+    // Wrapped in AccountCompressionEvent::ApplicationData(V1 { application_data }).
+    let leaf = [vec![1u8, 0], (event.len() as u32).to_le_bytes().to_vec(), event].concat();
+    let mint = bubblegum_tx(
+        instruction(
+            &bubblegum::BUBBLEGUM_PROGRAM_ID,
+            &[&authority, &owner, &owner, &tree, &Pubkey::new_unique()],
+            &[discriminator("mint_to_collection_v1"), args].concat(),
+        ),
+        vec![instruction(&bubblegum::NOOP_PROGRAM_ID, &[], &leaf)],
+    );
+    let transfer = bubblegum_tx(
+        instruction(
+            &bubblegum::BUBBLEGUM_PROGRAM_ID,
+            &[&authority, &owner, &owner, &new_owner, &tree],
+            &[discriminator("transfer"), vec![0; 96], 0u64.to_le_bytes().to_vec(), vec![0; 4]].concat(),
+        ),
+        vec![],
+    );
+    set_block(&app, MINT_SLOT, 1_700_000_000, vec![mint]);
+    set_block(&app, MINT_SLOT + 1, 1_700_000_000, vec![transfer]);
+    app.rpc.set(
+        &format!("getAccountInfo.{}", tree),
+        account_info(&bubblegum::COMPRESSION_PROGRAM_ID, &tree_data(&authority, [7; 32])),
+    );
+
+    assert_eq!(index_bubblegum(&app, MINT_SLOT).await, 1);
+    assert_eq!(index_bubblegum(&app, MINT_SLOT + 1).await, 1);
+    // Re-indexing a block records nothing new.
+    assert_eq!(index_bubblegum(&app, MINT_SLOT + 1).await, 0);
+
+    let nft = app.get(&format!("/api/nfts/{}", asset)).await;
+
+    assert_eq!(nft["name"], "Compressed #0");
+    assert_eq!(nft["compressed"], true);
+    assert_eq!(nft["tree"], tree.to_string());
+    assert_eq!(nft["leaf_index"], 0);
+    assert_eq!(nft["owner"], new_owner.to_string());
+    assert_eq!(nft["delegate"], new_owner.to_string());
+    assert_eq!(nft["collection"], collection.to_string());
+    assert_eq!(nft["collection_verified"], true);
+    assert_eq!(nft["image"], "https://example.com/xnft.png");
+    assert_eq!(nft["attributes"][0]["trait_type"], "Background");
+    let history = nft["history"].as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["kind"], "transfer");
+    assert_eq!(history[0]["from"], owner.to_string());
+    assert_eq!(history[0]["to"], new_owner.to_string());
+    assert_eq!(history[1]["kind"], "mint");
+    assert_eq!(history[1]["to"], owner.to_string());
+    let merkle_tree = &nft["merkle_tree"];
+    assert_eq!(merkle_tree["max_depth"], 3);
+    assert_eq!(merkle_tree["max_buffer_size"], 2);
+    assert_eq!(merkle_tree["authority"], authority.to_string());
+    assert_eq!(merkle_tree["sequence_number"], 2);
+    assert_eq!(merkle_tree["leaves"], 1);
+    assert_eq!(merkle_tree["root"], Hash::new_from_array([7; 32]).to_string());
+    assert_eq!(merkle_tree["canopy_depth"], 1);
+
+    let details = app.get(&format!("/api/addresses/{}", new_owner)).await;
+    assert_eq!(details["nfts"][0]["nfts"][0]["mint"], asset.to_string());
+    let previous = app.get(&format!("/api/addresses/{}", owner)).await;
+    assert_eq!(previous["nfts"], json!([]));
+}
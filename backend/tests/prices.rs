@@ -0,0 +1,160 @@
+mod support;
+
+use serde_json::json;
+use solana_explorer_backend::{
+    services::{
+        prices::{self, PriceService, PriceSource, StubSource},
+        token_list,
+    },
+    AppState,
+};
+use std::sync::Arc;
+use support::{random_mint, TestApp, MINT, SLOT, WALLET};
+
+const WSOL: &str = "So11111111111111111111111111111111111111112";
+
+fn with_sources(app: &TestApp, prices: &[f64], mint: &str) -> AppState {
+    let sources = prices
+        .iter()
+        .enumerate()
+        .map(|(i, price)| {
+            let source = StubSource::new(&format!("stub-{}", i));
+            source.set(mint, *price);
+            Arc::new(source) as Arc<dyn PriceSource>
+        })
+        .collect();
+    AppState { prices: PriceService::new(sources), ..app.state.clone() }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn token_list_refresh_uses_the_aggregated_price() {
+    let app = TestApp::spawn().await;
+    app.prices.set(WSOL, 150.0);
+
+    token_list::refresh(&app.state).await.unwrap();
+    let body = app.get("/api/tokens?verified=true&limit=100").await;
+
+    let sol = body["tokens"].as_array().unwrap().iter().find(|t| t["mint"] == WSOL).unwrap();
+    assert_eq!(sol["price"], 150.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn outlier_quotes_are_rejected() {
+    let app = TestApp::spawn().await;
+    let mint = random_mint();
+    let state = with_sources(&app, &[100.0, 101.0, 250.0], &mint);
+
+    let price = prices::price(&state, &mint).await.unwrap();
+
+    assert_eq!(price.price, 100.5);
+    assert_eq!(price.sources, 2);
+    assert!(!price.stale);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sources_that_disagree_give_no_price() {
+    let app = TestApp::spawn().await;
+    let mint = random_mint();
+    let state = with_sources(&app, &[100.0, 200.0], &mint);
+
+    assert!(prices::price(&state, &mint).await.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn one_quote_is_not_enough_when_several_sources_are_configured() {
+    let app = TestApp::spawn().await;
+    let mint = random_mint();
+    let quoting = StubSource::new("stub-0");
+    quoting.set(&mint, 100.0);
+    let sources: Vec<Arc<dyn PriceSource>> = vec![Arc::new(quoting), Arc::new(StubSource::new("stub-1"))];
+    let state = AppState { prices: PriceService::new(sources), ..app.state.clone() };
+
+    assert!(prices::price(&state, &mint).await.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn price_change_compares_against_a_day_ago() {
+    let app = TestApp::spawn().await;
+    let mint = random_mint();
+    sqlx::query("INSERT INTO token_price_history (mint, price, recorded_at) VALUES ($1, 100.0, NOW() - INTERVAL '25 hours')")
+        .bind(&mint)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    app.prices.set(&mint, 110.0);
+
+    let price = prices::price(&app.state, &mint).await.unwrap();
+
+    assert_eq!(price.price, 110.0);
+    assert!((price.price_change_24h.unwrap() - 10.0).abs() < 1e-9);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn last_known_price_is_served_stale_when_sources_fail() {
+    let app = TestApp::spawn().await;
+    let mint = random_mint();
+    app.prices.set(&mint, 2.0);
+    prices::price(&app.state, &mint).await.unwrap();
+
+    app.prices.remove(&mint);
+    app.state.cache.purge("price:*").await.unwrap();
+    sqlx::query("UPDATE token_prices SET updated_at = NOW() - INTERVAL '10 minutes' WHERE mint = $1")
+        .bind(&mint)
+        .execute(&app.db)
+        .await
+        .unwrap();
+
+    let price = prices::price(&app.state, &mint).await.unwrap();
+    assert_eq!(price.price, 2.0);
+    assert!(price.stale);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn address_token_balances_have_usd_values() {
+    let app = TestApp::spawn().await;
+    app.prices.set(MINT, 4.0);
+    app.rpc.set(
+        &format!("getTokenAccountsByOwner.{}", WALLET),
+        json!({
+            "context": { "slot": SLOT },
+            "value": [{
+                "pubkey": "8bKqcpCdvxZsy5hgkY9w3Nv4EXmSAMaD7AFBFjmyRPey",
+                "account": {
+                    "data": {
+                        "program": "spl-token",
+                        "parsed": {
+                            "type": "account",
+                            "info": {
+                                "mint": MINT,
+                                "owner": WALLET,
+                                "state": "initialized",
+                                "isNative": false,
+                                "tokenAmount": {
+                                    "amount": "2500000",
+                                    "decimals": 6,
+                                    "uiAmount": 2.5,
+                                    "uiAmountString": "2.5",
+                                },
+                            },
+                        },
+                        "space": 165,
+                    },
+                    "executable": false,
+                    "lamports": 2039280,
+                    "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "rentEpoch": 0,
+                    "space": 165,
+                },
+            }],
+        }),
+    );
+
+    let details = app.get(&format!("/api/addresses/{}", WALLET)).await;
+
+    // Both token programs are queried, but the account is listed once.
+    let tokens = details["tokens"].as_array().unwrap();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0]["mint"], MINT);
+    assert_eq!(tokens[0]["balance"], "2500000");
+    assert_eq!(tokens[0]["usd_value"], 10.0);
+}
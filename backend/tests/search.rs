@@ -0,0 +1,120 @@
+mod support;
+
+use solana_explorer_backend::services::search_index;
+use support::TestApp;
+
+const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_finds_tokens_by_symbol() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/search?q=usdc").await;
+
+    assert_eq!(body["type"], "token");
+    assert_eq!(body["result"]["address"], USDC);
+    assert_eq!(body["result"]["symbol"], "USDC");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_ranks_exact_names_first_and_keeps_other_matches() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/search?q=jupiter").await;
+    let candidates = body["candidates"].as_array().unwrap();
+
+    assert_eq!(body["type"], "token");
+    assert_eq!(body["result"]["symbol"], "JUP");
+    assert!(candidates.iter().any(|c| c["type"] == "program" && c["result"]["name"] == "Jupiter Aggregator v6"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_tolerates_typos() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/search?q=jupitr").await;
+
+    assert_eq!(body["result"]["name"], "Jupiter");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn suggest_completes_prefixes_by_popularity() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/search/suggest?q=US").await;
+    let symbols: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e["symbol"].as_str())
+        .collect();
+
+    assert_eq!(&symbols[..2], ["USDC", "USDT"]);
+    assert!(app.get("/api/search/suggest?q=%25").await.as_array().unwrap().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn upserted_entries_are_searchable() {
+    let app = TestApp::spawn().await;
+    let name = format!("Label {}", uuid::Uuid::new_v4());
+
+    search_index::upsert(&app.db, "label", support::WALLET, &name, None, 5.0).await.unwrap();
+    search_index::upsert(&app.db, "label", support::WALLET, &name, None, 0.0).await.unwrap();
+
+    let body = app.get(&format!("/api/search/suggest?q={}", &name[..12])).await;
+    let entry = &body.as_array().unwrap()[0];
+    assert_eq!(entry["kind"], "label");
+    assert_eq!(entry["address"], support::WALLET);
+    assert_eq!(entry["popularity"], 5.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn listed_tokens_are_ranked_by_holders_and_liquidity() {
+    let app = TestApp::spawn().await;
+    let name = format!("Ranked {}", uuid::Uuid::new_v4());
+    let (small, large) = (uuid::Uuid::new_v4().simple().to_string(), uuid::Uuid::new_v4().simple().to_string());
+    for (mint, holders, liquidity) in [(&small, 10i64, 100.0), (&large, 1_000_000, 1e9)] {
+        sqlx::query("INSERT INTO tokens (mint, name, holders, liquidity) VALUES ($1, $2, $3, $4)")
+            .bind(mint)
+            .bind(&name)
+            .bind(holders)
+            .bind(liquidity)
+            .execute(&app.db)
+            .await
+            .unwrap();
+    }
+
+    search_index::rank_tokens(&app.db).await.unwrap();
+
+    let body = app.get(&format!("/api/search/suggest?q={}", name)).await;
+    let entries = body.as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["address"], *large);
+    assert!(entries[0]["popularity"].as_f64().unwrap() > 70.0);
+    assert!(entries[1]["popularity"].as_f64().unwrap() > 0.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn token_rankings_fall_when_holders_and_liquidity_do() {
+    let app = TestApp::spawn().await;
+    let name = format!("Rugged {}", uuid::Uuid::new_v4());
+    let mint = uuid::Uuid::new_v4().simple().to_string();
+    sqlx::query("INSERT INTO tokens (mint, name, holders, liquidity) VALUES ($1, $2, 1000000, 1e9)")
+        .bind(&mint)
+        .bind(&name)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    search_index::rank_tokens(&app.db).await.unwrap();
+
+    sqlx::query("UPDATE tokens SET holders = 0, liquidity = 0 WHERE mint = $1")
+        .bind(&mint)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    search_index::rank_tokens(&app.db).await.unwrap();
+
+    let body = app.get(&format!("/api/search/suggest?q={}", name)).await;
+    assert_eq!(body[0]["address"], *mint);
+    assert_eq!(body[0]["popularity"], 0.0);
+}
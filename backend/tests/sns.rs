@@ -0,0 +1,173 @@
+mod support;
+
+use serde_json::{json, Value};
+use solana_explorer_backend::services::sns;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use std::str::FromStr;
+use support::{account_info, TestApp, WALLET};
+
+fn wallet() -> Pubkey {
+    Pubkey::from_str(WALLET).unwrap()
+}
+
+/// A name-service account: parent, owner and class keys followed by `data`.
+fn name_account(parent: &Pubkey, owner: &Pubkey, data: &[u8]) -> Value {
+    let bytes = [parent.as_ref(), owner.as_ref(), &[0u8; 32], data].concat();
+    account_info(&sns::NAME_PROGRAM_ID, &bytes)
+}
+
+/// Registers `name`.sol to `owner`, including its reverse-lookup record.
+fn register(app: &TestApp, name: &str, owner: &Pubkey) -> Pubkey {
+    let key = sns::domain_key(name).unwrap();
+    app.rpc.set(&format!("getAccountInfo.{}", key), name_account(&sns::SOL_TLD, owner, &[]));
+
+    let mut reverse = (name.len() as u32).to_le_bytes().to_vec();
+    reverse.extend_from_slice(name.as_bytes());
+    app.rpc.set(
+        &format!("getAccountInfo.{}", sns::reverse_key(&key)),
+        name_account(&Pubkey::default(), &Pubkey::default(), &reverse),
+    );
+    key
+}
+
+fn set_owned_domains(app: &TestApp, domains: &[Pubkey]) {
+    let accounts: Vec<Value> = domains
+        .iter()
+        .map(|key| json!({
+            "pubkey": key.to_string(),
+            "account": {
+                "data": ["", "base64"],
+                "executable": false,
+                "lamports": 2039280,
+                "owner": sns::NAME_PROGRAM_ID.to_string(),
+                "rentEpoch": 0,
+            }
+        }))
+        .collect();
+    app.rpc.set(&format!("getProgramAccounts.{}", sns::NAME_PROGRAM_ID), json!(accounts));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn domains_resolve_to_their_owner() {
+    let app = TestApp::spawn().await;
+    register(&app, "toly", &wallet());
+
+    let body = app.get("/api/addresses/toly.sol").await;
+
+    assert_eq!(body["address"], WALLET);
+    assert_eq!(body["balance"], 12.5);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unregistered_domains_are_null() {
+    let app = TestApp::spawn().await;
+
+    assert!(app.get("/api/addresses/nobody-here.sol").await.is_null());
+}
+
+async fn queued(app: &TestApp, owner: &Pubkey) -> bool {
+    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM sns_scans WHERE owner = $1)")
+        .bind(owner.to_string())
+        .fetch_one(&app.db)
+        .await
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn address_details_show_the_primary_domain_once_scanned() {
+    let app = TestApp::spawn().await;
+    // The domain index is shared, so each run scans a fresh wallet.
+    let owner = Keypair::new().pubkey();
+    let short = register(&app, "toly", &owner);
+    let long = register(&app, "anatoly", &owner);
+    set_owned_domains(&app, &[long, short]);
+
+    let before = app.get(&format!("/api/addresses/{}", owner)).await;
+    assert!(before["domain"].is_null());
+    assert_eq!(app.rpc.calls("getProgramAccounts"), 0);
+    assert!(queued(&app, &owner).await);
+
+    assert_eq!(sns::scan(&app.state, &owner).await.unwrap().as_deref(), Some("toly.sol"));
+    let after = app.get(&format!("/api/addresses/{}", owner)).await;
+
+    assert_eq!(after["domain"], "toly.sol");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_favourite_domain_wins_over_owned_ones() {
+    let app = TestApp::spawn().await;
+    let short = register(&app, "toly", &wallet());
+    let favourite = register(&app, "anatoly", &wallet());
+    set_owned_domains(&app, &[short, favourite]);
+
+    let data = [&[1u8][..], favourite.as_ref()].concat();
+    app.rpc.set(
+        &format!("getAccountInfo.{}", sns::favourite_key(&wallet())),
+        account_info(&sns::NAME_OFFERS_ID, &data),
+    );
+
+    let body = app.get(&format!("/api/addresses/{}", WALLET)).await;
+
+    assert_eq!(body["domain"], "anatoly.sol");
+    assert_eq!(app.rpc.calls("getProgramAccounts"), 0);
+    assert_eq!(sns::scan(&app.state, &wallet()).await.unwrap().as_deref(), Some("anatoly.sol"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn wallets_without_domains_are_cached_and_queued() {
+    let app = TestApp::spawn().await;
+    let owner = Keypair::new().pubkey();
+
+    let body = app.get(&format!("/api/addresses/{}", owner)).await;
+
+    assert!(body["domain"].is_null());
+    assert_eq!(
+        app.state.cache.get::<Option<String>>(&format!("sns-primary:{}", owner)).await,
+        Some(None)
+    );
+    assert!(queued(&app, &owner).await);
+    assert_eq!(app.rpc.calls("getProgramAccounts"), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scans_drop_domains_the_wallet_no_longer_owns() {
+    let app = TestApp::spawn().await;
+    let owner = Keypair::new().pubkey();
+    let key = register(&app, "sold", &owner);
+    set_owned_domains(&app, &[key]);
+    sns::scan(&app.state, &owner).await.unwrap();
+
+    set_owned_domains(&app, &[]);
+    assert_eq!(sns::scan(&app.state, &owner).await.unwrap(), None);
+
+    let indexed: Option<String> =
+        sqlx::query_scalar("SELECT name FROM search_entries WHERE kind = 'domain' AND address = $1")
+            .bind(owner.to_string())
+            .fetch_optional(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(indexed, None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_resolves_domains_and_suggests_scanned_names() {
+    let app = TestApp::spawn().await;
+    // A wallet no other test registers domains for, so its index entry
+    // isn't renamed concurrently.
+    let owner = Pubkey::from_str("7MJwMFmc9qnGCwb2XpJZDk5kEzocyLfZ9iChfhfTfFDo").unwrap();
+    let key = register(&app, "mert", &owner);
+    set_owned_domains(&app, &[key]);
+
+    let body = app.get("/api/search?q=mert.sol").await;
+    assert_eq!(body["type"], "address");
+    assert_eq!(body["result"]["address"], owner.to_string());
+
+    sns::scan(&app.state, &owner).await.unwrap();
+
+    let suggestions = app.get("/api/search/suggest?q=mert.s").await;
+    assert!(suggestions
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|e| e["kind"] == "domain" && e["address"] == owner.to_string()));
+}
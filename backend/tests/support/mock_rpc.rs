@@ -0,0 +1,151 @@
+//! A fake Solana JSON-RPC node backed by fixture files.
+//!
+//! Every request is answered from `tests/fixtures/rpc`. A method is resolved by
+//! first looking for `<method>.<first param>.json` and falling back to
+//! `<method>.json`, so a test can pin a response to one slot, signature or
+//! address without touching the default. Missing fixtures produce a JSON-RPC
+//! "method not found" error, which is what a real node returns for unknown
+//! methods and which the handlers already treat as "no data".
+//!
+//! The same server also serves `GET /files/<path>` from `tests/fixtures/http`,
+//! standing in for off-chain hosts such as token metadata URIs.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[derive(Clone, Default)]
+struct MockState {
+    overrides: Arc<Mutex<HashMap<String, Value>>>,
+    calls: Arc<Mutex<Vec<String>>>,
+    delay: Arc<Mutex<Duration>>,
+}
+
+pub struct MockRpc {
+    pub url: String,
+    state: MockState,
+}
+
+impl MockRpc {
+    pub async fn start() -> Self {
+        let state = MockState::default();
+        let app = Router::new()
+            .route("/", post(handle))
+            .route("/files/*path", get(serve_file))
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr: SocketAddr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        Self {
+            url: format!("http://{}", addr),
+            state,
+        }
+    }
+
+    /// Replaces the response for `method` (optionally keyed by its first
+    /// param, e.g. `"getBlock.250000000"`) for the rest of the test.
+    pub fn set(&self, key: &str, result: Value) {
+        self.state.overrides.lock().unwrap().insert(key.to_string(), result);
+    }
+
+    /// Delays every response, so concurrent requests overlap in flight.
+    pub fn set_delay(&self, delay: Duration) {
+        *self.state.delay.lock().unwrap() = delay;
+    }
+
+    /// URL of a file under `tests/fixtures/http`.
+    pub fn file_url(&self, path: &str) -> String {
+        format!("{}/files/{}", self.url, path)
+    }
+
+    /// Number of times `method` has been called so far. Static files count
+    /// as `GET <path>`.
+    pub fn calls(&self, method: &str) -> usize {
+        self.state.calls.lock().unwrap().iter().filter(|m| *m == method).count()
+    }
+}
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/rpc")
+}
+
+fn first_param_key(params: &Value) -> Option<String> {
+    match params.get(0)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn lookup(state: &MockState, method: &str, params: &Value) -> Option<Value> {
+    let keyed = first_param_key(params).map(|p| format!("{}.{}", method, p));
+    let overrides = state.overrides.lock().unwrap();
+
+    for key in keyed.iter().map(String::as_str).chain(std::iter::once(method)) {
+        if let Some(value) = overrides.get(key) {
+            return Some(value.clone());
+        }
+        let path = fixtures_dir().join(format!("{}.json", key));
+        if let Ok(raw) = std::fs::read_to_string(&path) {
+            return Some(serde_json::from_str(&raw).unwrap_or_else(|e| {
+                panic!("invalid fixture {}: {}", path.display(), e)
+            }));
+        }
+    }
+
+    None
+}
+
+fn respond(state: &MockState, request: &Value) -> Value {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let method = request["method"].as_str().unwrap_or_default();
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    state.calls.lock().unwrap().push(method.to_string());
+
+    match lookup(state, method, &params) {
+        Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        None => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32601, "message": format!("Method not found: {}", method) }
+        }),
+    }
+}
+
+async fn handle(State(state): State<MockState>, Json(body): Json<Value>) -> Json<Value> {
+    let delay = *state.delay.lock().unwrap();
+    if !delay.is_zero() {
+        tokio::time::sleep(delay).await;
+    }
+
+    match body {
+        Value::Array(batch) => Json(Value::Array(
+            batch.iter().map(|request| respond(&state, request)).collect(),
+        )),
+        request => Json(respond(&state, &request)),
+    }
+}
+
+async fn serve_file(State(state): State<MockState>, Path(path): Path<String>) -> Result<Json<Value>, StatusCode> {
+    state.calls.lock().unwrap().push(format!("GET {}", path));
+
+    let raw = std::fs::read_to_string(fixtures_dir().join("../http").join(&path))
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    serde_json::from_str(&raw).map(Json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
@@ -0,0 +1,542 @@
+mod support;
+
+use serde_json::{json, Value};
+use solana_explorer_backend::services::{
+    solana::{TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID},
+    token_holders, token_metadata,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use std::str::FromStr;
+use support::{account_info, borsh_string, keyed_account, token_account_data, TestApp, MINT, RECIPIENT, SLOT, WALLET};
+
+const TOKEN_2022_MINT: &str = "AodEXr15YtXHHJz1ZNAFuA4dBcMneifDRgw2dc1bbdDv";
+
+/// An initialized mint with `authority` as both mint and freeze authority.
+fn mint_data(supply: u64, decimals: u8, authority: &Pubkey) -> Vec<u8> {
+    let coption = [&[1u8, 0, 0, 0][..], authority.as_ref()].concat();
+    [
+        coption.clone(),
+        supply.to_le_bytes().to_vec(),
+        vec![decimals, 1],
+        coption,
+    ]
+    .concat()
+}
+
+fn metaplex_data(mint: &Pubkey, name: &str, symbol: &str, uri: &str) -> Vec<u8> {
+    [
+        vec![4u8],
+        Pubkey::from_str(WALLET).unwrap().to_bytes().to_vec(),
+        mint.to_bytes().to_vec(),
+        borsh_string(name, 32),
+        borsh_string(symbol, 10),
+        borsh_string(uri, 200),
+        vec![0; 8],
+    ]
+    .concat()
+}
+
+/// A Token-2022 mint carrying an inline `TokenMetadata` extension.
+fn token_2022_data(mint: &Pubkey, name: &str, symbol: &str) -> Vec<u8> {
+    let metadata = [
+        vec![0u8; 32],
+        mint.to_bytes().to_vec(),
+        borsh_string(name, 0),
+        borsh_string(symbol, 0),
+        borsh_string("", 0),
+        0u32.to_le_bytes().to_vec(),
+    ]
+    .concat();
+
+    with_extensions(
+        mint_data(1_000_000, 9, &Pubkey::from_str(WALLET).unwrap()),
+        1,
+        &[(token_metadata::TOKEN_METADATA_EXTENSION, metadata)],
+    )
+}
+
+/// Token-2022 `data` (a mint for account type 1, a token account for 2)
+/// followed by the given `(type, value)` extensions.
+fn with_extensions(mut data: Vec<u8>, account_type: u8, extensions: &[(u16, Vec<u8>)]) -> Vec<u8> {
+    data.resize(165, 0);
+    data.push(account_type);
+    for (kind, value) in extensions {
+        data.extend_from_slice(&kind.to_le_bytes());
+        data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        data.extend_from_slice(value);
+    }
+    data
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_token_reads_the_mint_and_metaplex_metadata() {
+    let app = TestApp::spawn().await;
+    let mint = Pubkey::from_str(MINT).unwrap();
+    let uri = app.rpc.file_url("metadata/token.json");
+    app.rpc.set(
+        &format!("getAccountInfo.{}", token_metadata::metadata_key(&mint)),
+        account_info(&token_metadata::METADATA_PROGRAM_ID, &metaplex_data(&mint, "Explorer Test Token", "XTT", &uri)),
+    );
+
+    let token = app.get(&format!("/api/tokens/{}", MINT)).await;
+
+    assert_eq!(token["program"], TOKEN_PROGRAM_ID.to_string());
+    assert_eq!(token["supply"], "1000000000000");
+    assert_eq!(token["decimals"], 6);
+    assert_eq!(token["name"], "Explorer Test Token");
+    assert_eq!(token["symbol"], "XTT");
+    assert_eq!(token["uri"], uri);
+    assert_eq!(token["logo_uri"], "https://example.com/xtt.png");
+    assert_eq!(token["description"], "A token used by the explorer test suite.");
+    assert_eq!(token["update_authority"], WALLET);
+    assert_eq!(token["metadata_source"], "metaplex");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_token_prefers_token_2022_inline_metadata() {
+    let app = TestApp::spawn().await;
+    let mint = Pubkey::from_str(TOKEN_2022_MINT).unwrap();
+    app.rpc.set(
+        &format!("getAccountInfo.{}", TOKEN_2022_MINT),
+        account_info(&TOKEN_2022_PROGRAM_ID, &token_2022_data(&mint, "Inline Token", "INL")),
+    );
+
+    let token = app.get(&format!("/api/tokens/{}", TOKEN_2022_MINT)).await;
+
+    assert_eq!(token["program"], TOKEN_2022_PROGRAM_ID.to_string());
+    assert_eq!(token["decimals"], 9);
+    assert_eq!(token["mint_authority"], WALLET);
+    assert_eq!(token["freeze_authority"], WALLET);
+    assert_eq!(token["name"], "Inline Token");
+    assert_eq!(token["symbol"], "INL");
+    assert!(token["uri"].is_null());
+    assert!(token["update_authority"].is_null());
+    assert_eq!(token["metadata_source"], "token-2022");
+    // Inline metadata means the Metaplex account is never read.
+    assert_eq!(app.rpc.calls("getAccountInfo"), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn token_details_are_cached() {
+    let app = TestApp::spawn().await;
+    let mint = Pubkey::from_str(MINT).unwrap();
+    let uri = app.rpc.file_url("metadata/token.json");
+    app.rpc.set(
+        &format!("getAccountInfo.{}", token_metadata::metadata_key(&mint)),
+        account_info(&token_metadata::METADATA_PROGRAM_ID, &metaplex_data(&mint, "Explorer Test Token", "XTT", &uri)),
+    );
+
+    app.get(&format!("/api/tokens/{}", MINT)).await;
+    let token = app.get(&format!("/api/tokens/{}", MINT)).await;
+
+    assert_eq!(token["logo_uri"], "https://example.com/xtt.png");
+    assert_eq!(app.rpc.calls("getAccountInfo"), 2);
+    assert_eq!(app.rpc.calls("GET metadata/token.json"), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_token_is_null_for_non_mints() {
+    let app = TestApp::spawn().await;
+
+    assert!(app.get(&format!("/api/tokens/{}", WALLET)).await.is_null());
+    assert!(app.get("/api/tokens/not-a-mint").await.is_null());
+}
+
+fn largest_accounts(accounts: &[(Pubkey, u64)], decimals: u8) -> Value {
+    let value: Vec<Value> = accounts
+        .iter()
+        .map(|(address, amount)| {
+            let ui_amount = *amount as f64 / 10f64.powi(decimals as i32);
+            json!({
+                "address": address.to_string(),
+                "amount": amount.to_string(),
+                "decimals": decimals,
+                "uiAmount": ui_amount,
+                "uiAmountString": ui_amount.to_string(),
+            })
+        })
+        .collect();
+    json!({ "context": { "slot": SLOT }, "value": value })
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn token_holders_report_owners_and_share_of_supply() {
+    let app = TestApp::spawn().await;
+    let mint = Pubkey::from_str(MINT).unwrap();
+    let (wallet, recipient) = (Pubkey::from_str(WALLET).unwrap(), Pubkey::from_str(RECIPIENT).unwrap());
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+    app.rpc.set(
+        &format!("getTokenLargestAccounts.{}", MINT),
+        largest_accounts(&[(first, 600_000_000_000), (second, 300_000_000_000)], 6),
+    );
+    app.rpc.set(
+        "getMultipleAccounts",
+        json!({
+            "context": { "slot": SLOT },
+            "value": [
+                account_info(&TOKEN_PROGRAM_ID, &token_account_data(&mint, &wallet, 600_000_000_000))["value"],
+                account_info(&TOKEN_PROGRAM_ID, &token_account_data(&mint, &recipient, 300_000_000_000))["value"],
+            ],
+        }),
+    );
+
+    let response = app.get(&format!("/api/tokens/{}/holders", MINT)).await;
+
+    let holders = response["holders"].as_array().unwrap();
+    assert_eq!(holders.len(), 2);
+    assert_eq!(holders[0]["address"], first.to_string());
+    assert_eq!(holders[0]["owner"], WALLET);
+    assert_eq!(holders[0]["amount"], "600000000000");
+    assert_eq!(holders[0]["ui_amount"], 600000.0);
+    assert_eq!(holders[0]["share"], 0.6);
+    assert_eq!(holders[1]["owner"], RECIPIENT);
+    // MINT is never indexed by the tests, so there is no count yet.
+    assert!(response["holder_count"].is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn indexed_mints_report_holder_counts() {
+    let app = TestApp::spawn().await;
+    let mint = Pubkey::from_str(TOKEN_2022_MINT).unwrap();
+    let (wallet, recipient) = (Pubkey::from_str(WALLET).unwrap(), Pubkey::from_str(RECIPIENT).unwrap());
+    let accounts = [
+        (Pubkey::new_unique(), wallet, 600_000),
+        (Pubkey::new_unique(), recipient, 300_000),
+        (Pubkey::new_unique(), wallet, 100_000),
+        (Pubkey::new_unique(), Pubkey::new_unique(), 0),
+    ];
+    app.rpc.set(
+        &format!("getAccountInfo.{}", TOKEN_2022_MINT),
+        account_info(&TOKEN_2022_PROGRAM_ID, &token_2022_data(&mint, "Inline Token", "INL")),
+    );
+    app.rpc.set(
+        &format!("getProgramAccounts.{}", TOKEN_2022_PROGRAM_ID),
+        Value::Array(
+            accounts
+                .iter()
+                .map(|(key, owner, amount)| {
+                    keyed_account(key, &TOKEN_2022_PROGRAM_ID, &token_account_data(&mint, owner, *amount))
+                })
+                .collect(),
+        ),
+    );
+    app.rpc.set(
+        &format!("getTokenLargestAccounts.{}", TOKEN_2022_MINT),
+        largest_accounts(&[(accounts[0].0, 600_000), (accounts[1].0, 300_000)], 9),
+    );
+
+    // Two wallets hold a balance; the empty account doesn't count.
+    let count = token_holders::index_mint(&app.state, &mint).await.unwrap();
+    assert_eq!(count, Some(2));
+
+    let response = app.get(&format!("/api/tokens/{}/holders", TOKEN_2022_MINT)).await;
+    assert_eq!(response["holder_count"], 2);
+    assert!(response["indexed_at"].is_string());
+    assert_eq!(response["holders"][0]["owner"], WALLET);
+    assert_eq!(response["holders"][1]["owner"], RECIPIENT);
+    // Owners of indexed accounts come from the index, not RPC.
+    assert_eq!(app.rpc.calls("getMultipleAccounts"), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn token_holders_are_null_for_non_mints() {
+    let app = TestApp::spawn().await;
+
+    assert!(app.get(&format!("/api/tokens/{}/holders", WALLET)).await.is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn token_2022_mint_extensions_are_decoded_and_flagged() {
+    let app = TestApp::spawn().await;
+    let mint = Keypair::new().pubkey();
+    let wallet = Pubkey::from_str(WALLET).unwrap();
+    // No fee authority and a 0.5% fee, capped at 1 token.
+    let transfer_fee = [
+        vec![0u8; 64],
+        7u64.to_le_bytes().to_vec(),
+        [0u64.to_le_bytes(), 0u64.to_le_bytes()].concat(),
+        0u16.to_le_bytes().to_vec(),
+        [500u64.to_le_bytes(), 1_000_000_000u64.to_le_bytes()].concat(),
+        50u16.to_le_bytes().to_vec(),
+    ]
+    .concat();
+    app.rpc.set(
+        &format!("getAccountInfo.{}", mint),
+        account_info(
+            &TOKEN_2022_PROGRAM_ID,
+            &with_extensions(
+                mint_data(1_000_000, 9, &wallet),
+                1,
+                &[(1, transfer_fee), (3, wallet.to_bytes().to_vec()), (12, wallet.to_bytes().to_vec())],
+            ),
+        ),
+    );
+
+    let token = app.get(&format!("/api/tokens/{}", mint)).await;
+
+    let extensions = token["extensions"].as_array().unwrap();
+    let names: Vec<&str> = extensions.iter().map(|e| e["extension"].as_str().unwrap()).collect();
+    assert_eq!(names, ["transferFeeConfig", "mintCloseAuthority", "permanentDelegate"]);
+    assert_eq!(extensions[0]["state"]["withheldAmount"], 7);
+    assert_eq!(extensions[0]["state"]["newerTransferFee"]["transferFeeBasisPoints"], 50);
+    assert!(extensions[0]["state"]["transferFeeConfigAuthority"].is_null());
+    assert_eq!(extensions[0]["risky"], true);
+    assert_eq!(extensions[1]["state"]["closeAuthority"], WALLET);
+    assert_eq!(extensions[1]["risky"], false);
+    assert_eq!(extensions[2]["state"]["delegate"], WALLET);
+    assert_eq!(extensions[2]["risky"], true);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn address_token_balances_carry_extensions_and_risks() {
+    let app = TestApp::spawn().await;
+    let (mint, account) = (Keypair::new().pubkey(), Keypair::new().pubkey());
+    let wallet = Pubkey::from_str(WALLET).unwrap();
+    app.rpc.set(
+        &format!("getTokenAccountsByOwner.{}", WALLET),
+        json!({
+            "context": { "slot": SLOT },
+            "value": [{
+                "pubkey": account.to_string(),
+                "account": {
+                    "data": {
+                        "program": "spl-token-2022",
+                        "parsed": {
+                            "type": "account",
+                            "info": {
+                                "mint": mint.to_string(),
+                                "owner": WALLET,
+                                "state": "initialized",
+                                "isNative": false,
+                                "tokenAmount": { "amount": "5000", "decimals": 3, "uiAmount": 5.0, "uiAmountString": "5" },
+                            },
+                        },
+                        "space": 175,
+                    },
+                    "executable": false,
+                    "lamports": 2039280,
+                    "owner": TOKEN_2022_PROGRAM_ID.to_string(),
+                    "rentEpoch": 0,
+                    "space": 175,
+                },
+            }],
+        }),
+    );
+    app.rpc.set(
+        "getMultipleAccounts",
+        json!({
+            "context": { "slot": SLOT },
+            "value": [
+                account_info(
+                    &TOKEN_2022_PROGRAM_ID,
+                    &with_extensions(token_account_data(&mint, &wallet, 5000), 2, &[(7, vec![])]),
+                )["value"],
+                account_info(
+                    &TOKEN_2022_PROGRAM_ID,
+                    &with_extensions(mint_data(1_000_000, 3, &wallet), 1, &[(9, vec![])]),
+                )["value"],
+            ],
+        }),
+    );
+
+    let details = app.get(&format!("/api/addresses/{}", WALLET)).await;
+
+    let tokens = details["tokens"].as_array().unwrap();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0]["extensions"][0]["extension"], "immutableOwner");
+    assert!(tokens[0]["extensions"][0]["state"].is_null());
+    // The mint's non-transferable extension makes the holding risky.
+    assert_eq!(tokens[0]["risks"], json!(["nonTransferable"]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn risk_report_flags_a_fresh_look_alike_token() {
+    let app = TestApp::spawn().await;
+    let mint = Keypair::new().pubkey();
+    let wallet = Pubkey::from_str(WALLET).unwrap();
+    app.rpc.set(&format!("getAccountInfo.{}", mint), account_info(&TOKEN_PROGRAM_ID, &mint_data(1_000_000, 6, &wallet)));
+    app.rpc.set(
+        &format!("getAccountInfo.{}", token_metadata::metadata_key(&mint)),
+        account_info(&token_metadata::METADATA_PROGRAM_ID, &metaplex_data(&mint, "USD Coin", "usdc", "")),
+    );
+    app.rpc.set(
+        &format!("getTokenLargestAccounts.{}", mint),
+        largest_accounts(&[(Pubkey::new_unique(), 900_000)], 6),
+    );
+    app.rpc.set(
+        &format!("getSignaturesForAddress.{}", mint),
+        json!([{
+            "signature": Keypair::new().sign_message(b"mint").to_string(),
+            "slot": SLOT,
+            "err": null,
+            "memo": null,
+            "blockTime": chrono::Utc::now().timestamp() - 3600,
+            "confirmationStatus": "finalized",
+        }]),
+    );
+
+    let risk = app.get(&format!("/api/tokens/{}/risk", mint)).await;
+
+    let flags: Vec<&str> = risk["flags"].as_array().unwrap().iter().map(|f| f["flag"].as_str().unwrap()).collect();
+    assert_eq!(
+        flags,
+        ["mint_authority", "freeze_authority", "holder_concentration", "low_liquidity", "new_token", "impersonates_verified"]
+    );
+    assert!(risk["flags"][5]["detail"].as_str().unwrap().contains("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"));
+    assert_eq!(risk["score"], 100);
+    assert_eq!(risk["verified"], false);
+    assert_eq!(risk["top_holders_share"], 0.9);
+
+    let (stored,): (Vec<String>,) = sqlx::query_as("SELECT risk_flags FROM tokens WHERE mint = $1")
+        .bind(mint.to_string())
+        .fetch_one(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(stored, flags);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn risk_report_sees_through_homoglyphs_in_symbols_and_names() {
+    let app = TestApp::spawn().await;
+    let wallet = Pubkey::from_str(WALLET).unwrap();
+    // A Cyrillic "В" and "о" in the symbol; a Cyrillic "і" and padding in the name.
+    for (name, symbol, original) in [
+        ("Dog Coin", "Воnk", "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263"),
+        (" Jupіter\u{200b}", "JUPX", "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN"),
+    ] {
+        let mint = Keypair::new().pubkey();
+        app.rpc.set(&format!("getAccountInfo.{}", mint), account_info(&TOKEN_PROGRAM_ID, &mint_data(1_000_000, 6, &wallet)));
+        app.rpc.set(
+            &format!("getAccountInfo.{}", token_metadata::metadata_key(&mint)),
+            account_info(&token_metadata::METADATA_PROGRAM_ID, &metaplex_data(&mint, name, symbol, "")),
+        );
+
+        let risk = app.get(&format!("/api/tokens/{}/risk", mint)).await;
+
+        let impersonation = risk["flags"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|f| f["flag"] == "impersonates_verified")
+            .unwrap_or_else(|| panic!("{} / {} was not flagged", name, symbol));
+        assert!(impersonation["detail"].as_str().unwrap().contains(original));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn established_tokens_are_aged_once_from_a_single_page() {
+    let app = TestApp::spawn().await;
+    let mint = Keypair::new().pubkey();
+    let wallet = Pubkey::from_str(WALLET).unwrap();
+    app.rpc.set(&format!("getAccountInfo.{}", mint), account_info(&TOKEN_PROGRAM_ID, &mint_data(1_000_000, 6, &wallet)));
+    app.rpc.set(
+        &format!("getAccountInfo.{}", token_metadata::metadata_key(&mint)),
+        account_info(&token_metadata::METADATA_PROGRAM_ID, &metaplex_data(&mint, "Old Coin", "OLDC", "")),
+    );
+    // A full page of signatures, all a month old.
+    let month_ago = chrono::Utc::now().timestamp() - 30 * 86400;
+    let page: Vec<Value> = (0..1000)
+        .map(|i| json!({
+            "signature": Keypair::new().sign_message(&[i as u8]).to_string(),
+            "slot": SLOT,
+            "err": null,
+            "memo": null,
+            "blockTime": month_ago,
+            "confirmationStatus": "finalized",
+        }))
+        .collect();
+    app.rpc.set(&format!("getSignaturesForAddress.{}", mint), json!(page));
+
+    let first = app.get(&format!("/api/tokens/{}/risk", mint)).await;
+    app.state.cache.purge("token-risk:*").await.unwrap();
+    let second = app.get(&format!("/api/tokens/{}/risk", mint)).await;
+
+    assert_eq!(app.rpc.calls("getSignaturesForAddress"), 1);
+    assert_eq!(first["first_activity"], second["first_activity"]);
+    assert!(second["flags"].as_array().unwrap().iter().all(|f| f["flag"] != "new_token"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn token_list_reports_the_clamped_page_and_limit() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/tokens?page=0&limit=2147483647").await;
+
+    assert_eq!(body["page"], 1);
+    assert_eq!(body["limit"], 100);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn verified_token_lists_are_imported() {
+    let app = TestApp::spawn().await;
+    let admin = app.sign_up_admin().await;
+    let mint = Keypair::new().pubkey().to_string();
+    let list = json!({
+        "name": "Verified",
+        "tokens": [{ "address": mint, "symbol": "IMPT", "name": "Imported", "decimals": 6, "logoURI": "https://example.com/impt.png" }],
+    });
+
+    let response = app.client
+        .post(format!("{}/api/admin/tokens/import", app.address))
+        .bearer_auth(&admin)
+        .body(list.to_string())
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let (verified, symbol): (bool, String) = sqlx::query_as("SELECT verified, symbol FROM tokens WHERE mint = $1")
+        .bind(&mint)
+        .fetch_one(&app.db)
+        .await
+        .unwrap();
+    // Imported tokens would crowd the verified listing other tests page through.
+    sqlx::query("DELETE FROM tokens WHERE mint = $1").bind(&mint).execute(&app.db).await.unwrap();
+    assert!(verified);
+    assert_eq!(symbol, "IMPT");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn verified_token_lists_with_an_invalid_mint_write_nothing() {
+    let app = TestApp::spawn().await;
+    let admin = app.sign_up_admin().await;
+    let mint = Keypair::new().pubkey().to_string();
+    let list = json!([{ "address": mint, "symbol": "OK" }, { "address": "not-a-mint", "symbol": "BAD" }]);
+
+    let response = app.client
+        .post(format!("{}/api/admin/tokens/import", app.address))
+        .bearer_auth(&admin)
+        .body(list.to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tokens WHERE mint = $1")
+        .bind(&mint)
+        .fetch_one(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn verified_token_imports_require_an_admin() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.sign_up().await;
+    let mint = Keypair::new().pubkey().to_string();
+    let list = json!([{ "address": mint, "symbol": "SCAM" }]);
+    let url = format!("{}/api/admin/tokens/import", app.address);
+
+    let anonymous = app.client.post(&url).body(list.to_string()).send().await.unwrap();
+    assert_eq!(anonymous.status(), 401);
+    let user = app.client.post(&url).bearer_auth(&token).body(list.to_string()).send().await.unwrap();
+    assert_eq!(user.status(), 403);
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tokens WHERE mint = $1")
+        .bind(&mint)
+        .fetch_one(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
@@ -0,0 +1,192 @@
+mod support;
+
+use serde_json::{json, Value};
+use solana_explorer_backend::services::{block_follower, transfers};
+use solana_sdk::{signature::Keypair, signer::Signer};
+use support::{set_block, token_balance, TestApp, RECIPIENT, WALLET};
+
+const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const BLOCK_SLOT: u64 = 250_000_100;
+const BLOCK_TIME: i64 = 1_708_000_000;
+
+fn random_key() -> String {
+    Keypair::new().pubkey().to_string()
+}
+
+fn random_signature() -> String {
+    Keypair::new().sign_message(b"transfer").to_string()
+}
+
+fn token_ix(kind: &str, info: Value) -> Value {
+    json!({
+        "program": "spl-token",
+        "programId": TOKEN_PROGRAM,
+        "parsed": { "type": kind, "info": info },
+        "stackHeight": null,
+    })
+}
+
+fn parsed_tx(signature: &str, keys: &[&str], instructions: Value, inner: Value, balances: Value, err: Value) -> Value {
+    json!({
+        "transaction": {
+            "signatures": [signature],
+            "message": {
+                "accountKeys": keys
+                    .iter()
+                    .map(|key| json!({ "pubkey": key, "writable": true, "signer": false, "source": "transaction" }))
+                    .collect::<Vec<_>>(),
+                "recentBlockhash": "EG5HVD9aBAa1vEvQBZuGy5R6y8ikveZg4oMUf7a2jgge",
+                "instructions": instructions,
+            },
+        },
+        "meta": {
+            "err": err,
+            "status": if err.is_null() { json!({ "Ok": null }) } else { json!({ "Err": err }) },
+            "fee": 5000,
+            "preBalances": keys.iter().map(|_| 0).collect::<Vec<_>>(),
+            "postBalances": keys.iter().map(|_| 0).collect::<Vec<_>>(),
+            "innerInstructions": inner,
+            "logMessages": [],
+            "preTokenBalances": balances,
+            "postTokenBalances": balances,
+            "rewards": [],
+        },
+        "version": 0,
+    })
+}
+
+/// A block with one successful transaction that transfers 2.5 tokens from
+/// WALLET to RECIPIENT at the top level, then mints 1 and burns 0.5 in inner
+/// instructions, and one failed transfer.
+fn seed_block(app: &TestApp, mint: &str) {
+    let (source, destination, authority) = (random_key(), random_key(), random_key());
+    let keys = [WALLET, source.as_str(), destination.as_str(), mint, TOKEN_PROGRAM];
+    let balances = json!([
+        token_balance(1, mint, WALLET, 10_000_000),
+        token_balance(2, mint, RECIPIENT, 0),
+    ]);
+
+    let ok = parsed_tx(
+        &random_signature(),
+        &keys,
+        json!([token_ix("transferChecked", json!({
+            "source": source,
+            "destination": destination,
+            "mint": mint,
+            "authority": WALLET,
+            "tokenAmount": { "amount": "2500000", "decimals": 6, "uiAmount": 2.5, "uiAmountString": "2.5" },
+        }))]),
+        json!([{ "index": 0, "instructions": [
+            token_ix("mintTo", json!({ "mint": mint, "account": source, "mintAuthority": authority, "amount": "1000000" })),
+            token_ix("burn", json!({ "mint": mint, "account": destination, "authority": RECIPIENT, "amount": "500000" })),
+        ]}]),
+        balances.clone(),
+        Value::Null,
+    );
+    let failed = parsed_tx(
+        &random_signature(),
+        &keys,
+        json!([token_ix("transfer", json!({
+            "source": source,
+            "destination": destination,
+            "authority": WALLET,
+            "amount": "7000000",
+        }))]),
+        json!([]),
+        balances,
+        json!({ "InstructionError": [0, { "Custom": 1 }] }),
+    );
+
+    set_block(app, BLOCK_SLOT, BLOCK_TIME, vec![ok, failed]);
+}
+
+async fn total(app: &TestApp, mint: &str, query: &str) -> Value {
+    app.get(&format!("/api/tokens/{}/transfers?{}", mint, query)).await["total"].clone()
+}
+
+/// Runs the transfer index alone over the block at `slot`.
+async fn index_transfers(app: &TestApp, slot: u64) -> usize {
+    let block = block_follower::fetch(&app.state, slot).unwrap();
+    transfers::index(&app.state.db, slot, &block).await.unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transfers_mints_and_burns_are_indexed() {
+    let app = TestApp::spawn().await;
+    let mint = random_key();
+    seed_block(&app, &mint);
+
+    assert_eq!(index_transfers(&app, BLOCK_SLOT).await, 3);
+
+    let body = app.get(&format!("/api/tokens/{}/transfers", mint)).await;
+    assert_eq!(body["total"], 3);
+    let transfers = body["transfers"].as_array().unwrap();
+    let kinds: Vec<&str> = transfers.iter().map(|t| t["kind"].as_str().unwrap()).collect();
+    assert_eq!(kinds, ["transfer", "mint", "burn"]);
+
+    let transfer = &transfers[0];
+    assert_eq!(transfer["inner_index"], -1);
+    assert_eq!(transfer["slot"], BLOCK_SLOT);
+    assert_eq!(transfer["from_owner"], WALLET);
+    assert_eq!(transfer["to_owner"], RECIPIENT);
+    assert_eq!(transfer["amount"], "2500000");
+    assert_eq!(transfer["ui_amount"], 2.5);
+    assert_eq!(transfer["block_time"], "2024-02-15T12:26:40Z");
+    assert!(transfers[1]["from_account"].is_null());
+    assert!(transfers[2]["to_account"].is_null());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reindexing_a_block_records_nothing_new() {
+    let app = TestApp::spawn().await;
+    let mint = random_key();
+    seed_block(&app, &mint);
+
+    index_transfers(&app, BLOCK_SLOT).await;
+    index_transfers(&app, BLOCK_SLOT).await;
+
+    let body = app.get(&format!("/api/tokens/{}/transfers", mint)).await;
+    assert_eq!(body["total"], 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transfers_report_the_clamped_page_and_limit() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get(&format!("/api/tokens/{}/transfers?page=0&limit=1000", random_key())).await;
+
+    assert_eq!(body["page"], 1);
+    assert_eq!(body["limit"], 100);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transfers_filter_by_owner_amount_and_time() {
+    let app = TestApp::spawn().await;
+    let mint = random_key();
+    seed_block(&app, &mint);
+    index_transfers(&app, BLOCK_SLOT).await;
+
+    assert_eq!(total(&app, &mint, &format!("from={}", WALLET)).await, 1);
+    // Only the mint credits an account owned by WALLET.
+    assert_eq!(total(&app, &mint, &format!("to={}", WALLET)).await, 1);
+    assert_eq!(total(&app, &mint, "min_amount=1").await, 2);
+    assert_eq!(total(&app, &mint, "max_amount=0.5").await, 1);
+    assert_eq!(total(&app, &mint, &format!("start_time={}", BLOCK_TIME + 1)).await, 0);
+    assert_eq!(total(&app, &mint, &format!("start_time={}&end_time={}", BLOCK_TIME, BLOCK_TIME)).await, 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn the_block_follower_fetches_each_block_once_and_reports_missing_ones() {
+    let app = TestApp::spawn().await;
+    let mint = random_key();
+    seed_block(&app, &mint);
+
+    assert_eq!(block_follower::index_block(&app.state, BLOCK_SLOT).await.unwrap(), 3);
+    assert_eq!(app.rpc.calls("getBlock"), 1);
+    assert_eq!(total(&app, &mint, "").await, 3);
+
+    // A block the node can't return is an error rather than an empty block.
+    app.rpc.set(&format!("getBlock.{}", BLOCK_SLOT + 1), json!(null));
+    let missing = block_follower::index_block(&app.state, BLOCK_SLOT + 1).await;
+    assert!(matches!(missing, Err(block_follower::IndexError::Rpc(_))));
+}
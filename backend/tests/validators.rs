@@ -0,0 +1,68 @@
+mod support;
+
+use support::{TestApp, LEADER, VOTE_ACCOUNT};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn validators_are_sorted_by_stake_by_default() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/validators").await;
+
+    assert_eq!(body["total"], 3);
+    let validators = body["validators"].as_array().unwrap();
+    assert_eq!(validators[0]["vote_pubkey"], VOTE_ACCOUNT);
+    assert_eq!(validators[0]["identity"], LEADER);
+    assert_eq!(validators[0]["activated_stake"], 6000.0);
+    assert_eq!(validators[0]["stake_share"], 60.0);
+    assert_eq!(validators[0]["skip_rate"], 5.0);
+    assert_eq!(validators[0]["version"], "1.17.26");
+    assert_eq!(validators[0]["delinquent"], false);
+    assert_eq!(validators[2]["delinquent"], true);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn validators_can_be_sorted_and_paged() {
+    let app = TestApp::spawn().await;
+
+    let body = app
+        .get("/api/validators?sort=commission&order=asc&page=2&limit=1")
+        .await;
+
+    let validators = body["validators"].as_array().unwrap();
+    assert_eq!(validators.len(), 1);
+    assert_eq!(validators[0]["commission"], 10);
+    assert_eq!(body["total"], 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn huge_pages_and_limits_are_clamped() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get("/api/validators?page=2147483647&limit=2147483647").await;
+
+    assert!(body["validators"].as_array().unwrap().is_empty());
+    assert_eq!(body["limit"], 100);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn delinquent_validator_has_no_version_and_full_skip_rate() {
+    let app = TestApp::spawn().await;
+
+    let body = app
+        .get("/api/validators/4UqPXdXeuYKSFk858y5tjftG5VSfJg3uLuMtPTazBkKh")
+        .await;
+
+    assert_eq!(body["delinquent"], true);
+    assert!(body["version"].is_null());
+    assert_eq!(body["skip_rate"], 100.0);
+    assert_eq!(body["stake_share"], 10.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_vote_account_is_null() {
+    let app = TestApp::spawn().await;
+
+    let body = app.get(&format!("/api/validators/{}", LEADER)).await;
+
+    assert!(body.is_null());
+}
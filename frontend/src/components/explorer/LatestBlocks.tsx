@@ -35,14 +35,14 @@ export function LatestBlocks() {
           ) : (
             data?.blocks.map((block) => (
               <Link
-                key={block.block_number}
-                href={`/block/${block.block_number}`}
+                key={block.slot}
+                href={`/block/${block.slot}`}
                 className="flex items-center justify-between p-3 rounded-lg hover:bg-accent/50 transition-colors"
               >
                 <div>
-                  <div className="font-medium">#{block.block_number}</div>
+                  <div className="font-medium">#{block.slot}</div>
                   <div className="text-sm text-muted-foreground">
-                    {block.transactions_count} txns
+                    {block.skipped ? 'Skipped' : `${block.transactions_count} txns`}
                   </div>
                 </div>
                 <div className="text-right">
@@ -40,7 +40,7 @@ export function TrendingTokens() {
                       {token.name}
                     </div>
                   </div>
-                  {token.price_change_24h >= 0 ? (
+                  {(token.price_change_24h ?? 0) >= 0 ? (
                     <TrendingUp className="h-5 w-5 text-chart-up" />
                   ) : (
                     <TrendingDown className="h-5 w-5 text-chart-down" />
@@ -48,14 +48,14 @@ export function TrendingTokens() {
                 </div>
                 <div className="space-y-1">
                   <div className="text-lg font-bold">
-                    {formatCurrency(token.price)}
+                    {token.price !== null ? formatCurrency(token.price) : '—'}
                   </div>
                   <div
                     className={`text-sm font-medium ${
-                      token.price_change_24h >= 0 ? 'text-chart-up' : 'text-chart-down'
+                      (token.price_change_24h ?? 0) >= 0 ? 'text-chart-up' : 'text-chart-down'
                     }`}
                   >
-                    {formatPercent(token.price_change_24h)}
+                    {token.price_change_24h !== null ? formatPercent(token.price_change_24h) : '—'}
                   </div>
                 </div>
               </Link>
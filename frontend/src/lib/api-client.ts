@@ -88,18 +88,68 @@ export const api = {
     apiClient.get<TransactionsResponse>(`/api/addresses/${address}/transactions?page=${page}&limit=${limit}`),
 
   // Tokens
-  getTokens: (page = 1, limit = 20, sort = 'volume_24h') =>
-    apiClient.get<TokensResponse>(`/api/tokens?page=${page}&limit=${limit}&sort=${sort}`),
+  getTokens: (page = 1, limit = 20, sort: TokenSort = 'volume_24h', filters: TokenFilters = {}) => {
+    const params = new URLSearchParams({ page: String(page), limit: String(limit), sort })
+    Object.entries(filters).forEach(([key, value]) => {
+      if (value !== undefined) params.set(key, String(value))
+    })
+    return apiClient.get<TokensResponse>(`/api/tokens?${params}`)
+  },
   
   getToken: (mint: string) =>
     apiClient.get<TokenDetails>(`/api/tokens/${mint}`),
 
+  getTokenHolders: (mint: string) =>
+    apiClient.get<TokenHolders | null>(`/api/tokens/${mint}/holders`),
+
+  getTokenCandles: (mint: string, interval: CandleInterval = '1h', from?: number, to?: number) => {
+    const params = new URLSearchParams({ interval })
+    if (from !== undefined) params.set('from', String(from))
+    if (to !== undefined) params.set('to', String(to))
+    return apiClient.get<Candles | null>(`/api/tokens/${mint}/candles?${params}`)
+  },
+
+  getTokenRisk: (mint: string) =>
+    apiClient.get<TokenRisk | null>(`/api/tokens/${mint}/risk`),
+
+  getTokenTransfers: (mint: string, page = 1, limit = 20, filters: TransferFilters = {}) => {
+    const params = new URLSearchParams({ page: String(page), limit: String(limit) })
+    Object.entries(filters).forEach(([key, value]) => {
+      if (value !== undefined) params.set(key, String(value))
+    })
+    return apiClient.get<TransfersResponse>(`/api/tokens/${mint}/transfers?${params}`)
+  },
+
   // Markets
-  getMarkets: (page = 1, limit = 20, sort = 'volume_24h') =>
-    apiClient.get<MarketsResponse>(`/api/markets?page=${page}&limit=${limit}&sort=${sort}`),
+  getMarkets: (page = 1, limit = 20, sort = 'volume_24h', filters: MarketFilters = {}) => {
+    const params = new URLSearchParams({ page: String(page), limit: String(limit), sort })
+    Object.entries(filters).forEach(([key, value]) => {
+      if (value !== undefined) params.set(key, String(value))
+    })
+    return apiClient.get<MarketsResponse>(`/api/markets?${params}`)
+  },
   
+  // `pair` is a pool address or `BASE-QUOTE` mints or symbols
   getMarket: (pair: string) =>
-    apiClient.get<MarketDetails>(`/api/markets/${pair}`),
+    apiClient.get<MarketPair | null>(`/api/markets/${pair}`),
+
+  getMarketTrades: (pair: string, page = 1, limit = 20, filters: TradeFilters = {}) => {
+    const params = new URLSearchParams({ page: String(page), limit: String(limit) })
+    Object.entries(filters).forEach(([key, value]) => {
+      if (value !== undefined) params.set(key, String(value))
+    })
+    return apiClient.get<TradesResponse>(`/api/markets/${pair}/trades?${params}`)
+  },
+
+  getMarketQuote: (pair: string, amount: number, input: 'base' | 'quote' = 'base') =>
+    apiClient.get<RouteQuote | null>(`/api/markets/${pair}/quote?amount=${amount}&input=${input}`),
+
+  // NFTs
+  getNft: (mint: string) =>
+    apiClient.get<NftDetails | null>(`/api/nfts/${mint}`),
+
+  getCollection: (collection: string, page = 1, limit = 50) =>
+    apiClient.get<CollectionResponse>(`/api/collections/${collection}?page=${page}&limit=${limit}`),
 
   // Network Stats
   getNetworkStats: () =>
@@ -169,25 +219,39 @@ export const api = {
 }
 
 // Types
+export interface AddressLabel {
+  name: string
+  category: string | null
+  private: boolean
+}
+
+export type AddressLabels = Record<string, AddressLabel>
+
 export interface BlocksResponse {
   blocks: Block[]
   total: number
   page: number
   limit: number
+  labels?: AddressLabels
 }
 
 export interface Block {
-  block_number: number
+  /** Block height; null for skipped slots. */
+  block_number: number | null
   timestamp: number
   leader: string
   transactions_count: number
   slot: number
+  blockhash: string | null
+  parent_slot: number | null
+  skipped: boolean
 }
 
 export interface BlockDetails extends Block {
   parent_slot: number
   blockhash: string
   previous_blockhash: string
+  labels?: AddressLabels
 }
 
 export interface TransactionsResponse {
@@ -199,15 +263,17 @@ export interface TransactionsResponse {
 
 export interface Transaction {
   signature: string
-  block_number: number
+  /** Height of the transaction's block; null when it can't be read. */
+  block_number: number | null
+  slot: number
   timestamp: number
   fee: number
   status: 'success' | 'failed'
   signer: string
+  labels?: AddressLabels
 }
 
 export interface TransactionDetails extends Transaction {
-  slot: number
   instructions: Instruction[]
   token_transfers: TokenTransfer[]
 }
@@ -230,7 +296,10 @@ export interface AddressDetails {
   address: string
   balance: number
   type: string
+  domain?: string | null
+  labels?: AddressLabels
   tokens: TokenBalance[]
+  nfts: NftCollection[]
   transaction_count: number
 }
 
@@ -241,6 +310,8 @@ export interface TokenBalance {
   balance: string
   decimals: number
   usd_value?: number
+  extensions: TokenExtension[]
+  risks: string[]
 }
 
 export interface TokensResponse {
@@ -254,18 +325,134 @@ export interface Token {
   mint: string
   symbol: string
   name: string
-  price: number
-  price_change_24h: number
+  program: string
+  decimals: number
+  logo_uri?: string
+  verified: boolean
+  price: number | null
+  price_change_24h: number | null
   volume_24h: number
-  market_cap: number
-  holders: number
+  liquidity: number
+  market_cap: number | null
+  holders: number | null
+  risk_flags: string[]
+}
+
+export interface RiskFlag {
+  flag: string
+  severity: 'high' | 'medium' | 'low'
+  detail: string
+}
+
+export interface TokenRisk {
+  mint: string
+  verified: boolean
+  score: number
+  flags: RiskFlag[]
+  top_holders_share: number | null
+  liquidity: number
+  first_activity: string | null
+  checked_at: string
+}
+
+export type TokenSort = 'volume_24h' | 'market_cap' | 'price' | 'price_change_24h' | 'holders'
+
+export interface TokenFilters {
+  order?: 'asc' | 'desc'
+  verified?: boolean
+  program?: 'spl-token' | 'token-2022'
+  min_liquidity?: number
 }
 
-export interface TokenDetails extends Token {
+export interface TokenDetails {
+  mint: string
+  program: string
+  supply: string
   decimals: number
-  total_supply: string
+  mint_authority?: string
+  freeze_authority?: string
+  name?: string
+  symbol?: string
+  uri?: string
   logo_uri?: string
   description?: string
+  update_authority?: string
+  metadata_source?: 'metaplex' | 'token-2022'
+  metadata_mutable?: boolean
+  extensions: TokenExtension[]
+}
+
+export interface TokenExtension {
+  extension: string
+  state: Record<string, unknown> | null
+  risky: boolean
+}
+
+export type CandleInterval = '1m' | '5m' | '1h' | '1d'
+
+export interface Candle {
+  bucket: string
+  open: number
+  high: number
+  low: number
+  close: number
+  volume: number
+}
+
+export interface Candles {
+  mint: string
+  interval: CandleInterval
+  from: number
+  to: number
+  candles: Candle[]
+}
+
+export interface TokenTransfer {
+  signature: string
+  instruction_index: number
+  inner_index: number
+  slot: number
+  block_time: string | null
+  kind: 'transfer' | 'mint' | 'burn'
+  mint: string
+  from_account: string | null
+  from_owner: string | null
+  to_account: string | null
+  to_owner: string | null
+  amount: string
+  decimals: number
+  ui_amount: number
+}
+
+export interface TransferFilters {
+  from?: string
+  to?: string
+  min_amount?: number
+  max_amount?: number
+  start_time?: number
+  end_time?: number
+}
+
+export interface TransfersResponse {
+  transfers: TokenTransfer[]
+  total: number
+  page: number
+  limit: number
+}
+
+export interface TokenHolder {
+  address: string
+  owner?: string
+  amount: string
+  ui_amount: number
+  share: number
+}
+
+export interface TokenHolders {
+  mint: string
+  holders: TokenHolder[]
+  holder_count: number | null
+  indexed_at: string | null
 }
 
 export interface MarketsResponse {
@@ -276,19 +463,130 @@ export interface MarketsResponse {
 }
 
 export interface Market {
+  address: string
   pair: string
   base_token: string
   quote_token: string
+  base_mint: string
+  quote_mint: string
+  base_reserve: number
+  quote_reserve: number
   price: number
-  price_change_24h: number
+  price_change_24h: number | null
   volume_24h: number
   liquidity: number
+  fee_rate: number
+  dex: string
+  updated_at: string
+}
+
+export interface MarketFilters {
+  order?: 'asc' | 'desc'
+  dex?: string
+  mint?: string
+  min_liquidity?: number
+}
+
+export interface MarketPair {
+  pair: string
+  base_token: string
+  quote_token: string
+  base_mint: string
+  quote_mint: string
+  best_buy_price: number
+  best_sell_price: number
+  liquidity: number
+  volume_24h: number
+  markets: Market[]
+}
+
+export interface PoolQuote {
+  market: string
   dex: string
+  amount_out: number
+  fee: number
+  price_impact: number
+}
+
+export interface RouteQuote {
+  input_mint: string
+  output_mint: string
+  amount_in: number
+  amount_out: number | null
+  routes: PoolQuote[]
+}
+
+export interface Nft {
+  mint: string
+  name: string
+  symbol: string
+  uri: string
+  image: string | null
+  collection: string | null
+  collection_name: string | null
+  collection_verified: boolean
+  owner: string | null
+  compressed: boolean
+  tree: string | null
+  leaf_index: number | null
+  burned: boolean
+}
+
+export interface NftCollection {
+  collection: string | null
+  name: string | null
+  nfts: Nft[]
+}
+
+export interface NftCreator {
+  address: string
+  verified: boolean
+  share: number
+}
+
+export interface NftAttribute {
+  trait_type: string
+  value: unknown
 }
 
-export interface MarketDetails extends Market {
-  chart_data: ChartData[]
-  recent_trades: Trade[]
+export interface NftOwnership {
+  signature: string
+  slot: number
+  block_time: string | null
+  kind: 'mint' | 'transfer' | 'delegate' | 'burn'
+  from: string | null
+  to: string | null
+}
+
+export interface MerkleTree {
+  address: string
+  max_depth: number
+  max_buffer_size: number
+  authority: string
+  creation_slot: number
+  sequence_number: number
+  leaves: number
+  root: string
+  canopy_depth: number
+}
+
+export interface NftDetails extends Nft {
+  description: string | null
+  attributes: NftAttribute[]
+  update_authority: string | null
+  seller_fee_basis_points: number
+  creators: NftCreator[]
+  delegate: string | null
+  history: NftOwnership[]
+  merkle_tree: MerkleTree | null
+}
+
+export interface CollectionResponse {
+  collection: string
+  nfts: Nft[]
+  total: number
+  page: number
+  limit: number
 }
 
 export interface ChartData {
@@ -301,11 +599,28 @@ export interface ChartData {
 }
 
 export interface Trade {
-  timestamp: number
-  price: number
-  amount: number
-  side: 'buy' | 'sell'
   signature: string
+  market: string
+  slot: number
+  block_time: string | null
+  side: 'buy' | 'sell'
+  trader: string
+  base_amount: number
+  quote_amount: number
+  price: number
+  volume_usd: number | null
+}
+
+export interface TradesResponse {
+  trades: Trade[]
+  total: number
+  page: number
+  limit: number
+}
+
+export interface TradeFilters {
+  trader?: string
+  side?: 'buy' | 'sell'
 }
 
 export interface NetworkStats {
@@ -317,9 +632,24 @@ export interface NetworkStats {
   epoch_progress: number
 }
 
+export type SearchResultType =
+  | 'block'
+  | 'transaction'
+  | 'address'
+  | 'token'
+  | 'program'
+  | 'label'
+  | 'domain'
+
+export interface SearchMatch {
+  type: SearchResultType
+  result: unknown
+}
+
 export interface SearchResults {
-  type: 'block' | 'transaction' | 'address' | 'token' | 'unknown'
+  type: SearchResultType | 'unknown'
   result?: unknown
+  candidates: SearchMatch[]
 }
 
 export interface AuthResponse {